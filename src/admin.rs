@@ -0,0 +1,362 @@
+//! Administrative routes for capturing and restoring snapshots of the database,
+//! allowing test suites to return the server to a known state between tests
+//! without restarting it, and for managing the injected faults
+
+use crate::{
+    database::{DbHandle, DbResult},
+    fault::{FaultRule, FaultStore, StoredFaultRule},
+    handlers::{
+        access::authorize_identity,
+        error::{
+            AwsError, IntoErrorResponse, InvalidParameterException, InvalidRequestException,
+            ResourceNotFoundException,
+        },
+    },
+    middleware::aws_sig_v4::AuthenticatedIdentity,
+};
+use axum::{
+    Extension, Json, Router,
+    body::Bytes,
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, post},
+};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tempfile::NamedTempFile;
+use tokio_rusqlite::rusqlite::{self, Connection, backup::Backup, ffi};
+
+/// Maximum length of a snapshot name
+const MAX_SNAPSHOT_NAME_LENGTH: usize = 64;
+
+/// Create the router for the administrative routes, the routes expect the
+/// [DbHandle], [SnapshotStore], [FaultStore] and [AuthenticatedIdentity] extensions
+pub fn admin_router() -> Router {
+    Router::new()
+        .route("/_loker/snapshot/{name}", post(create_snapshot))
+        .route("/_loker/restore/{name}", post(restore_snapshot))
+        .route("/_loker/reset", post(reset))
+        .route(
+            "/_loker/faults",
+            post(create_fault).get(list_faults).delete(clear_faults),
+        )
+        .route("/_loker/faults/{id}", delete(delete_fault))
+}
+
+/// Store of the snapshots captured through the administrative routes along
+/// with the initial snapshot the database is reset to
+#[derive(Clone)]
+pub struct SnapshotStore {
+    /// Encryption key of the database
+    encryption_key: Option<String>,
+    /// Snapshot of the database when the store was created
+    initial: Arc<Mutex<Snapshot>>,
+    /// Snapshots by name
+    snapshots: Arc<Mutex<HashMap<String, Snapshot>>>,
+}
+
+/// Copy of the database contents
+struct Snapshot {
+    db: Connection,
+    /// File the snapshot is stored in, declared after the connection so
+    /// that it is removed once the connection is closed
+    _file: Option<NamedTempFile>,
+}
+
+impl SnapshotStore {
+    /// Create a snapshot store capturing the current state of the `db` as
+    /// the state the database is reset to.
+    ///
+    /// The `encryption_key` is the key the database is encrypted with, [None]
+    /// for unencrypted databases
+    pub async fn create(
+        db: &DbHandle,
+        encryption_key: Option<String>,
+    ) -> Result<SnapshotStore, tokio_rusqlite::Error> {
+        let initial = db
+            .call({
+                let encryption_key = encryption_key.clone();
+                move |db| capture_snapshot(db, encryption_key.as_deref())
+            })
+            .await?;
+
+        Ok(SnapshotStore {
+            encryption_key,
+            initial: Arc::new(Mutex::new(initial)),
+            snapshots: Default::default(),
+        })
+    }
+
+    /// Capture the current state of the `db` as the snapshot `name`, replacing
+    /// any existing snapshot with the same name
+    fn snapshot(&self, db: &Connection, name: String) -> DbResult<()> {
+        let snapshot = capture_snapshot(db, self.encryption_key.as_deref())?;
+
+        self.snapshots
+            .lock()
+            .expect("snapshot store lock poisoned")
+            .insert(name, snapshot);
+
+        Ok(())
+    }
+
+    /// Replace the contents of the `db` with the snapshot `name`, provides false
+    /// when the snapshot doesn't exist
+    fn restore(&self, db: &mut Connection, name: &str) -> DbResult<bool> {
+        let snapshots = self.snapshots.lock().expect("snapshot store lock poisoned");
+
+        let snapshot = match snapshots.get(name) {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        copy_database(&snapshot.db, db)?;
+        Ok(true)
+    }
+
+    /// Replace the contents of the `db` with the initial snapshot
+    fn reset(&self, db: &mut Connection) -> DbResult<()> {
+        let initial = self.initial.lock().expect("snapshot store lock poisoned");
+        copy_database(&initial.db, db)
+    }
+}
+
+/// Capture a snapshot of the `db`.
+///
+/// SQLCipher doesn't support backups between encrypted and unencrypted databases
+/// so snapshots of encrypted databases are stored in temporary files encrypted
+/// using the same `encryption_key`, other snapshots are stored in memory
+fn capture_snapshot(db: &Connection, encryption_key: Option<&str>) -> DbResult<Snapshot> {
+    let (mut snapshot_db, file) = match encryption_key {
+        Some(encryption_key) => {
+            let file = snapshot_file(db)?;
+            let snapshot_db = Connection::open(file.path())?;
+            snapshot_db.pragma_update(None, "key", encryption_key)?;
+            (snapshot_db, Some(file))
+        }
+        None => (Connection::open_in_memory()?, None),
+    };
+
+    copy_database(db, &mut snapshot_db)?;
+
+    Ok(Snapshot {
+        db: snapshot_db,
+        _file: file,
+    })
+}
+
+/// Create a temporary file to store a snapshot of the `db` in, the file is created
+/// with a random name only readable by the current user in the directory of the
+/// database (or the system temporary directory for in memory databases)
+fn snapshot_file(db: &Connection) -> DbResult<NamedTempFile> {
+    let directory = match db.path().filter(|path| !path.is_empty()) {
+        Some(path) => std::path::Path::new(path)
+            .parent()
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .map_or_else(|| PathBuf::from("."), |directory| directory.to_path_buf()),
+        None => std::env::temp_dir(),
+    };
+
+    tempfile::Builder::new()
+        .prefix("loker-snapshot-")
+        .suffix(".db")
+        .tempfile_in(&directory)
+        .map_err(|error| {
+            tracing::error!(?error, ?directory, "failed to create snapshot file");
+            rusqlite::Error::SqliteFailure(
+                ffi::Error::new(ffi::SQLITE_CANTOPEN),
+                Some(format!("failed to create snapshot file: {error}")),
+            )
+        })
+}
+
+/// Replace the contents of the `to` database with the contents of the `from` database
+/// using the SQLite backup API, all pages are copied in a single step so the `to`
+/// database is replaced atomically
+fn copy_database(from: &Connection, to: &mut Connection) -> DbResult<()> {
+    let backup = Backup::new(from, to)?;
+    backup.run_to_completion(i32::MAX, Duration::ZERO, None)
+}
+
+/// Snapshot names may only contain alphanumeric characters, hyphens and underscores
+fn is_valid_snapshot_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_SNAPSHOT_NAME_LENGTH
+        && name
+            .chars()
+            .all(|value| value.is_ascii_alphanumeric() || value == '-' || value == '_')
+}
+
+/// Convert the result of an administrative route into a response
+fn admin_response<R: IntoResponse>(result: Result<R, AwsError>) -> Response {
+    match result {
+        Ok(response) => response.into_response(),
+        Err(error) => error.into_error_response(),
+    }
+}
+
+/// POST /_loker/snapshot/{name}
+async fn create_snapshot(
+    Extension(db): Extension<DbHandle>,
+    Extension(snapshots): Extension<SnapshotStore>,
+    Extension(identity): Extension<AuthenticatedIdentity>,
+    Path(name): Path<String>,
+) -> Response {
+    admin_response(
+        async move {
+            authorize_identity(&identity, "loker:CreateSnapshot", "*")?;
+
+            if !is_valid_snapshot_name(&name) {
+                return Err(InvalidParameterException.into());
+            }
+
+            db.call(move |db| snapshots.snapshot(db, name))
+                .await
+                .inspect_err(|error| tracing::error!(?error, "failed to capture snapshot"))?;
+
+            Ok::<_, AwsError>(StatusCode::NO_CONTENT)
+        }
+        .await,
+    )
+}
+
+/// POST /_loker/restore/{name}
+async fn restore_snapshot(
+    Extension(db): Extension<DbHandle>,
+    Extension(snapshots): Extension<SnapshotStore>,
+    Extension(identity): Extension<AuthenticatedIdentity>,
+    Path(name): Path<String>,
+) -> Response {
+    admin_response(
+        async move {
+            authorize_identity(&identity, "loker:RestoreSnapshot", "*")?;
+
+            let restored = db
+                .call(move |db| snapshots.restore(db, &name))
+                .await
+                .inspect_err(|error| tracing::error!(?error, "failed to restore snapshot"))?;
+
+            if !restored {
+                return Err(ResourceNotFoundException.into());
+            }
+
+            Ok::<_, AwsError>(StatusCode::NO_CONTENT)
+        }
+        .await,
+    )
+}
+
+/// POST /_loker/reset
+async fn reset(
+    Extension(db): Extension<DbHandle>,
+    Extension(snapshots): Extension<SnapshotStore>,
+    Extension(identity): Extension<AuthenticatedIdentity>,
+) -> Response {
+    admin_response(
+        async move {
+            authorize_identity(&identity, "loker:ResetDatabase", "*")?;
+
+            db.call(move |db| snapshots.reset(db))
+                .await
+                .inspect_err(|error| tracing::error!(?error, "failed to reset database"))?;
+
+            Ok::<_, AwsError>(StatusCode::NO_CONTENT)
+        }
+        .await,
+    )
+}
+
+#[derive(Serialize)]
+struct CreateFaultResponse {
+    id: String,
+}
+
+#[derive(Serialize)]
+struct ListFaultsResponse {
+    faults: Vec<StoredFaultRule>,
+}
+
+/// POST /_loker/faults
+async fn create_fault(
+    Extension(faults): Extension<FaultStore>,
+    Extension(identity): Extension<AuthenticatedIdentity>,
+    body: Bytes,
+) -> Response {
+    admin_response(
+        async move {
+            authorize_identity(&identity, "loker:CreateFault", "*")?;
+
+            let rule: FaultRule = serde_json::from_slice(&body).map_err(|error| {
+                tracing::debug!(?error, "failed to parse fault rule");
+                InvalidRequestException
+            })?;
+
+            if !rule.is_valid() {
+                return Err(InvalidParameterException.into());
+            }
+
+            let id = faults.add(rule);
+            Ok::<_, AwsError>(Json(CreateFaultResponse { id }))
+        }
+        .await,
+    )
+}
+
+/// GET /_loker/faults
+async fn list_faults(
+    Extension(faults): Extension<FaultStore>,
+    Extension(identity): Extension<AuthenticatedIdentity>,
+) -> Response {
+    admin_response(
+        async move {
+            authorize_identity(&identity, "loker:ListFaults", "*")?;
+
+            Ok::<_, AwsError>(Json(ListFaultsResponse {
+                faults: faults.list(),
+            }))
+        }
+        .await,
+    )
+}
+
+/// DELETE /_loker/faults/{id}
+async fn delete_fault(
+    Extension(faults): Extension<FaultStore>,
+    Extension(identity): Extension<AuthenticatedIdentity>,
+    Path(id): Path<String>,
+) -> Response {
+    admin_response(
+        async move {
+            authorize_identity(&identity, "loker:DeleteFault", "*")?;
+
+            if !faults.remove(&id) {
+                return Err(ResourceNotFoundException.into());
+            }
+
+            Ok::<_, AwsError>(StatusCode::NO_CONTENT)
+        }
+        .await,
+    )
+}
+
+/// DELETE /_loker/faults
+async fn clear_faults(
+    Extension(faults): Extension<FaultStore>,
+    Extension(identity): Extension<AuthenticatedIdentity>,
+) -> Response {
+    admin_response(
+        async move {
+            authorize_identity(&identity, "loker:DeleteFault", "*")?;
+
+            faults.clear();
+            Ok::<_, AwsError>(StatusCode::NO_CONTENT)
+        }
+        .await,
+    )
+}
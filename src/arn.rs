@@ -0,0 +1,84 @@
+/// Default partition used in generated ARNs
+pub const DEFAULT_PARTITION: &str = "aws";
+
+/// Default region used in generated ARNs when the request doesn't specify a region
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+/// Default account ID used in generated ARNs
+pub const DEFAULT_ACCOUNT_ID: &str = "000000000000";
+
+/// Partition, region and account used when generating ARNs
+#[derive(Debug, Clone)]
+pub struct ArnConfig {
+    /// Partition of the ARNs (i.e aws, aws-cn, aws-us-gov)
+    pub partition: String,
+    /// Region used when the request doesn't specify a region
+    pub region: String,
+    /// 12 digit ID of the account owning the resources
+    pub account_id: String,
+}
+
+impl Default for ArnConfig {
+    fn default() -> Self {
+        Self {
+            partition: DEFAULT_PARTITION.to_string(),
+            region: DEFAULT_REGION.to_string(),
+            account_id: DEFAULT_ACCOUNT_ID.to_string(),
+        }
+    }
+}
+
+impl ArnConfig {
+    /// Get the region to use for a request that was signed for `region`, falls back
+    /// to the configured region when the request region is empty
+    pub fn request_region(&self, region: &str) -> String {
+        if region.is_empty() {
+            self.region.clone()
+        } else {
+            region.to_string()
+        }
+    }
+
+    /// Get the prefix of the ARNs for secrets in `region`
+    /// (arn:<partition>:secretsmanager:<region>:<account-id>:secret:)
+    pub fn secret_arn_prefix(&self, region: &str) -> String {
+        format!(
+            "arn:{}:secretsmanager:{region}:{}:secret:",
+            self.partition, self.account_id
+        )
+    }
+
+    /// Get the ARN of an IAM `resource` (i.e root, user/name) in the account
+    pub fn iam_arn(&self, resource: &str) -> String {
+        format!("arn:{}:iam::{}:{resource}", self.partition, self.account_id)
+    }
+
+    /// Get the ARN of the KMS key with the `key_id` in `region`
+    /// (arn:<partition>:kms:<region>:<account-id>:key/<key-id>)
+    pub fn kms_key_arn(&self, region: &str, key_id: &str) -> String {
+        format!(
+            "arn:{}:kms:{region}:{}:key/{key_id}",
+            self.partition, self.account_id
+        )
+    }
+
+    /// Get the region of a secret from its `arn`
+    pub fn secret_arn_region(arn: &str) -> &str {
+        arn.split(':').nth(3).unwrap_or_default()
+    }
+
+    /// Get the ARN of the secret with the `arn` as it appears in `region`, replicas
+    /// share the ARN of their primary secret with only the region changed
+    pub fn secret_arn_in_region(arn: &str, region: &str) -> String {
+        let mut parts: Vec<&str> = arn.split(':').collect();
+        if let Some(arn_region) = parts.get_mut(3) {
+            *arn_region = region;
+        }
+        parts.join(":")
+    }
+
+    /// Check if the provided account ID is a valid 12 digit account ID
+    pub fn is_valid_account_id(account_id: &str) -> bool {
+        account_id.len() == 12 && account_id.chars().all(|char| char.is_ascii_digit())
+    }
+}
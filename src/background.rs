@@ -1,9 +1,14 @@
-use crate::database::{
-    DbHandle,
-    secrets::{delete_excess_secret_versions, delete_scheduled_secrets},
+use crate::{
+    database::{
+        DbHandle,
+        secrets::{delete_excess_secret_versions, delete_scheduled_secrets},
+    },
+    metrics::Metrics,
+    rotation::RotationRunner,
 };
 use chrono::Utc;
 use futures::StreamExt;
+use std::time::Instant;
 use tokio_simple_fixed_scheduler::{SchedulerEventStream, SchedulerQueueEvent};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
@@ -14,9 +19,23 @@ pub enum BackgroundEvent {
     /// Task to prune the secrets with versions in excess of 100 versions that are
     /// over 24h old
     PurgeExcessSecrets,
+
+    /// Task to rotate secrets that have reached their next rotation date
+    ScheduledRotations,
+}
+
+impl BackgroundEvent {
+    /// Name of the task used when recording metrics
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackgroundEvent::PurgeDeletedSecrets => "purge_deleted_secrets",
+            BackgroundEvent::PurgeExcessSecrets => "purge_excess_secrets",
+            BackgroundEvent::ScheduledRotations => "scheduled_rotations",
+        }
+    }
 }
 
-pub async fn perform_background_tasks(db: DbHandle) {
+pub async fn perform_background_tasks(db: DbHandle, rotation: RotationRunner, metrics: Metrics) {
     let events = vec![
         SchedulerQueueEvent {
             event: BackgroundEvent::PurgeDeletedSecrets,
@@ -26,31 +45,56 @@ pub async fn perform_background_tasks(db: DbHandle) {
             event: BackgroundEvent::PurgeExcessSecrets,
             interval: 60 * 60,
         },
+        SchedulerQueueEvent {
+            event: BackgroundEvent::ScheduledRotations,
+            interval: 60,
+        },
     ];
 
     let mut events = SchedulerEventStream::new(events);
 
     while let Some(event) = events.next().await {
-        match event {
+        let start = Instant::now();
+
+        let success = match event {
             BackgroundEvent::PurgeDeletedSecrets => {
                 tracing::debug!("performing background purge for presigned tasks");
                 let now = Utc::now();
 
-                if let Err(error) = db.call(move |db| delete_scheduled_secrets(db, now)).await {
+                let result = db.call(move |db| delete_scheduled_secrets(db, now)).await;
+                if let Err(error) = &result {
                     tracing::error!(?error, "failed to performed scheduled secrets deletion")
                 }
+
+                result.is_ok()
             }
 
             BackgroundEvent::PurgeExcessSecrets => {
                 tracing::debug!("performing background deletion for secret version limits");
 
-                if let Err(error) = db.call(move |db| delete_excess_secret_versions(db)).await {
+                let result = db.call(move |db| delete_excess_secret_versions(db)).await;
+                if let Err(error) = &result {
                     tracing::error!(
                         ?error,
                         "failed to performed background deletion for secret version limits"
                     )
                 }
+
+                result.is_ok()
             }
-        }
+
+            BackgroundEvent::ScheduledRotations => {
+                tracing::debug!("performing background rotation for due secrets");
+
+                let result = rotation.rotate_due_secrets(&db).await;
+                if let Err(error) = &result {
+                    tracing::error!(?error, "failed to perform background secret rotation")
+                }
+
+                result.is_ok()
+            }
+        };
+
+        metrics.record_background_task(event.name(), success, start.elapsed());
     }
 }
@@ -0,0 +1,382 @@
+//! Administrative commands for inspecting, exporting and importing the database
+//! without starting the server
+
+use crate::{
+    config::{ConfigError, DatabaseConfig},
+    database::{
+        self, CreateDatabaseError, DbHandle, MEMORY_DATABASE_PATH,
+        secrets::{get_secret_by_version_stage, get_secret_regions, get_secrets_by_filter},
+        transaction,
+    },
+    dump::{DumpError, SecretsDump, export_secrets, import_secrets},
+    kms::decrypt_secret_value,
+};
+use std::{collections::HashMap, path::Path};
+use thiserror::Error;
+
+/// Usage information for the commands
+pub const USAGE: &str = "\
+Usage: loker [COMMAND]
+
+Commands:
+  server                       Start the server (default)
+  export [--out <path>]        Export all secrets as JSON to <path> or stdout
+  import <path>                Import secrets from a JSON export
+  list [--region <region>]     List the secrets in the database
+  get <name> [--region <region>] [--version-stage <stage>]
+                               Print the value of a secret
+  help                         Print this help information
+
+The database is opened using the SM_ENCRYPTION_KEY and SM_DATABASE_PATH settings";
+
+/// Number of secrets loaded at a time while listing
+const LIST_PAGE_SIZE: i64 = 100;
+
+/// Command to run, parsed from the command line arguments
+pub enum Command {
+    /// Start the server
+    Server,
+    /// Export all secrets to the file at `out` or stdout
+    Export { out: Option<String> },
+    /// Import the secrets from the export at `path`
+    Import { path: String },
+    /// List the secrets in `region` or all regions
+    List { region: Option<String> },
+    /// Print the value of the secret `name`
+    Get {
+        name: String,
+        region: Option<String>,
+        version_stage: Option<String>,
+    },
+    /// Print the usage information
+    Help,
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("unknown command \"{0}\"")]
+    UnknownCommand(String),
+
+    #[error("unknown option \"{0}\"")]
+    UnknownOption(String),
+
+    #[error("option \"{0}\" requires a value")]
+    MissingOptionValue(String),
+
+    #[error("missing required argument <{0}>")]
+    MissingArgument(&'static str),
+
+    #[error("unexpected argument \"{0}\"")]
+    UnexpectedArgument(String),
+
+    #[error("database file {0} does not exist")]
+    MissingDatabase(String),
+
+    #[error("commands cannot be used with an in-memory database")]
+    MemoryDatabase,
+
+    #[error("secret {0} was not found")]
+    SecretNotFound(String),
+
+    #[error("failed to read export file: {0}")]
+    ReadExport(std::io::Error),
+
+    #[error("export file is not valid: {0}")]
+    InvalidExport(serde_json::Error),
+
+    #[error("failed to serialize export: {0}")]
+    SerializeExport(serde_json::Error),
+
+    #[error("failed to write export file: {0}")]
+    WriteExport(std::io::Error),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    CreateDatabase(#[from] CreateDatabaseError),
+
+    #[error(transparent)]
+    Db(#[from] tokio_rusqlite::Error<DumpError>),
+}
+
+/// Arguments provided to a command split into the positional arguments
+/// and the `--name value` options
+struct Arguments {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Arguments {
+    /// Parse the `args` allowing only the options in `allowed_options`
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+        allowed_options: &[&str],
+    ) -> Result<Arguments, CliError> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                positional.push(arg);
+                continue;
+            }
+
+            if !allowed_options.contains(&arg.as_str()) {
+                return Err(CliError::UnknownOption(arg));
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| CliError::MissingOptionValue(arg.clone()))?;
+            options.insert(arg, value);
+        }
+
+        Ok(Arguments {
+            positional,
+            options,
+        })
+    }
+
+    /// Take the next positional argument named `name`
+    fn required(&mut self, name: &'static str) -> Result<String, CliError> {
+        if self.positional.is_empty() {
+            return Err(CliError::MissingArgument(name));
+        }
+
+        Ok(self.positional.remove(0))
+    }
+
+    /// Take the value of the `option`
+    fn option(&mut self, option: &str) -> Option<String> {
+        self.options.remove(option)
+    }
+
+    /// Ensure all the positional arguments were used
+    fn finish(self) -> Result<(), CliError> {
+        match self.positional.into_iter().next() {
+            Some(arg) => Err(CliError::UnexpectedArgument(arg)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parse the command from the command line `args` (excluding the program name),
+/// the server is started when no command is provided
+pub fn parse_command(mut args: impl Iterator<Item = String>) -> Result<Command, CliError> {
+    let command = match args.next() {
+        Some(value) => value,
+        None => return Ok(Command::Server),
+    };
+
+    let command = match command.as_str() {
+        "server" => {
+            Arguments::parse(args, &[])?.finish()?;
+            Command::Server
+        }
+        "export" => {
+            let mut args = Arguments::parse(args, &["--out"])?;
+            let out = args.option("--out");
+            args.finish()?;
+            Command::Export { out }
+        }
+        "import" => {
+            let mut args = Arguments::parse(args, &[])?;
+            let path = args.required("path")?;
+            args.finish()?;
+            Command::Import { path }
+        }
+        "list" => {
+            let mut args = Arguments::parse(args, &["--region"])?;
+            let region = args.option("--region");
+            args.finish()?;
+            Command::List { region }
+        }
+        "get" => {
+            let mut args = Arguments::parse(args, &["--region", "--version-stage"])?;
+            let name = args.required("name")?;
+            let region = args.option("--region");
+            let version_stage = args.option("--version-stage");
+            args.finish()?;
+            Command::Get {
+                name,
+                region,
+                version_stage,
+            }
+        }
+        "help" | "--help" | "-h" => Command::Help,
+        other => return Err(CliError::UnknownCommand(other.to_string())),
+    };
+
+    Ok(command)
+}
+
+/// Run an administrative `command`
+pub async fn run_command(command: Command) -> Result<(), CliError> {
+    match command {
+        // Server is started by the caller
+        Command::Server => Ok(()),
+        Command::Help => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Command::Export { out } => export(out).await,
+        Command::Import { path } => import(path).await,
+        Command::List { region } => list(region).await,
+        Command::Get {
+            name,
+            region,
+            version_stage,
+        } => get(name, region, version_stage).await,
+    }
+}
+
+/// Open the existing database using the config from the environment
+async fn open_database() -> Result<(DbHandle, DatabaseConfig), CliError> {
+    let config = DatabaseConfig::from_env()?;
+
+    // Commands only operate on existing databases
+    if config.database_path == MEMORY_DATABASE_PATH {
+        return Err(CliError::MemoryDatabase);
+    }
+
+    if !Path::new(&config.database_path).exists() {
+        return Err(CliError::MissingDatabase(config.database_path));
+    }
+
+    let db = database::create_database(
+        config.encryption_key.clone(),
+        None,
+        config.database_path.clone(),
+    )
+    .await?;
+
+    Ok((db.into(), config))
+}
+
+async fn export(out: Option<String>) -> Result<(), CliError> {
+    let (db, _config) = open_database().await?;
+
+    let dump = db.call(|db| export_secrets(db)).await?;
+    let value = serde_json::to_string_pretty(&dump).map_err(CliError::SerializeExport)?;
+
+    match out {
+        Some(out) => {
+            std::fs::write(&out, value).map_err(CliError::WriteExport)?;
+            eprintln!("exported {} secrets to {out}", dump.secrets.len());
+        }
+        None => println!("{value}"),
+    }
+
+    Ok(())
+}
+
+async fn import(path: String) -> Result<(), CliError> {
+    let value = std::fs::read_to_string(&path).map_err(CliError::ReadExport)?;
+    let dump: SecretsDump = serde_json::from_str(&value).map_err(CliError::InvalidExport)?;
+
+    let (db, _config) = open_database().await?;
+
+    let summary = db
+        .call(move |db| transaction(db, move |t| import_secrets(t, dump)))
+        .await?;
+
+    eprintln!(
+        "imported {path}, created {} secrets and {} versions",
+        summary.secrets_created, summary.versions_created
+    );
+
+    Ok(())
+}
+
+async fn list(region: Option<String>) -> Result<(), CliError> {
+    let (db, _config) = open_database().await?;
+
+    let secrets = db
+        .call(move |db| {
+            let regions = match region {
+                Some(region) => vec![region],
+                None => get_secret_regions(db)?,
+            };
+
+            let mut secrets = Vec::new();
+
+            for region in regions {
+                let mut offset = 0;
+
+                loop {
+                    let page = get_secrets_by_filter(
+                        db,
+                        &region,
+                        &[],
+                        true,
+                        LIST_PAGE_SIZE,
+                        offset,
+                        true,
+                    )?;
+                    let is_last_page = (page.len() as i64) < LIST_PAGE_SIZE;
+                    offset += LIST_PAGE_SIZE;
+
+                    secrets.extend(page.into_iter().map(|secret| {
+                        let is_scheduled_delete = secret.scheduled_delete_at.is_some();
+                        (region.clone(), secret.name, secret.arn, is_scheduled_delete)
+                    }));
+
+                    if is_last_page {
+                        break;
+                    }
+                }
+            }
+
+            Ok::<_, DumpError>(secrets)
+        })
+        .await?;
+
+    for (region, name, arn, is_scheduled_delete) in secrets {
+        if is_scheduled_delete {
+            println!("{region}\t{name}\t{arn}\t(scheduled for deletion)");
+        } else {
+            println!("{region}\t{name}\t{arn}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn get(
+    name: String,
+    region: Option<String>,
+    version_stage: Option<String>,
+) -> Result<(), CliError> {
+    let (db, config) = open_database().await?;
+
+    let region = region.unwrap_or(config.region);
+    let version_stage = version_stage.unwrap_or_else(|| "AWSCURRENT".to_string());
+
+    let secret = db
+        .call({
+            let name = name.clone();
+            move |db| {
+                let mut secret =
+                    match get_secret_by_version_stage(db, &region, &name, &version_stage)? {
+                        Some(value) => value,
+                        None => return Ok(None),
+                    };
+
+                decrypt_secret_value(db, &mut secret)
+                    .map_err(|_| DumpError::Decrypt(name, secret.version_id.clone()))?;
+
+                Ok::<_, DumpError>(Some(secret))
+            }
+        })
+        .await?
+        .ok_or(CliError::SecretNotFound(name))?;
+
+    if let Some(secret_string) = secret.secret_string {
+        println!("{secret_string}");
+    } else if let Some(secret_binary) = secret.secret_binary {
+        println!("{secret_binary}");
+    }
+
+    Ok(())
+}
@@ -1,6 +1,21 @@
+use crate::{
+    arn::{ArnConfig, DEFAULT_ACCOUNT_ID, DEFAULT_PARTITION, DEFAULT_REGION},
+    database::MEMORY_DATABASE_PATH,
+    middleware::{aws_sig_v4::AccessKey, replay::DEFAULT_REPLAY_CACHE_SIZE},
+    policy::{PolicyDocument, PolicyKind},
+    seed::SeedMode,
+    throttle::default_throttle_limits,
+    utils::date::chrono_to_system_time,
+};
 use aws_credential_types::Credentials;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::path::Path;
+use std::sync::Arc;
 use thiserror::Error;
+use uuid::Uuid;
 
 /// Default server address when not specified (HTTP)
 const DEFAULT_SERVER_ADDRESS_HTTP: SocketAddr =
@@ -11,9 +26,12 @@ const DEFAULT_SERVER_ADDRESS_HTTPS: SocketAddr =
     SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 8443));
 
 pub struct Config {
-    /// Encryption key to encrypt and decrypt the database
-    pub encryption_key: String,
-    /// Path to the server database file
+    /// Encryption key to encrypt and decrypt the database, [None] when
+    /// using an in-memory database
+    pub encryption_key: Option<String>,
+    /// New encryption key to rekey the database with on startup
+    pub new_encryption_key: Option<String>,
+    /// Path to the server database file or [MEMORY_DATABASE_PATH]
     pub database_path: String,
 
     /// Server address to bind against
@@ -26,8 +44,35 @@ pub struct Config {
     /// Path to the HTTPS private key file
     pub private_key_path: String,
 
-    /// Credentials for AWS SigV4
-    pub credentials: Credentials,
+    /// Access keys accepted for AWS SigV4
+    pub access_keys: Vec<AccessKey>,
+
+    /// Mapping from rotation lambda ARNs to the webhook URL to invoke instead
+    pub rotation_lambdas: HashMap<String, String>,
+
+    /// Partition, region and account used when generating ARNs
+    pub arn: ArnConfig,
+
+    /// IDs of the KMS keys to create in the default region on startup
+    pub kms_keys: Vec<String>,
+
+    /// Path to the seed file to apply on startup
+    pub seed_file: Option<String>,
+    /// How the seed file is applied
+    pub seed_mode: SeedMode,
+
+    /// Requests per second allowed for each target, [None] when throttling is disabled
+    pub throttle_limits: Option<HashMap<String, u32>>,
+
+    /// Whether signature errors include the expected canonical request and string to sign
+    pub debug_signatures: bool,
+
+    /// Number of signatures remembered to reject replayed requests, [None] when
+    /// replay protection is disabled
+    pub replay_cache_size: Option<usize>,
+
+    /// Whether the unauthenticated metrics route is served
+    pub metrics: bool,
 }
 
 #[derive(Debug, Error)]
@@ -35,7 +80,13 @@ pub enum ConfigError {
     #[error("Must specify SM_ENCRYPTION_KEY environment variable")]
     MissingEncryptionKey,
 
-    #[error("Must specify SM_ACCESS_KEY_ID environment variable")]
+    #[error("SM_NEW_ENCRYPTION_KEY must be different from SM_ENCRYPTION_KEY")]
+    SameNewEncryptionKey,
+
+    #[error("SM_NEW_ENCRYPTION_KEY cannot be used with an in-memory database")]
+    MemoryDatabaseRekey,
+
+    #[error("Must specify SM_ACCESS_KEY_ID or SM_CREDENTIALS_PATH environment variable")]
     MissingAccessKeyId,
 
     #[error("Must specify SM_ACCESS_KEY_SECRET environment variable")]
@@ -43,65 +94,681 @@ pub enum ConfigError {
 
     #[error("SM_USE_HTTPS must be either true or false")]
     InvalidUseHttps,
+
+    #[error("Failed to read identity policy file {0}: {1}")]
+    ReadIdentityPolicy(String, std::io::Error),
+
+    #[error("Identity policy file {0} is not a valid identity policy")]
+    InvalidIdentityPolicy(String),
+
+    #[error("Failed to read SM_CREDENTIALS_PATH file: {0}")]
+    ReadCredentials(std::io::Error),
+
+    #[error("SM_CREDENTIALS_PATH file is not valid: {0}")]
+    InvalidCredentials(String),
+
+    #[error("Access key {0} is defined more than once")]
+    DuplicateAccessKey(String),
+
+    #[error("SM_ROTATION_LAMBDAS must be a comma separated list of <lambda-arn>=<webhook-url>")]
+    InvalidRotationLambdas,
+
+    #[error("SM_ACCOUNT_ID must be a 12 digit account ID")]
+    InvalidAccountId,
+
+    #[error("SM_KMS_KEYS must be a comma separated list of UUID key IDs")]
+    InvalidKmsKeys,
+
+    #[error("SM_SEED_MODE must be one of create_if_missing, overwrite or reset")]
+    InvalidSeedMode,
+
+    #[error("SM_THROTTLING must be either true or false")]
+    InvalidThrottling,
+
+    #[error(
+        "SM_THROTTLE_LIMITS must be a comma separated list of <target>=<requests-per-second> with limits above zero"
+    )]
+    InvalidThrottleLimits,
+
+    #[error("SM_DEBUG_SIGNATURES must be either true or false")]
+    InvalidDebugSignatures,
+
+    #[error("SM_REPLAY_PROTECTION must be either true or false")]
+    InvalidReplayProtection,
+
+    #[error("SM_REPLAY_CACHE_SIZE must be a number above zero")]
+    InvalidReplayCacheSize,
+
+    #[error("SM_METRICS must be either true or false")]
+    InvalidMetrics,
+
+    #[error("Only one of {0} and {0}_FILE can be specified")]
+    ConflictingVariable(String),
+
+    #[error("Failed to read {0} file: {1}")]
+    ReadVariableFile(String, std::io::Error),
+
+    #[error("{0} file is empty")]
+    EmptyVariableFile(String),
+
+    #[error("Failed to read SM_CONFIG_PATH file: {0}")]
+    ReadConfigFile(std::io::Error),
+
+    #[error("SM_CONFIG_PATH file is not valid: {0}")]
+    InvalidConfigFile(String),
 }
 
 impl Config {
-    /// Load the config from the environment variables
+    /// Load the config from the environment variables and the optional config file.
+    ///
+    /// Each setting is loaded from the first of the following that is set:
+    /// - The environment variable (i.e `SM_ENCRYPTION_KEY`)
+    /// - The file named by the `_FILE` environment variable (i.e `SM_ENCRYPTION_KEY_FILE`)
+    /// - The config file at `SM_CONFIG_PATH`
+    /// - The default value
     pub fn from_env() -> Result<Config, ConfigError> {
-        let encryption_key =
-            std::env::var("SM_ENCRYPTION_KEY").map_err(|_| ConfigError::MissingEncryptionKey)?;
+        let file = load_config_file_from_env()?;
+
+        let database_path = env_var("SM_DATABASE_PATH")?
+            .or(file.database_path)
+            .unwrap_or_else(|| "secrets.db".to_string());
+
+        let new_encryption_key = env_var("SM_NEW_ENCRYPTION_KEY")?.or(file.new_encryption_key);
 
-        let access_key_id =
-            std::env::var("SM_ACCESS_KEY_ID").map_err(|_| ConfigError::MissingAccessKeyId)?;
+        // In-memory databases are not encrypted so don't require an encryption key
+        let encryption_key = if database_path == MEMORY_DATABASE_PATH {
+            if new_encryption_key.is_some() {
+                return Err(ConfigError::MemoryDatabaseRekey);
+            }
 
-        let access_key_secret = std::env::var("SM_ACCESS_KEY_SECRET")
-            .map_err(|_| ConfigError::MissingAccessKeySecret)?;
+            None
+        } else {
+            let encryption_key = env_var("SM_ENCRYPTION_KEY")?
+                .or(file.encryption_key)
+                .ok_or(ConfigError::MissingEncryptionKey)?;
 
-        let credentials = Credentials::new(
-            access_key_id,
-            access_key_secret,
-            None,
-            None,
-            "sm-credentials",
-        );
+            if new_encryption_key
+                .as_ref()
+                .is_some_and(|new_encryption_key| new_encryption_key.eq(&encryption_key))
+            {
+                return Err(ConfigError::SameNewEncryptionKey);
+            }
 
-        let database_path =
-            std::env::var("SM_DATABASE_PATH").unwrap_or_else(|_| "secrets.db".to_string());
+            Some(encryption_key)
+        };
+
+        let account_id = env_var("SM_ACCOUNT_ID")?
+            .or(file.account_id)
+            .unwrap_or_else(|| DEFAULT_ACCOUNT_ID.to_string());
+
+        if !ArnConfig::is_valid_account_id(&account_id) {
+            return Err(ConfigError::InvalidAccountId);
+        }
+
+        let arn = ArnConfig {
+            partition: env_var("SM_PARTITION")?
+                .or(file.partition)
+                .unwrap_or_else(|| DEFAULT_PARTITION.to_string()),
+            region: env_var("SM_REGION")?
+                .or(file.region)
+                .unwrap_or_else(|| DEFAULT_REGION.to_string()),
+            account_id,
+        };
 
-        let use_https = match std::env::var("SM_USE_HTTPS") {
-            Ok(value) => value
+        let mut access_keys = match env_var("SM_CREDENTIALS_PATH")?.or(file.credentials_path) {
+            Some(path) => load_credentials_file(&path, &arn)?,
+            None => Vec::new(),
+        };
+
+        match env_var("SM_ACCESS_KEY_ID")?.or(file.access_key_id) {
+            Some(access_key_id) => {
+                let access_key_secret = env_var("SM_ACCESS_KEY_SECRET")?
+                    .or(file.access_key_secret)
+                    .ok_or(ConfigError::MissingAccessKeySecret)?;
+
+                let credentials = Credentials::new(
+                    access_key_id,
+                    access_key_secret,
+                    None,
+                    None,
+                    "sm-credentials",
+                );
+
+                let policy = match env_var("SM_IDENTITY_POLICY_PATH")?.or(file.identity_policy_path)
+                {
+                    Some(path) => Some(load_identity_policy(&path)?),
+                    None => None,
+                };
+
+                access_keys.push(AccessKey {
+                    policy,
+                    ..AccessKey::new(credentials, &arn)
+                });
+            }
+            // Access keys can come entirely from the credentials file
+            None if !access_keys.is_empty() => {}
+            None => return Err(ConfigError::MissingAccessKeyId),
+        }
+
+        // Ensure the same access key isn't defined multiple times
+        let mut access_key_ids = std::collections::HashSet::new();
+        for access_key in &access_keys {
+            let access_key_id = access_key.credentials.access_key_id();
+            if !access_key_ids.insert(access_key_id) {
+                return Err(ConfigError::DuplicateAccessKey(access_key_id.to_string()));
+            }
+        }
+
+        let use_https = match env_var("SM_USE_HTTPS")? {
+            Some(value) => value
                 .parse::<bool>()
                 .map_err(|_| ConfigError::InvalidUseHttps)?,
-            Err(_) => false,
+            None => file.use_https.unwrap_or_default(),
         };
 
-        let server_address = std::env::var("SM_SERVER_ADDRESS")
-            .ok()
+        let server_address = env_var("SM_SERVER_ADDRESS")?
             .and_then(|value| value.parse::<SocketAddr>().ok())
+            .or(file.server_address)
             .unwrap_or(if use_https {
                 DEFAULT_SERVER_ADDRESS_HTTPS
             } else {
                 DEFAULT_SERVER_ADDRESS_HTTP
             });
 
-        let certificate_path = match std::env::var("SM_HTTPS_CERTIFICATE_PATH") {
-            Ok(value) => value,
-            Err(_) => "sm.cert.pem".to_string(),
+        let certificate_path = env_var("SM_HTTPS_CERTIFICATE_PATH")?
+            .or(file.https_certificate_path)
+            .unwrap_or_else(|| "sm.cert.pem".to_string());
+
+        let private_key_path = env_var("SM_HTTPS_PRIVATE_KEY_PATH")?
+            .or(file.https_private_key_path)
+            .unwrap_or_else(|| "sm.key.pem".to_string());
+
+        let rotation_lambdas = match env_var("SM_ROTATION_LAMBDAS")? {
+            Some(value) => parse_rotation_lambdas(&value)?,
+            None => file.rotation_lambdas.unwrap_or_default(),
+        };
+
+        let kms_keys = match env_var("SM_KMS_KEYS")? {
+            Some(value) => parse_kms_keys(&value)?,
+            None => file
+                .kms_keys
+                .unwrap_or_default()
+                .iter()
+                .map(String::as_str)
+                .map(parse_kms_key_id)
+                .collect::<Result<_, _>>()?,
+        };
+
+        let seed_file = env_var("SM_SEED_FILE")?.or(file.seed_file);
+
+        let seed_mode = match env_var("SM_SEED_MODE")? {
+            Some(value) => SeedMode::parse(&value).ok_or(ConfigError::InvalidSeedMode)?,
+            None => file.seed_mode.unwrap_or_default(),
+        };
+
+        let throttling = match env_var("SM_THROTTLING")? {
+            Some(value) => value
+                .parse::<bool>()
+                .map_err(|_| ConfigError::InvalidThrottling)?,
+            None => file.throttling.unwrap_or_default(),
         };
 
-        let private_key_path = match std::env::var("SM_HTTPS_PRIVATE_KEY_PATH") {
-            Ok(value) => value,
-            Err(_) => "sm.key.pem".to_string(),
+        let throttle_limits = match env_var("SM_THROTTLE_LIMITS")? {
+            Some(value) => parse_throttle_limits(&value)?,
+            None => file.throttle_limits.unwrap_or_default(),
+        };
+
+        if throttle_limits.values().any(|limit| *limit == 0) {
+            return Err(ConfigError::InvalidThrottleLimits);
+        }
+
+        // Configured limits override the defaults for their target
+        let throttle_limits = throttling.then(|| {
+            let mut limits = default_throttle_limits();
+            limits.extend(throttle_limits);
+            limits
+        });
+
+        let debug_signatures = match env_var("SM_DEBUG_SIGNATURES")? {
+            Some(value) => value
+                .parse::<bool>()
+                .map_err(|_| ConfigError::InvalidDebugSignatures)?,
+            None => file.debug_signatures.unwrap_or_default(),
+        };
+
+        let replay_protection = match env_var("SM_REPLAY_PROTECTION")? {
+            Some(value) => value
+                .parse::<bool>()
+                .map_err(|_| ConfigError::InvalidReplayProtection)?,
+            None => file.replay_protection.unwrap_or_default(),
+        };
+
+        let replay_cache_size = match env_var("SM_REPLAY_CACHE_SIZE")? {
+            Some(value) => value
+                .parse::<usize>()
+                .map_err(|_| ConfigError::InvalidReplayCacheSize)?,
+            None => file.replay_cache_size.unwrap_or(DEFAULT_REPLAY_CACHE_SIZE),
+        };
+
+        if replay_cache_size == 0 {
+            return Err(ConfigError::InvalidReplayCacheSize);
+        }
+
+        let replay_cache_size = replay_protection.then_some(replay_cache_size);
+
+        let metrics = match env_var("SM_METRICS")? {
+            Some(value) => value
+                .parse::<bool>()
+                .map_err(|_| ConfigError::InvalidMetrics)?,
+            None => file.metrics.unwrap_or_default(),
         };
 
         Ok(Config {
             encryption_key,
+            new_encryption_key,
             database_path,
             use_https,
             server_address,
             certificate_path,
             private_key_path,
-            credentials,
+            access_keys,
+            rotation_lambdas,
+            arn,
+            kms_keys,
+            seed_file,
+            seed_mode,
+            throttle_limits,
+            debug_signatures,
+            replay_cache_size,
+            metrics,
+        })
+    }
+}
+
+/// Subset of the [Config] required to open the database, used by the
+/// administrative commands that don't start the server
+pub struct DatabaseConfig {
+    /// Encryption key to encrypt and decrypt the database
+    pub encryption_key: String,
+    /// Path to the server database file
+    pub database_path: String,
+    /// Region used when a command doesn't specify a region
+    pub region: String,
+}
+
+impl DatabaseConfig {
+    /// Load the database config using the same sources as [Config::from_env]
+    pub fn from_env() -> Result<DatabaseConfig, ConfigError> {
+        let file = load_config_file_from_env()?;
+
+        let encryption_key = env_var("SM_ENCRYPTION_KEY")?
+            .or(file.encryption_key)
+            .ok_or(ConfigError::MissingEncryptionKey)?;
+
+        let database_path = env_var("SM_DATABASE_PATH")?
+            .or(file.database_path)
+            .unwrap_or_else(|| "secrets.db".to_string());
+
+        let region = env_var("SM_REGION")?
+            .or(file.region)
+            .unwrap_or_else(|| DEFAULT_REGION.to_string());
+
+        Ok(DatabaseConfig {
+            encryption_key,
+            database_path,
+            region,
+        })
+    }
+}
+
+/// Get the value of the environment variable `name`, falling back to the trimmed
+/// contents of the file at the path in the `<name>_FILE` environment variable
+fn env_var(name: &str) -> Result<Option<String>, ConfigError> {
+    let file_name = format!("{name}_FILE");
+
+    match (std::env::var(name), std::env::var(&file_name)) {
+        (Ok(_), Ok(_)) => Err(ConfigError::ConflictingVariable(name.to_string())),
+        (Ok(value), Err(_)) => Ok(Some(value)),
+        (Err(_), Ok(path)) => {
+            let value = std::fs::read_to_string(&path)
+                .map_err(|error| ConfigError::ReadVariableFile(file_name.clone(), error))?;
+            let value = value.trim();
+
+            if value.is_empty() {
+                return Err(ConfigError::EmptyVariableFile(file_name));
+            }
+
+            Ok(Some(value.to_string()))
+        }
+        (Err(_), Err(_)) => Ok(None),
+    }
+}
+
+/// Config file covering the settings that can be provided through environment
+/// variables, environment variables take precedence over the config file
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    encryption_key: Option<String>,
+    new_encryption_key: Option<String>,
+    database_path: Option<String>,
+    server_address: Option<SocketAddr>,
+    use_https: Option<bool>,
+    https_certificate_path: Option<String>,
+    https_private_key_path: Option<String>,
+    access_key_id: Option<String>,
+    access_key_secret: Option<String>,
+    credentials_path: Option<String>,
+    identity_policy_path: Option<String>,
+    /// Mapping from rotation lambda ARNs to webhook URLs
+    rotation_lambdas: Option<HashMap<String, String>>,
+    partition: Option<String>,
+    region: Option<String>,
+    account_id: Option<String>,
+    kms_keys: Option<Vec<String>>,
+    seed_file: Option<String>,
+    seed_mode: Option<SeedMode>,
+    throttling: Option<bool>,
+    /// Mapping from targets to the requests per second allowed
+    throttle_limits: Option<HashMap<String, u32>>,
+    debug_signatures: Option<bool>,
+    replay_protection: Option<bool>,
+    replay_cache_size: Option<usize>,
+    metrics: Option<bool>,
+}
+
+/// Load the TOML config file at `SM_CONFIG_PATH`, an empty config is used when
+/// `SM_CONFIG_PATH` is not set
+fn load_config_file_from_env() -> Result<ConfigFile, ConfigError> {
+    match std::env::var("SM_CONFIG_PATH") {
+        Ok(path) => load_config_file(&path),
+        Err(_) => Ok(ConfigFile::default()),
+    }
+}
+
+/// Load the TOML config file at `path`
+fn load_config_file(path: &str) -> Result<ConfigFile, ConfigError> {
+    let value = std::fs::read_to_string(path).map_err(ConfigError::ReadConfigFile)?;
+    let file: ConfigFile = toml::from_str(&value)
+        .map_err(|error| ConfigError::InvalidConfigFile(error.to_string()))?;
+    Ok(file)
+}
+
+/// Parse a comma separated list of `<lambda-arn>=<webhook-url>` pairs
+fn parse_rotation_lambdas(value: &str) -> Result<HashMap<String, String>, ConfigError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (arn, url) = pair
+                .split_once('=')
+                .ok_or(ConfigError::InvalidRotationLambdas)?;
+            let (arn, url) = (arn.trim(), url.trim());
+
+            if arn.is_empty() || url.is_empty() {
+                return Err(ConfigError::InvalidRotationLambdas);
+            }
+
+            Ok((arn.to_string(), url.to_string()))
+        })
+        .collect()
+}
+
+/// Parse a comma separated list of `<target>=<requests-per-second>` throttle limits
+fn parse_throttle_limits(value: &str) -> Result<HashMap<String, u32>, ConfigError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (target, limit) = pair
+                .split_once('=')
+                .ok_or(ConfigError::InvalidThrottleLimits)?;
+            let target = target.trim();
+            let limit = limit
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| ConfigError::InvalidThrottleLimits)?;
+
+            if target.is_empty() {
+                return Err(ConfigError::InvalidThrottleLimits);
+            }
+
+            Ok((target.to_string(), limit))
         })
+        .collect()
+}
+
+/// Parse a comma separated list of KMS key IDs
+fn parse_kms_keys(value: &str) -> Result<Vec<String>, ConfigError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|key_id| !key_id.is_empty())
+        .map(parse_kms_key_id)
+        .collect()
+}
+
+/// Parse a KMS key ID, key IDs must be UUIDs
+fn parse_kms_key_id(value: &str) -> Result<String, ConfigError> {
+    let key_id = Uuid::parse_str(value).map_err(|_| ConfigError::InvalidKmsKeys)?;
+    Ok(key_id.to_string())
+}
+
+/// Credentials file containing the access keys accepted by the server
+#[derive(Deserialize)]
+struct CredentialsFile {
+    access_keys: Vec<CredentialsFileAccessKey>,
+}
+
+/// Access key within a [CredentialsFile]
+#[derive(Deserialize)]
+struct CredentialsFileAccessKey {
+    /// Access key ID
+    access_key_id: String,
+    /// Secret access key
+    secret_access_key: String,
+    /// Session token for temporary credentials
+    session_token: Option<String>,
+    /// Time the temporary credentials expire
+    expiration: Option<DateTime<Utc>>,
+    /// Principal ARN or name of the IAM user the key belongs to
+    principal: Option<String>,
+    /// Path to the identity policy for the principal
+    policy_path: Option<String>,
+}
+
+/// Load the access keys from a TOML or JSON credentials file, JSON is used
+/// for files with the ".json" extension
+fn load_credentials_file(path: &str, arn: &ArnConfig) -> Result<Vec<AccessKey>, ConfigError> {
+    let value = std::fs::read_to_string(path).map_err(ConfigError::ReadCredentials)?;
+
+    let is_json = Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+
+    let file: CredentialsFile = if is_json {
+        serde_json::from_str(&value)
+            .map_err(|error| ConfigError::InvalidCredentials(error.to_string()))?
+    } else {
+        toml::from_str(&value)
+            .map_err(|error| ConfigError::InvalidCredentials(error.to_string()))?
+    };
+
+    file.access_keys
+        .into_iter()
+        .map(|access_key| {
+            if access_key.expiration.is_some() && access_key.session_token.is_none() {
+                return Err(ConfigError::InvalidCredentials(format!(
+                    "access key {} has an expiration without a session_token",
+                    access_key.access_key_id
+                )));
+            }
+
+            let expiration = match access_key.expiration {
+                Some(expiration) => Some(chrono_to_system_time(expiration).ok_or_else(|| {
+                    ConfigError::InvalidCredentials(format!(
+                        "access key {} has an unsupported expiration {expiration}",
+                        access_key.access_key_id
+                    ))
+                })?),
+                None => None,
+            };
+
+            let credentials = Credentials::new(
+                access_key.access_key_id,
+                access_key.secret_access_key,
+                access_key.session_token,
+                expiration,
+                "sm-credentials-file",
+            );
+
+            let principal_arn = match access_key.principal {
+                Some(principal) => principal_arn(&principal, arn),
+                None => arn.iam_arn("root"),
+            };
+
+            let policy = match access_key.policy_path {
+                Some(path) => Some(load_identity_policy(&path)?),
+                None => None,
+            };
+
+            Ok(AccessKey {
+                credentials,
+                principal_arn,
+                policy,
+            })
+        })
+        .collect()
+}
+
+/// Get the principal ARN for a principal, names that aren't already an ARN
+/// are treated as the name of an IAM user in the configured account
+fn principal_arn(principal: &str, arn: &ArnConfig) -> String {
+    if principal.starts_with("arn:") {
+        return principal.to_string();
+    }
+
+    arn.iam_arn(&format!("user/{principal}"))
+}
+
+/// Load and parse the identity policy file at `path`
+fn load_identity_policy(path: &str) -> Result<Arc<PolicyDocument>, ConfigError> {
+    let value = std::fs::read_to_string(path)
+        .map_err(|error| ConfigError::ReadIdentityPolicy(path.to_string(), error))?;
+
+    let policy = PolicyDocument::parse(&value, PolicyKind::Identity).map_err(|errors| {
+        tracing::error!(?errors, %path, "invalid identity policy");
+        ConfigError::InvalidIdentityPolicy(path.to_string())
+    })?;
+
+    Ok(Arc::new(policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ConfigError, ConfigFile, load_credentials_file, parse_kms_keys, parse_throttle_limits,
+    };
+    use crate::arn::ArnConfig;
+    use std::io::Write;
+
+    #[test]
+    fn test_config_file() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            encryption_key = "key"
+            server_address = "127.0.0.1:9000"
+            use_https = true
+            kms_keys = ["4a0d2b5e-0f4a-4b9f-9a3c-8b2a3e4d5f60"]
+            metrics = true
+
+            [rotation_lambdas]
+            "arn:aws:lambda:us-east-1:1:function:rotate" = "http://localhost:9000/rotate"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(file.encryption_key.as_deref(), Some("key"));
+        assert_eq!(file.server_address, Some("127.0.0.1:9000".parse().unwrap()));
+        assert_eq!(file.use_https, Some(true));
+        assert_eq!(file.kms_keys.map(|keys| keys.len()), Some(1));
+        assert_eq!(file.rotation_lambdas.map(|lambdas| lambdas.len()), Some(1));
+        assert_eq!(file.metrics, Some(true));
+        assert!(file.database_path.is_none());
+    }
+
+    #[test]
+    fn test_config_file_unknown_setting() {
+        let result = toml::from_str::<ConfigFile>(r#"encryption_kye = "key""#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_kms_keys() {
+        let keys = parse_kms_keys(
+            "4a0d2b5e-0f4a-4b9f-9a3c-8b2a3e4d5f60, 1b6e1c0c-9c55-4d1e-8f63-7f0d2c1b9a44,",
+        )
+        .unwrap();
+        assert_eq!(keys.len(), 2);
+
+        assert!(parse_kms_keys("not-a-uuid").is_err());
+    }
+
+    #[test]
+    fn test_parse_throttle_limits() {
+        let limits = parse_throttle_limits(
+            "secretsmanager.GetSecretValue=100, secretsmanager.CreateSecret = 5,",
+        )
+        .unwrap();
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits.get("secretsmanager.CreateSecret"), Some(&5));
+
+        assert!(parse_throttle_limits("secretsmanager.CreateSecret").is_err());
+        assert!(parse_throttle_limits("secretsmanager.CreateSecret=fast").is_err());
+    }
+
+    #[test]
+    fn test_credentials_file_expiration() {
+        let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        write!(
+            file,
+            r#"
+            [[access_keys]]
+            access_key_id = "ASIATEST"
+            secret_access_key = "secret"
+            session_token = "token"
+            expiration = "2100-01-01T00:00:00Z"
+            "#
+        )
+        .unwrap();
+
+        let path = file.path().to_str().unwrap();
+        let access_keys = load_credentials_file(path, &ArnConfig::default()).unwrap();
+        assert!(access_keys[0].credentials.expiry().is_some());
+    }
+
+    #[test]
+    fn test_credentials_file_unsupported_expiration() {
+        let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        write!(
+            file,
+            r#"
+            [[access_keys]]
+            access_key_id = "ASIATEST"
+            secret_access_key = "secret"
+            session_token = "token"
+            expiration = "1969-12-31T00:00:00Z"
+            "#
+        )
+        .unwrap();
+
+        let path = file.path().to_str().unwrap();
+        let result = load_credentials_file(path, &ArnConfig::default());
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidCredentials(message)) if message.contains("ASIATEST")
+        ));
     }
 }
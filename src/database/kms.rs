@@ -0,0 +1,123 @@
+use crate::database::DbResult;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use tokio_rusqlite::{
+    OptionalExtension, Row, params,
+    rusqlite::{self, Connection},
+};
+
+#[derive(Clone)]
+pub struct StoredKmsKey {
+    pub key_id: String,
+    pub arn: String,
+    pub region: String,
+    //
+    pub description: Option<String>,
+    pub enabled: bool,
+    //
+    pub key_material: Vec<u8>,
+    //
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> TryFrom<&'a Row<'a>> for StoredKmsKey {
+    type Error = rusqlite::Error;
+
+    fn try_from(value: &'a Row<'a>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key_id: value.get("key_id")?,
+            arn: value.get("arn")?,
+            region: value.get("region")?,
+            description: value.get("description")?,
+            enabled: value.get("enabled")?,
+            key_material: value.get("key_material")?,
+            created_at: value.get("created_at")?,
+            updated_at: value.get("updated_at")?,
+        })
+    }
+}
+
+pub struct CreateKmsKey {
+    pub key_id: String,
+    pub arn: String,
+    pub region: String,
+    pub description: Option<String>,
+    pub key_material: Vec<u8>,
+}
+
+/// Create a new KMS key, keys with an existing key ID are left unchanged
+pub fn create_kms_key(db: &Connection, create: CreateKmsKey) -> DbResult<usize> {
+    let created_at = Utc::now();
+
+    db.execute(
+        r#"
+        INSERT INTO "kms_keys" ("key_id", "arn", "region", "description", "key_material", "created_at")
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT("key_id") DO NOTHING
+        "#,
+        params![
+            create.key_id,
+            create.arn,
+            create.region,
+            create.description,
+            create.key_material,
+            created_at
+        ],
+    )
+}
+
+/// Get a KMS key in `region` where the key ID OR arn matches the `key_id`
+pub fn get_kms_key(db: &Connection, region: &str, key_id: &str) -> DbResult<Option<StoredKmsKey>> {
+    db.query_one(
+        r#"
+        SELECT * FROM "kms_keys"
+        WHERE "region" = ? AND ("key_id" = ? OR "arn" = ?)
+        LIMIT 1
+        "#,
+        params![region, key_id, key_id],
+        |row| StoredKmsKey::try_from(row),
+    )
+    .optional()
+}
+
+/// Get a KMS key by its `arn`
+pub fn get_kms_key_by_arn(db: &Connection, arn: &str) -> DbResult<Option<StoredKmsKey>> {
+    db.query_one(
+        r#"SELECT * FROM "kms_keys" WHERE "arn" = ?"#,
+        params![arn],
+        |row| StoredKmsKey::try_from(row),
+    )
+    .optional()
+}
+
+/// Get a page of the KMS keys in `region`
+pub fn get_kms_keys_page(
+    db: &Connection,
+    region: &str,
+    limit: i64,
+    offset: i64,
+) -> DbResult<Vec<StoredKmsKey>> {
+    db.prepare(
+        r#"
+        SELECT * FROM "kms_keys"
+        WHERE "region" = ?
+        ORDER BY "created_at" ASC, "key_id" ASC
+        LIMIT ? OFFSET ?
+        "#,
+    )?
+    .query_map(params![region, limit, offset], |row| {
+        StoredKmsKey::try_from(row)
+    })?
+    .try_collect()
+}
+
+/// Enable or disable a KMS key
+pub fn set_kms_key_enabled(db: &Connection, key_arn: &str, enabled: bool) -> DbResult<usize> {
+    let updated_at = Utc::now();
+
+    db.execute(
+        r#"UPDATE "kms_keys" SET "enabled" = ?, "updated_at" = ? WHERE "arn" = ?"#,
+        params![enabled, updated_at, key_arn],
+    )
+}
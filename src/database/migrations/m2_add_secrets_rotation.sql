@@ -0,0 +1,12 @@
+-- Whether automatic rotation is enabled for the secret
+ALTER TABLE "secrets" ADD COLUMN "rotation_enabled" BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- ARN of the function used to rotate the secret
+ALTER TABLE "secrets" ADD COLUMN "rotation_lambda_arn" TEXT NULL;
+
+-- JSON encoded rotation rules for the secret
+ALTER TABLE "secrets" ADD COLUMN "rotation_rules" TEXT NULL;
+
+-- Timestamps for the last completed rotation and the next scheduled rotation
+ALTER TABLE "secrets" ADD COLUMN "last_rotated_at" TEXT NULL;
+ALTER TABLE "secrets" ADD COLUMN "next_rotation_at" TEXT NULL;
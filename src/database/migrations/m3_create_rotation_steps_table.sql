@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS "secrets_rotation_steps" (
+    -- Secret version being rotated
+    "secret_arn" TEXT NOT NULL,
+    "version_id" TEXT NOT NULL,
+
+    -- Rotation step that was performed (createSecret, setSecret, testSecret, finishSecret)
+    "step" TEXT NOT NULL,
+
+    -- Outcome of the step and the failure message if the step failed
+    "success" BOOLEAN NOT NULL,
+    "message" TEXT NULL,
+
+    "created_at" TEXT NOT NULL,
+
+    -- Foreign key to "secrets"
+    FOREIGN KEY ("secret_arn") REFERENCES "secrets"("arn") ON DELETE CASCADE
+);
+
+-- Fast lookups by ARN + Version ID
+CREATE INDEX IF NOT EXISTS "idx_secrets_rotation_steps_version_id" ON "secrets_rotation_steps"("secret_arn", "version_id");
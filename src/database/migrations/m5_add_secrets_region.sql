@@ -0,0 +1,73 @@
+-- Rebuild the secrets table to scope the unique name by region, SQLite doesn't
+-- support dropping constraints so the table must be recreated
+CREATE TABLE IF NOT EXISTS "secrets_new" (
+    -- Secret ARN
+    "arn" TEXT PRIMARY KEY NOT NULL,
+
+    -- Region the secret belongs to
+    "region" TEXT NOT NULL,
+
+    -- Metadata
+    "name" TEXT NOT NULL,
+    "description" TEXT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- Datetime the resource was marked for deletion and the datetime its scheduled to be deleted by
+    "deleted_at" TEXT NULL,
+    "scheduled_delete_at" TEXT NULL,
+
+    -- Rotation configuration
+    "rotation_enabled" BOOLEAN NOT NULL DEFAULT FALSE,
+    "rotation_lambda_arn" TEXT NULL,
+    "rotation_rules" TEXT NULL,
+    "last_rotated_at" TEXT NULL,
+    "next_rotation_at" TEXT NULL,
+
+    -- Name must be unique within a region
+    UNIQUE ("region", "name")
+);
+
+-- Secrets created before regions were tracked are stored in the region of their
+-- ARN (arn:partition:secretsmanager:region:account:secret:name), falling back to
+-- us-east-1 for ARNs that don't contain a region
+INSERT INTO "secrets_new" (
+    "arn", "region", "name", "description", "created_at", "updated_at", "deleted_at",
+    "scheduled_delete_at", "rotation_enabled", "rotation_lambda_arn", "rotation_rules",
+    "last_rotated_at", "next_rotation_at"
+)
+WITH
+    -- ARN after the "arn:" prefix
+    "arn_partition" AS (
+        SELECT "secrets".*, substr("arn", instr("arn", ':') + 1) AS "arn_rest"
+        FROM "secrets"
+    ),
+    -- ARN after the partition
+    "arn_service" AS (
+        SELECT "arn_partition".*, substr("arn_rest", instr("arn_rest", ':') + 1) AS "service_rest"
+        FROM "arn_partition"
+    ),
+    -- ARN after the service, starting with the region
+    "arn_region" AS (
+        SELECT "arn_service".*, substr("service_rest", instr("service_rest", ':') + 1) AS "region_rest"
+        FROM "arn_service"
+    )
+SELECT
+    "arn",
+    COALESCE(NULLIF(substr("region_rest", 1, instr("region_rest", ':') - 1), ''), 'us-east-1'),
+    "name", "description", "created_at", "updated_at", "deleted_at",
+    "scheduled_delete_at", "rotation_enabled", "rotation_lambda_arn", "rotation_rules",
+    "last_rotated_at", "next_rotation_at"
+FROM "arn_region";
+
+DROP TABLE "secrets";
+
+ALTER TABLE "secrets_new" RENAME TO "secrets";
+
+-- Fast lookups by ARN
+CREATE INDEX IF NOT EXISTS "idx_secrets_arn" ON "secrets"("arn");
+
+-- Fast lookups by region + name
+CREATE INDEX IF NOT EXISTS "idx_secrets_region_name" ON "secrets"("region", "name");
@@ -0,0 +1,28 @@
+-- ARN of the primary secret when the secret is a replica
+ALTER TABLE "secrets" ADD COLUMN "primary_arn" TEXT NULL;
+
+-- Fast lookups of the replicas of a primary secret
+CREATE INDEX IF NOT EXISTS "idx_secrets_primary_arn" ON "secrets"("primary_arn");
+
+CREATE TABLE IF NOT EXISTS "secrets_replicas" (
+    -- Primary secret being replicated
+    "secret_arn" TEXT NOT NULL,
+
+    -- Region the secret is replicated to
+    "region" TEXT NOT NULL,
+    "kms_key_id" TEXT NULL,
+
+    -- Replication status (InSync, Failed) and the message describing the status
+    "status" TEXT NOT NULL,
+    "status_message" TEXT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- Composite primary key
+    PRIMARY KEY ("secret_arn", "region"),
+
+    -- Foreign key to "secrets"
+    FOREIGN KEY ("secret_arn") REFERENCES "secrets"("arn") ON DELETE CASCADE
+);
@@ -0,0 +1,36 @@
+CREATE TABLE IF NOT EXISTS "kms_keys" (
+    -- Key ID and ARN
+    "key_id" TEXT PRIMARY KEY NOT NULL,
+    "arn" TEXT NOT NULL,
+
+    -- Region the key belongs to
+    "region" TEXT NOT NULL,
+
+    -- Metadata
+    "description" TEXT NULL,
+    "enabled" BOOLEAN NOT NULL DEFAULT TRUE,
+
+    -- 256-bit key material used to encrypt data keys
+    "key_material" BLOB NOT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- ARN must be unique
+    UNIQUE ("arn")
+);
+
+-- Fast lookups by ARN
+CREATE INDEX IF NOT EXISTS "idx_kms_keys_arn" ON "kms_keys"("arn");
+
+-- ARN of the KMS key new versions of the secret are encrypted with
+ALTER TABLE "secrets" ADD COLUMN "kms_key_id" TEXT NULL;
+
+-- ARN of the KMS key the version is encrypted with
+ALTER TABLE "secrets_versions" ADD COLUMN "kms_key_id" TEXT NULL;
+
+-- Data key encrypted by the KMS key and the secret value encrypted by the data key,
+-- the plain text "secret_string" and "secret_binary" are not stored for these versions
+ALTER TABLE "secrets_versions" ADD COLUMN "encrypted_data_key" BLOB NULL;
+ALTER TABLE "secrets_versions" ADD COLUMN "encrypted_value" BLOB NULL;
@@ -4,13 +4,39 @@ use itertools::Itertools;
 use serde::Serialize;
 use tokio_rusqlite::{
     Row, params,
-    rusqlite::{self, Connection},
+    rusqlite::{self, Connection, ffi},
 };
 
-pub const MIGRATIONS: &[(&str, &str)] = &[(
-    "m1_create_secrets_tables",
-    include_str!("./m1_create_secrets_tables.sql"),
-)];
+pub const MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "m1_create_secrets_tables",
+        include_str!("./m1_create_secrets_tables.sql"),
+    ),
+    (
+        "m2_add_secrets_rotation",
+        include_str!("./m2_add_secrets_rotation.sql"),
+    ),
+    (
+        "m3_create_rotation_steps_table",
+        include_str!("./m3_create_rotation_steps_table.sql"),
+    ),
+    (
+        "m4_create_resource_policies_table",
+        include_str!("./m4_create_resource_policies_table.sql"),
+    ),
+    (
+        "m5_add_secrets_region",
+        include_str!("./m5_add_secrets_region.sql"),
+    ),
+    (
+        "m6_create_replicas_table",
+        include_str!("./m6_create_replicas_table.sql"),
+    ),
+    (
+        "m7_create_kms_keys_table",
+        include_str!("./m7_create_kms_keys_table.sql"),
+    ),
+];
 
 const MIGRATIONS_SETUP_SQL: &str = include_str!("./m0_create_migrations_table.sql");
 
@@ -63,6 +89,7 @@ pub fn setup_migrations(t: &Connection) -> DbResult<()> {
 
 pub fn apply_migrations(t: &Connection) -> DbResult<()> {
     let migrations = applied_migrations(t)?;
+    let mut applied_any = false;
 
     for (migration_name, migration) in MIGRATIONS {
         // Skip already applied migrations
@@ -84,11 +111,39 @@ pub fn apply_migrations(t: &Connection) -> DbResult<()> {
                 applied_at: Utc::now(),
             },
         )?;
+
+        applied_any = true;
+    }
+
+    // Foreign keys aren't enforced while migrating, ensure rebuilt tables didn't
+    // leave any rows referencing missing rows before the migrations are committed
+    if applied_any {
+        check_foreign_keys(t)?;
     }
 
     Ok(())
 }
 
+/// Check that no rows in the database violate their foreign key constraints
+fn check_foreign_keys(db: &Connection) -> DbResult<()> {
+    let tables: Vec<String> = db
+        .prepare("PRAGMA foreign_key_check")?
+        .query_map(params![], |row| row.get::<_, String>(0))?
+        .try_collect()?;
+
+    if tables.is_empty() {
+        return Ok(());
+    }
+
+    let tables = tables.into_iter().unique().join(", ");
+    tracing::error!(%tables, "migrations left foreign key violations");
+
+    Err(rusqlite::Error::SqliteFailure(
+        ffi::Error::new(ffi::SQLITE_CONSTRAINT_FOREIGNKEY),
+        Some(format!("foreign key violations in tables: {tables}")),
+    ))
+}
+
 /// Apply a migration to the specific database
 pub fn apply_migration(db: &Connection, migration_name: &str, migration: &str) -> DbResult<()> {
     // Split the SQL queries into multiple queries
@@ -1,16 +1,68 @@
-use std::path::Path;
+use std::{path::Path, time::Instant};
 
 use thiserror::Error;
 use tokio::fs::File;
-use tokio_rusqlite::{Connection, rusqlite};
+use tokio_rusqlite::{Connection, ErrorCode, rusqlite};
 
-use crate::database::migrations::{apply_migrations, setup_migrations};
+use crate::{
+    database::migrations::{apply_migrations, setup_migrations},
+    metrics::Metrics,
+};
 
 pub mod ext;
+pub mod kms;
 pub mod migrations;
 pub mod secrets;
 
-pub type DbHandle = Connection;
+/// Handle to the database connection, the time taken by calls made through the
+/// handle is recorded in the database call latency [Metrics]
+#[derive(Clone)]
+pub struct DbHandle {
+    connection: Connection,
+    metrics: Metrics,
+}
+
+impl DbHandle {
+    /// Create a handle to the `connection` recording its calls to the `metrics`
+    pub fn new(connection: Connection, metrics: Metrics) -> Self {
+        Self {
+            connection,
+            metrics,
+        }
+    }
+
+    /// Call the `function` with the database connection on the database thread
+    pub async fn call<F, R, E>(&self, function: F) -> Result<R, tokio_rusqlite::Error<E>>
+    where
+        F: FnOnce(&mut DbConnection) -> Result<R, E> + Send + 'static,
+        R: Send + 'static,
+        E: Send + 'static,
+    {
+        let start = Instant::now();
+        let result = self.connection.call(function).await;
+        self.metrics.record_db_call(start.elapsed());
+        result
+    }
+
+    /// Call the `function` with the database connection on the database thread,
+    /// panics if the connection has been closed
+    pub async fn call_unwrap<F, R>(&self, function: F) -> R
+    where
+        F: FnOnce(&mut DbConnection) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let start = Instant::now();
+        let result = self.connection.call_unwrap(function).await;
+        self.metrics.record_db_call(start.elapsed());
+        result
+    }
+}
+
+impl From<Connection> for DbHandle {
+    fn from(connection: Connection) -> Self {
+        Self::new(connection, Metrics::default())
+    }
+}
 
 pub type DbConnection = rusqlite::Connection;
 
@@ -20,6 +72,9 @@ pub type DbErr = rusqlite::Error;
 /// Type alias for a result where the error is a [DbErr]
 pub type DbResult<T> = Result<T, DbErr>;
 
+/// Database path used to store the database in memory instead of a file
+pub const MEMORY_DATABASE_PATH: &str = ":memory:";
+
 #[derive(Debug, Error)]
 pub enum CreateDatabaseError {
     #[error("failed to create database file parent folders")]
@@ -28,6 +83,12 @@ pub enum CreateDatabaseError {
     #[error("failed to create database file")]
     CreateFile(std::io::Error),
 
+    #[error("encryption key is not able to decrypt the database, is SM_ENCRYPTION_KEY correct?")]
+    InvalidEncryptionKey,
+
+    #[error("database was not readable using the new encryption key after rekeying")]
+    RekeyFailed,
+
     #[error(transparent)]
     AsyncDb(#[from] tokio_rusqlite::Error),
 
@@ -35,8 +96,11 @@ pub enum CreateDatabaseError {
     Db(#[from] rusqlite::Error),
 }
 
+/// Open the database at `raw_path` using the encryption `key`, when a `new_key` is
+/// provided the database is rekeyed to use the `new_key` once the `key` has been verified
 pub async fn create_database(
     key: String,
+    new_key: Option<String>,
     raw_path: String,
 ) -> Result<Connection, CreateDatabaseError> {
     let path = Path::new(&raw_path);
@@ -53,9 +117,47 @@ pub async fn create_database(
             .map_err(CreateDatabaseError::CreateFile)?;
     }
 
-    let db = Connection::open(path).await?;
+    let db = match (open_database(path, key).await?, new_key) {
+        // Key is valid, rekey the database when requested
+        (Some(db), Some(new_key)) => {
+            rekey_database(&db, new_key).await?;
+            db
+        }
+
+        (Some(db), None) => db,
+
+        // A previous start may have already rekeyed the database
+        (None, Some(new_key)) => {
+            let db = open_database(path, new_key)
+                .await?
+                .ok_or(CreateDatabaseError::InvalidEncryptionKey)?;
+
+            tracing::warn!(
+                "database is already encrypted using SM_NEW_ENCRYPTION_KEY, set SM_ENCRYPTION_KEY to the new key and remove SM_NEW_ENCRYPTION_KEY"
+            );
+
+            db
+        }
+
+        (None, None) => return Err(CreateDatabaseError::InvalidEncryptionKey),
+    };
+
+    db.call(move |db| {
+        db.pragma_update(None, "case_sensitive_like", true)?;
+        initialize_database(db)?;
+        Ok(())
+    })
+    .await?;
+
+    Ok(db)
+}
+
+/// Create an unencrypted database that only exists in memory, everything stored
+/// in the database is lost once the connection is closed
+pub async fn create_memory_database() -> Result<Connection, CreateDatabaseError> {
+    let db = Connection::open_in_memory().await?;
+
     db.call(move |db| {
-        db.pragma_update(None, "key", key)?;
         db.pragma_update(None, "case_sensitive_like", true)?;
         initialize_database(db)?;
         Ok(())
@@ -65,16 +167,81 @@ pub async fn create_database(
     Ok(db)
 }
 
+/// Open the database at `path` using the encryption `key`, provides [None]
+/// when the `key` is not able to decrypt the database
+async fn open_database(
+    path: &Path,
+    key: String,
+) -> Result<Option<Connection>, CreateDatabaseError> {
+    let db = Connection::open(path).await?;
+
+    let is_valid_key = db
+        .call(move |db| {
+            db.pragma_update(None, "key", key)?;
+            is_valid_database_key(db)
+        })
+        .await?;
+
+    if !is_valid_key {
+        // Connection is dropped as SQLCipher doesn't support changing the key after a failure
+        return Ok(None);
+    }
+
+    Ok(Some(db))
+}
+
+/// Change the encryption key of the database to the `new_key` using SQLCipher rekey,
+/// the database is verified to be readable using the new key afterwards
+async fn rekey_database(db: &Connection, new_key: String) -> Result<(), CreateDatabaseError> {
+    tracing::info!("rekeying database using SM_NEW_ENCRYPTION_KEY, this may take some time");
+
+    let is_valid_key = db
+        .call(move |db| {
+            // Rekey re-encrypts every page of the database within its own transaction,
+            // the database is left using the old key if this fails
+            db.pragma_update(None, "rekey", new_key)?;
+            is_valid_database_key(db)
+        })
+        .await
+        .inspect_err(|error| tracing::error!(?error, "failed to rekey database"))?;
+
+    if !is_valid_key {
+        return Err(CreateDatabaseError::RekeyFailed);
+    }
+
+    tracing::info!(
+        "database rekeyed, set SM_ENCRYPTION_KEY to the new key and remove SM_NEW_ENCRYPTION_KEY"
+    );
+
+    Ok(())
+}
+
+/// Check if the database can be read using the key it was opened with, SQLCipher
+/// reports the database as "not a database" when the key is wrong
+fn is_valid_database_key(db: &rusqlite::Connection) -> DbResult<bool> {
+    match db.query_row(r#"SELECT COUNT(*) FROM "sqlite_master""#, [], |_| Ok(())) {
+        Ok(_) => Ok(true),
+        Err(error) if error.sqlite_error_code() == Some(ErrorCode::NotADatabase) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
 /// Initializes the database ensuring the migrations table is setup and that all migrations
 /// are applied
 pub fn initialize_database(db: &mut rusqlite::Connection) -> DbResult<()> {
-    transaction(db, move |t| {
+    // Foreign keys are disabled while migrating so migrations that rebuild a table
+    // don't cascade deletes into the tables referencing it
+    db.pragma_update(None, "foreign_keys", false)?;
+
+    let result = transaction(db, move |t| {
         setup_migrations(t)?;
         apply_migrations(t)?;
         Ok::<_, DbErr>(())
-    })?;
+    });
 
-    Ok(())
+    db.pragma_update(None, "foreign_keys", true)?;
+
+    result
 }
 
 /// Helper to perform an action future that requires a database transaction
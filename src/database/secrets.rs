@@ -1,6 +1,6 @@
 use crate::{
     database::{DbResult, ext::RowExt},
-    handlers::models::Filter,
+    handlers::models::{Filter, RotationRules},
     utils::filter::split_search_terms,
 };
 use chrono::{DateTime, Days, Utc};
@@ -22,12 +22,23 @@ pub struct StoredSecret {
     pub deleted_at: Option<DateTime<Utc>>,
     pub scheduled_delete_at: Option<DateTime<Utc>>,
     //
+    pub rotation_enabled: bool,
+    pub rotation_lambda_arn: Option<String>,
+    pub rotation_rules: Option<RotationRules>,
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    pub next_rotation_at: Option<DateTime<Utc>>,
+    //
+    pub primary_arn: Option<String>,
+    //
+    pub kms_key_id: Option<String>,
+    //
     pub version_id: String,
     pub version_stages: Vec<String>,
     //
     pub description: Option<String>,
     pub secret_string: Option<String>,
     pub secret_binary: Option<String>,
+    pub encrypted_value: Option<EncryptedSecretValue>,
     //
     pub version_created_at: DateTime<Utc>,
     pub version_last_accessed_at: Option<DateTime<Utc>>,
@@ -36,6 +47,11 @@ pub struct StoredSecret {
 }
 
 impl StoredSecret {
+    /// Check if the secret is a replica of a secret in another region
+    pub fn is_replica(&self) -> bool {
+        self.primary_arn.is_some()
+    }
+
     pub fn is_value_eq(
         &self,
         secret_string: &Option<String>,
@@ -56,11 +72,19 @@ impl<'a> TryFrom<&'a Row<'a>> for StoredSecret {
             updated_at: value.get("updated_at")?,
             deleted_at: value.get("deleted_at")?,
             scheduled_delete_at: value.get("scheduled_delete_at")?,
+            rotation_enabled: value.get("rotation_enabled")?,
+            rotation_lambda_arn: value.get("rotation_lambda_arn")?,
+            rotation_rules: value.get_json("rotation_rules")?,
+            last_rotated_at: value.get("last_rotated_at")?,
+            next_rotation_at: value.get("next_rotation_at")?,
+            primary_arn: value.get("primary_arn")?,
+            kms_key_id: value.get("kms_key_id")?,
             version_id: value.get("version_id")?,
             version_stages: value.get_json("version_stages")?,
             description: value.get("description")?,
             secret_string: value.get("secret_string")?,
             secret_binary: value.get("secret_binary")?,
+            encrypted_value: EncryptedSecretValue::try_from_row(value)?,
             version_created_at: value.get("version_created_at")?,
             version_last_accessed_at: value.get("Version_last_accessed_at")?,
             version_tags: value.get_json("version_tags")?,
@@ -68,6 +92,34 @@ impl<'a> TryFrom<&'a Row<'a>> for StoredSecret {
     }
 }
 
+/// Value of a secret version encrypted using the data key of the version, the data key
+/// itself is encrypted by the KMS key
+#[derive(Clone)]
+pub struct EncryptedSecretValue {
+    /// ARN of the KMS key the data key is encrypted with
+    pub kms_key_id: String,
+    pub encrypted_data_key: Vec<u8>,
+    pub encrypted_value: Vec<u8>,
+}
+
+impl EncryptedSecretValue {
+    /// Load the encrypted value of the version from `row`, versions without
+    /// a KMS key are not encrypted
+    fn try_from_row(value: &Row<'_>) -> Result<Option<Self>, rusqlite::Error> {
+        let kms_key_id: Option<String> = value.get("version_kms_key_id")?;
+        let kms_key_id = match kms_key_id {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            kms_key_id,
+            encrypted_data_key: value.get("encrypted_data_key")?,
+            encrypted_value: value.get("encrypted_value")?,
+        }))
+    }
+}
+
 #[derive(Clone)]
 pub struct StoredSecretWithVersionStages {
     pub arn: String,
@@ -78,12 +130,23 @@ pub struct StoredSecretWithVersionStages {
     pub deleted_at: Option<DateTime<Utc>>,
     pub scheduled_delete_at: Option<DateTime<Utc>>,
     //
+    pub rotation_enabled: bool,
+    pub rotation_lambda_arn: Option<String>,
+    pub rotation_rules: Option<RotationRules>,
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    pub next_rotation_at: Option<DateTime<Utc>>,
+    //
+    pub primary_arn: Option<String>,
+    //
+    pub kms_key_id: Option<String>,
+    //
     pub version_id: String,
     pub version_stages: Vec<String>,
     //
     pub description: Option<String>,
     pub secret_string: Option<String>,
     pub secret_binary: Option<String>,
+    pub encrypted_value: Option<EncryptedSecretValue>,
     //
     pub version_created_at: DateTime<Utc>,
     pub version_last_accessed_at: Option<DateTime<Utc>>,
@@ -91,6 +154,8 @@ pub struct StoredSecretWithVersionStages {
     pub version_tags: Vec<StoredVersionTags>,
     //
     pub versions: Vec<StoredVersionsListItem>,
+    //
+    pub primary_region: Option<String>,
 }
 
 impl<'a> TryFrom<&'a Row<'a>> for StoredSecretWithVersionStages {
@@ -104,15 +169,24 @@ impl<'a> TryFrom<&'a Row<'a>> for StoredSecretWithVersionStages {
             updated_at: value.get("updated_at")?,
             deleted_at: value.get("deleted_at")?,
             scheduled_delete_at: value.get("scheduled_delete_at")?,
+            rotation_enabled: value.get("rotation_enabled")?,
+            rotation_lambda_arn: value.get("rotation_lambda_arn")?,
+            rotation_rules: value.get_json("rotation_rules")?,
+            last_rotated_at: value.get("last_rotated_at")?,
+            next_rotation_at: value.get("next_rotation_at")?,
+            primary_arn: value.get("primary_arn")?,
+            kms_key_id: value.get("kms_key_id")?,
             version_id: value.get("version_id")?,
             version_stages: value.get_json("version_stages")?,
             description: value.get("description")?,
             secret_string: value.get("secret_string")?,
             secret_binary: value.get("secret_binary")?,
+            encrypted_value: EncryptedSecretValue::try_from_row(value)?,
             version_created_at: value.get("version_created_at")?,
             version_last_accessed_at: value.get("Version_last_accessed_at")?,
             version_tags: value.get_json("version_tags")?,
             versions: value.get_json("versions")?,
+            primary_region: value.get("primary_region")?,
         })
     }
 }
@@ -134,6 +208,7 @@ pub struct SecretVersion {
     //
     pub secret_string: Option<String>,
     pub secret_binary: Option<String>,
+    pub kms_key_id: Option<String>,
     //
     pub created_at: DateTime<Utc>,
     pub last_accessed_at: Option<DateTime<Utc>>,
@@ -149,6 +224,7 @@ impl<'a> TryFrom<&'a Row<'a>> for SecretVersion {
             version_stages: value.get_json("version_stages")?,
             secret_string: value.get("secret_string")?,
             secret_binary: value.get("secret_binary")?,
+            kms_key_id: value.get("kms_key_id")?,
             created_at: value.get("created_at")?,
             last_accessed_at: value.get("last_accessed_at")?,
         })
@@ -165,8 +241,10 @@ pub struct StoredVersionTags {
 
 pub struct CreateSecret {
     pub arn: String,
+    pub region: String,
     pub name: String,
     pub description: Option<String>,
+    pub kms_key_id: Option<String>,
 }
 
 /// Create a new "secret" with no versions
@@ -175,9 +253,17 @@ pub fn create_secret(db: &Connection, create: CreateSecret) -> DbResult<()> {
 
     db.execute(
         r#"
-        INSERT INTO "secrets" ("arn", "name", "description", "created_at") VALUES (?, ?, ?, ?)
+        INSERT INTO "secrets" ("arn", "region", "name", "description", "kms_key_id", "created_at")
+        VALUES (?, ?, ?, ?, ?, ?)
     "#,
-        params![create.arn, create.name, create.description, created_at],
+        params![
+            create.arn,
+            create.region,
+            create.name,
+            create.description,
+            create.kms_key_id,
+            created_at
+        ],
     )?;
 
     Ok(())
@@ -193,6 +279,25 @@ pub fn update_secret_description(db: &Connection, arn: &str, description: &str)
     )
 }
 
+/// Updates the KMS key used to encrypt new versions of a secret
+pub fn update_secret_kms_key_id(
+    db: &Connection,
+    arn: &str,
+    kms_key_id: Option<&str>,
+) -> DbResult<usize> {
+    let updated_at = Utc::now();
+
+    db.execute(
+        r#"UPDATE "secrets" SET "kms_key_id" = ?, "updated_at" = ? WHERE "secrets"."arn" = ?"#,
+        params![kms_key_id, updated_at, arn],
+    )
+}
+
+/// Remove all secrets
+pub fn delete_all_secrets(db: &Connection) -> DbResult<usize> {
+    db.execute(r#"DELETE FROM "secrets""#, params![])
+}
+
 /// Remove a secret
 pub fn delete_secret(db: &Connection, secret_arn: &str) -> DbResult<usize> {
     db.execute(
@@ -201,6 +306,33 @@ pub fn delete_secret(db: &Connection, secret_arn: &str) -> DbResult<usize> {
     )
 }
 
+/// Get the ARN of the secret named `name` in `region`, unlike the other secret
+/// queries this finds secrets that don't have any versions
+pub fn get_secret_arn(db: &Connection, region: &str, name: &str) -> DbResult<Option<String>> {
+    db.query_one(
+        r#"SELECT "arn" FROM "secrets" WHERE "region" = ? AND "name" = ?"#,
+        params![region, name],
+        |row| row.get::<_, String>(0),
+    )
+    .optional()
+}
+
+/// Get all the regions that contain at least one secret
+pub fn get_secret_regions(db: &Connection) -> DbResult<Vec<String>> {
+    db.prepare(r#"SELECT DISTINCT "region" FROM "secrets" ORDER BY "region" ASC"#)?
+        .query_map(params![], |row| row.get::<_, String>(0))?
+        .try_collect()
+}
+
+/// Get the total number of secrets and secret versions stored across all regions
+pub fn get_secret_counts(db: &Connection) -> DbResult<(i64, i64)> {
+    db.query_one(
+        r#"SELECT (SELECT COUNT(*) FROM "secrets"), (SELECT COUNT(*) FROM "secrets_versions")"#,
+        params![],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+    )
+}
+
 /// Get the ARN's of all the secrets that are scheduled for deletion
 ///
 /// Not used by the actual application, only used within tests to ensure
@@ -266,6 +398,576 @@ pub fn cancel_delete_secret(db: &Connection, secret_arn: &str) -> DbResult<()> {
     Ok(())
 }
 
+/// Enables rotation for a secret, the existing rotation function and rules
+/// are kept when `rotation_lambda_arn` or `rotation_rules` are not provided
+pub fn enable_secret_rotation(
+    db: &Connection,
+    secret_arn: &str,
+    rotation_lambda_arn: Option<&str>,
+    rotation_rules: Option<&RotationRules>,
+    next_rotation_at: Option<DateTime<Utc>>,
+) -> DbResult<usize> {
+    let rotation_rules = rotation_rules
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(FromSqlError::other)?;
+
+    db.execute(
+        r#"
+        UPDATE "secrets"
+        SET
+            "rotation_enabled" = TRUE,
+            "rotation_lambda_arn" = COALESCE(?, "rotation_lambda_arn"),
+            "rotation_rules" = COALESCE(?, "rotation_rules"),
+            "next_rotation_at" = ?
+        WHERE "arn" = ?
+        "#,
+        params![
+            rotation_lambda_arn,
+            rotation_rules,
+            next_rotation_at,
+            secret_arn
+        ],
+    )
+}
+
+/// Disables rotation for a secret, clearing the next scheduled rotation
+pub fn disable_secret_rotation(db: &Connection, secret_arn: &str) -> DbResult<usize> {
+    db.execute(
+        r#"
+        UPDATE "secrets"
+        SET
+            "rotation_enabled" = FALSE,
+            "next_rotation_at" = NULL
+        WHERE "arn" = ?
+        "#,
+        params![secret_arn],
+    )
+}
+
+/// Marks a secret as having completed a rotation at `rotated_at` and sets
+/// the date of the next scheduled rotation
+pub fn set_secret_rotated(
+    db: &Connection,
+    secret_arn: &str,
+    rotated_at: DateTime<Utc>,
+    next_rotation_at: Option<DateTime<Utc>>,
+) -> DbResult<usize> {
+    db.execute(
+        r#"
+        UPDATE "secrets"
+        SET
+            "last_rotated_at" = ?,
+            "next_rotation_at" = ?
+        WHERE "arn" = ?
+        "#,
+        params![rotated_at, next_rotation_at, secret_arn],
+    )
+}
+
+/// Sets the date of the next scheduled rotation without marking the
+/// secret as rotated
+pub fn set_secret_next_rotation(
+    db: &Connection,
+    secret_arn: &str,
+    next_rotation_at: Option<DateTime<Utc>>,
+) -> DbResult<usize> {
+    db.execute(
+        r#"UPDATE "secrets" SET "next_rotation_at" = ? WHERE "arn" = ?"#,
+        params![next_rotation_at, secret_arn],
+    )
+}
+
+/// Get the ARNs of all secrets with rotation enabled that are due to be
+/// rotated at `now`, replicas are rotated through their primary secret
+pub fn get_secrets_due_rotation(db: &Connection, now: DateTime<Utc>) -> DbResult<Vec<String>> {
+    db.prepare(
+        r#"
+        SELECT "arn" FROM "secrets"
+        WHERE "rotation_enabled" = TRUE
+            AND "next_rotation_at" <= ?
+            AND "scheduled_delete_at" IS NULL
+            AND "primary_arn" IS NULL
+        "#,
+    )?
+    .query_map(params![now], |row| row.get(0))?
+    .try_collect()
+}
+
+#[derive(Clone)]
+pub struct StoredRotationStep {
+    pub secret_arn: String,
+    pub version_id: String,
+    pub step: String,
+    pub success: bool,
+    pub message: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'a> TryFrom<&'a Row<'a>> for StoredRotationStep {
+    type Error = rusqlite::Error;
+
+    fn try_from(value: &'a Row<'a>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            secret_arn: value.get("secret_arn")?,
+            version_id: value.get("version_id")?,
+            step: value.get("step")?,
+            success: value.get("success")?,
+            message: value.get("message")?,
+            created_at: value.get("created_at")?,
+        })
+    }
+}
+
+pub struct CreateRotationStep {
+    pub secret_arn: String,
+    pub version_id: String,
+    pub step: String,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+/// Record the outcome of a step performed while rotating a secret
+pub fn create_rotation_step(db: &Connection, create: CreateRotationStep) -> DbResult<()> {
+    let created_at = Utc::now();
+
+    db.execute(
+        r#"
+        INSERT INTO "secrets_rotation_steps" ("secret_arn", "version_id", "step", "success", "message", "created_at")
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        params![
+            create.secret_arn,
+            create.version_id,
+            create.step,
+            create.success,
+            create.message,
+            created_at
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Get the outcomes of all the rotation steps performed for a secret
+///
+/// Not used by the actual application, only used within tests to ensure
+/// rotation steps were recorded
+pub fn get_secret_rotation_steps(
+    db: &Connection,
+    secret_arn: &str,
+) -> DbResult<Vec<StoredRotationStep>> {
+    db.prepare(
+        r#"
+        SELECT * FROM "secrets_rotation_steps"
+        WHERE "secret_arn" = ?
+        ORDER BY "rowid" ASC
+        "#,
+    )?
+    .query_map(params![secret_arn], |row| StoredRotationStep::try_from(row))?
+    .try_collect()
+}
+
+/// Set the resource policy attached to a secret
+pub fn put_secret_resource_policy(db: &Connection, secret_arn: &str, policy: &str) -> DbResult<()> {
+    let now = Utc::now();
+
+    db.execute(
+        r#"
+        INSERT INTO "secrets_resource_policies" ("secret_arn", "policy", "created_at")
+        VALUES (?, ?, ?)
+        ON CONFLICT("secret_arn")
+        DO UPDATE SET
+            "policy" = "excluded"."policy",
+            "updated_at" = "excluded"."created_at"
+        "#,
+        params![secret_arn, policy, now],
+    )?;
+
+    Ok(())
+}
+
+/// Get the resource policy attached to a secret
+pub fn get_secret_resource_policy(db: &Connection, secret_arn: &str) -> DbResult<Option<String>> {
+    db.query_row(
+        r#"SELECT "policy" FROM "secrets_resource_policies" WHERE "secret_arn" = ?"#,
+        params![secret_arn],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Remove the resource policy attached to a secret
+pub fn delete_secret_resource_policy(db: &Connection, secret_arn: &str) -> DbResult<usize> {
+    db.execute(
+        r#"DELETE FROM "secrets_resource_policies" WHERE "secret_arn" = ?"#,
+        params![secret_arn],
+    )
+}
+
+#[derive(Clone)]
+pub struct StoredSecretReplica {
+    pub secret_arn: String,
+    pub region: String,
+    pub kms_key_id: Option<String>,
+    pub status: String,
+    pub status_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    //
+    pub last_accessed_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> TryFrom<&'a Row<'a>> for StoredSecretReplica {
+    type Error = rusqlite::Error;
+
+    fn try_from(value: &'a Row<'a>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            secret_arn: value.get("secret_arn")?,
+            region: value.get("region")?,
+            kms_key_id: value.get("kms_key_id")?,
+            status: value.get("status")?,
+            status_message: value.get("status_message")?,
+            created_at: value.get("created_at")?,
+            updated_at: value.get("updated_at")?,
+            last_accessed_at: value.get("last_accessed_at")?,
+        })
+    }
+}
+
+pub struct CreateSecretReplica {
+    pub secret_arn: String,
+    pub region: String,
+    pub kms_key_id: Option<String>,
+    pub status: String,
+    pub status_message: Option<String>,
+}
+
+/// Set the replication status of a secret for a region
+pub fn put_secret_replica(db: &Connection, create: CreateSecretReplica) -> DbResult<()> {
+    let now = Utc::now();
+
+    db.execute(
+        r#"
+        INSERT INTO "secrets_replicas" ("secret_arn", "region", "kms_key_id", "status", "status_message", "created_at")
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT("secret_arn", "region")
+        DO UPDATE SET
+            "kms_key_id" = "excluded"."kms_key_id",
+            "status" = "excluded"."status",
+            "status_message" = "excluded"."status_message",
+            "updated_at" = "excluded"."created_at"
+        "#,
+        params![
+            create.secret_arn,
+            create.region,
+            create.kms_key_id,
+            create.status,
+            create.status_message,
+            now
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Get the replication status of all the regions a secret is replicated to
+pub fn get_secret_replicas(
+    db: &Connection,
+    secret_arn: &str,
+) -> DbResult<Vec<StoredSecretReplica>> {
+    db.prepare(
+        r#"
+        SELECT
+            "secret_replica".*,
+            (
+                SELECT MAX("secret_version"."last_accessed_at")
+                FROM "secrets" "replica_secret"
+                JOIN "secrets_versions" "secret_version"
+                    ON "secret_version"."secret_arn" = "replica_secret"."arn"
+                WHERE "replica_secret"."primary_arn" = "secret_replica"."secret_arn"
+                    AND "replica_secret"."region" = "secret_replica"."region"
+            ) AS "last_accessed_at"
+        FROM "secrets_replicas" "secret_replica"
+        WHERE "secret_replica"."secret_arn" = ?
+        ORDER BY "secret_replica"."region" ASC
+        "#,
+    )?
+    .query_map(params![secret_arn], |row| {
+        StoredSecretReplica::try_from(row)
+    })?
+    .try_collect()
+}
+
+/// Remove the replication status of a secret for a region
+pub fn delete_secret_replica(db: &Connection, secret_arn: &str, region: &str) -> DbResult<usize> {
+    db.execute(
+        r#"DELETE FROM "secrets_replicas" WHERE "secret_arn" = ? AND "region" = ?"#,
+        params![secret_arn, region],
+    )
+}
+
+/// Copy the secret with the `secret_arn` into its replica with the `replica_arn` in `region`
+/// using the `kms_key_id` of the replica, creating the replica if it doesn't already exist
+///
+/// The version stages, tags and resource policy of an existing replica are removed to be
+/// copied again by [copy_secret_replica_state]. Versions no longer present on the primary
+/// secret or encrypted with a different KMS key are also removed, the versions returned by
+/// [get_secret_versions_missing_from_replica] must then be created on the replica
+pub fn copy_secret_replica(
+    db: &Connection,
+    secret_arn: &str,
+    replica_arn: &str,
+    region: &str,
+    kms_key_id: Option<&str>,
+) -> DbResult<()> {
+    db.execute(
+        r#"
+        INSERT INTO "secrets" (
+            "arn", "region", "name", "description", "created_at", "updated_at", "deleted_at",
+            "scheduled_delete_at", "rotation_enabled", "rotation_lambda_arn", "rotation_rules",
+            "last_rotated_at", "next_rotation_at", "kms_key_id", "primary_arn"
+        )
+        SELECT
+            ?1, ?2, "name", "description", "created_at", "updated_at", "deleted_at",
+            "scheduled_delete_at", "rotation_enabled", "rotation_lambda_arn", "rotation_rules",
+            "last_rotated_at", "next_rotation_at", ?4, "arn"
+        FROM "secrets"
+        WHERE "arn" = ?3
+        ON CONFLICT("arn")
+        DO UPDATE SET
+            "description" = "excluded"."description",
+            "updated_at" = "excluded"."updated_at",
+            "deleted_at" = "excluded"."deleted_at",
+            "scheduled_delete_at" = "excluded"."scheduled_delete_at",
+            "rotation_enabled" = "excluded"."rotation_enabled",
+            "rotation_lambda_arn" = "excluded"."rotation_lambda_arn",
+            "rotation_rules" = "excluded"."rotation_rules",
+            "last_rotated_at" = "excluded"."last_rotated_at",
+            "next_rotation_at" = "excluded"."next_rotation_at",
+            "kms_key_id" = "excluded"."kms_key_id",
+            "primary_arn" = "excluded"."primary_arn"
+        "#,
+        params![replica_arn, region, secret_arn, kms_key_id],
+    )?;
+
+    // Clear the existing replica state
+    for table in [
+        "secret_version_stages",
+        "secrets_tags",
+        "secrets_resource_policies",
+    ] {
+        db.execute(
+            &format!(r#"DELETE FROM "{table}" WHERE "secret_arn" = ?"#),
+            params![replica_arn],
+        )?;
+    }
+
+    db.execute(
+        r#"
+        DELETE FROM "secrets_versions"
+        WHERE "secret_arn" = ?1
+            AND (
+                "kms_key_id" IS NOT ?3
+                OR "version_id" NOT IN (
+                    SELECT "version_id" FROM "secrets_versions" WHERE "secret_arn" = ?2
+                )
+            )
+        "#,
+        params![replica_arn, secret_arn, kms_key_id],
+    )?;
+
+    Ok(())
+}
+
+/// Version of a primary secret that is missing from its replica
+pub struct StoredReplicaSecretVersion {
+    pub version_id: String,
+    pub secret_string: Option<String>,
+    pub secret_binary: Option<String>,
+    pub encrypted_value: Option<EncryptedSecretValue>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'a> TryFrom<&'a Row<'a>> for StoredReplicaSecretVersion {
+    type Error = rusqlite::Error;
+
+    fn try_from(value: &'a Row<'a>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            version_id: value.get("version_id")?,
+            secret_string: value.get("secret_string")?,
+            secret_binary: value.get("secret_binary")?,
+            encrypted_value: EncryptedSecretValue::try_from_row(value)?,
+            created_at: value.get("created_at")?,
+        })
+    }
+}
+
+/// Get the versions of the secret with the `secret_arn` that are missing from its
+/// replica with the `replica_arn`
+pub fn get_secret_versions_missing_from_replica(
+    db: &Connection,
+    secret_arn: &str,
+    replica_arn: &str,
+) -> DbResult<Vec<StoredReplicaSecretVersion>> {
+    db.prepare(
+        r#"
+        SELECT
+            "secret_version"."version_id",
+            "secret_version"."secret_string",
+            "secret_version"."secret_binary",
+            "secret_version"."kms_key_id" AS "version_kms_key_id",
+            "secret_version"."encrypted_data_key",
+            "secret_version"."encrypted_value",
+            "secret_version"."created_at"
+        FROM "secrets_versions" "secret_version"
+        WHERE "secret_version"."secret_arn" = ?1
+            AND "secret_version"."version_id" NOT IN (
+                SELECT "version_id" FROM "secrets_versions" WHERE "secret_arn" = ?2
+            )
+        ORDER BY "secret_version"."created_at" ASC
+        "#,
+    )?
+    .query_map(params![secret_arn, replica_arn], |row| {
+        StoredReplicaSecretVersion::try_from(row)
+    })?
+    .try_collect()
+}
+
+pub struct CreateReplicaSecretVersion {
+    pub replica_arn: String,
+    pub version_id: String,
+    pub created_at: DateTime<Utc>,
+    //
+    pub secret_string: Option<String>,
+    pub secret_binary: Option<String>,
+    pub encrypted_value: Option<EncryptedSecretValue>,
+}
+
+/// Creates a copy of a primary secret version on its replica keeping the creation date
+/// of the primary version, the plain text value is not stored when the version has an
+/// encrypted value
+pub fn create_replica_secret_version(
+    db: &Connection,
+    create: CreateReplicaSecretVersion,
+) -> DbResult<()> {
+    let (secret_string, secret_binary, kms_key_id, encrypted_data_key, encrypted_value) =
+        match create.encrypted_value {
+            Some(value) => (
+                None,
+                None,
+                Some(value.kms_key_id),
+                Some(value.encrypted_data_key),
+                Some(value.encrypted_value),
+            ),
+            None => (create.secret_string, create.secret_binary, None, None, None),
+        };
+
+    db.execute(
+        r#"
+        INSERT INTO "secrets_versions" (
+            "secret_arn", "version_id", "secret_string", "secret_binary", "kms_key_id",
+            "encrypted_data_key", "encrypted_value", "created_at"
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        params![
+            create.replica_arn,
+            create.version_id,
+            secret_string,
+            secret_binary,
+            kms_key_id,
+            encrypted_data_key,
+            encrypted_value,
+            create.created_at
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Copy the version stages, tags and resource policy of the secret with the `secret_arn`
+/// into its replica with the `replica_arn`, the versions must already have been copied
+pub fn copy_secret_replica_state(
+    db: &Connection,
+    secret_arn: &str,
+    replica_arn: &str,
+) -> DbResult<()> {
+    db.execute(
+        r#"
+        INSERT INTO "secret_version_stages" ("secret_arn", "version_id", "value", "created_at")
+        SELECT ?1, "version_id", "value", "created_at"
+        FROM "secret_version_stages"
+        WHERE "secret_arn" = ?2
+        "#,
+        params![replica_arn, secret_arn],
+    )?;
+
+    db.execute(
+        r#"
+        INSERT INTO "secrets_tags" ("secret_arn", "key", "value", "created_at", "updated_at")
+        SELECT ?1, "key", "value", "created_at", "updated_at"
+        FROM "secrets_tags"
+        WHERE "secret_arn" = ?2
+        "#,
+        params![replica_arn, secret_arn],
+    )?;
+
+    db.execute(
+        r#"
+        INSERT INTO "secrets_resource_policies" ("secret_arn", "policy", "created_at", "updated_at")
+        SELECT ?1, "policy", "created_at", "updated_at"
+        FROM "secrets_resource_policies"
+        WHERE "secret_arn" = ?2
+        "#,
+        params![replica_arn, secret_arn],
+    )?;
+
+    Ok(())
+}
+
+/// Replica of a secret along with the KMS key its versions are encrypted with
+pub struct StoredReplicaTarget {
+    pub arn: String,
+    pub region: String,
+    pub kms_key_id: Option<String>,
+}
+
+/// Get the replicas of the secret with the `secret_arn` that are kept in sync, replicas
+/// whose last replication failed are left as they are
+pub fn get_secret_replica_targets(
+    db: &Connection,
+    secret_arn: &str,
+) -> DbResult<Vec<StoredReplicaTarget>> {
+    db.prepare(
+        r#"
+        SELECT "replica_secret"."arn", "replica_secret"."region", "secret_replica"."kms_key_id"
+        FROM "secrets" "replica_secret"
+        JOIN "secrets_replicas" "secret_replica"
+            ON "secret_replica"."secret_arn" = "replica_secret"."primary_arn"
+                AND "secret_replica"."region" = "replica_secret"."region"
+        WHERE "replica_secret"."primary_arn" = ? AND "secret_replica"."status" = 'InSync'
+        "#,
+    )?
+    .query_map(params![secret_arn], |row| {
+        Ok(StoredReplicaTarget {
+            arn: row.get(0)?,
+            region: row.get(1)?,
+            kms_key_id: row.get(2)?,
+        })
+    })?
+    .try_collect()
+}
+
+/// Promote a replica secret to a standalone secret that is no longer replicated
+/// from its primary secret
+pub fn promote_secret_replica(db: &Connection, replica_arn: &str) -> DbResult<usize> {
+    db.execute(
+        r#"UPDATE "secrets" SET "primary_arn" = NULL WHERE "arn" = ?"#,
+        params![replica_arn],
+    )
+}
+
 /// Set a tag on a secret
 pub fn put_secret_tag(db: &Connection, secret_arn: &str, key: &str, value: &str) -> DbResult<()> {
     let now = Utc::now();
@@ -299,18 +1001,41 @@ pub struct CreateSecretVersion {
     //
     pub secret_string: Option<String>,
     pub secret_binary: Option<String>,
+    pub encrypted_value: Option<EncryptedSecretValue>,
 }
 
-/// Creates a new version of a secret
+/// Creates a new version of a secret, the plain text value is not stored
+/// when the version has an encrypted value
 pub fn create_secret_version(db: &Connection, create: CreateSecretVersion) -> DbResult<()> {
     let now = Utc::now();
 
+    let (secret_string, secret_binary, kms_key_id, encrypted_data_key, encrypted_value) =
+        match create.encrypted_value {
+            Some(value) => (
+                None,
+                None,
+                Some(value.kms_key_id),
+                Some(value.encrypted_data_key),
+                Some(value.encrypted_value),
+            ),
+            None => (create.secret_string, create.secret_binary, None, None, None),
+        };
+
     db.execute(
         r#"
-        INSERT INTO "secrets_versions" ("secret_arn", "version_id", "secret_string", "secret_binary", "created_at")
-        VALUES (?, ?, ?, ?, ?)
+        INSERT INTO "secrets_versions" ("secret_arn", "version_id", "secret_string", "secret_binary", "kms_key_id", "encrypted_data_key", "encrypted_value", "created_at")
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         "#,
-        params![create.secret_arn, create.version_id, create.secret_string, create.secret_binary, now]
+        params![
+            create.secret_arn,
+            create.version_id,
+            secret_string,
+            secret_binary,
+            kms_key_id,
+            encrypted_data_key,
+            encrypted_value,
+            now
+        ],
     )?;
 
     Ok(())
@@ -385,12 +1110,13 @@ pub fn remove_secret_version_stage_any(
     )
 }
 
-/// Get the current version of a secret where the name OR arn matches the `secret_id`
+/// Get the current version of a secret in `region` where the name OR arn matches the `secret_id`
 pub fn get_secret_latest_version(
     db: &Connection,
+    region: &str,
     secret_id: &str,
 ) -> DbResult<Option<StoredSecret>> {
-    get_secret_by_version_stage(db, secret_id, "AWSCURRENT")
+    get_secret_by_version_stage(db, region, secret_id, "AWSCURRENT")
 }
 
 /// Check if the value is a partial arn
@@ -415,10 +1141,11 @@ fn make_partial_arn_like_query(value: &str) -> Option<String> {
     }
 }
 
-/// Get a secret where the name OR arn matches the `secret_id` and there is a version
+/// Get a secret in `region` where the name OR arn matches the `secret_id` and there is a version
 /// with the version ID of `version_id`
 pub fn get_secret_by_version_id(
     db: &Connection,
+    region: &str,
     secret_id: &str,
     version_id: &str,
 ) -> DbResult<Option<StoredSecret>> {
@@ -431,6 +1158,9 @@ pub fn get_secret_by_version_id(
             "secret_version"."version_id",
             "secret_version"."secret_string",
             "secret_version"."secret_binary",
+            "secret_version"."kms_key_id" AS "version_kms_key_id",
+            "secret_version"."encrypted_data_key",
+            "secret_version"."encrypted_value",
             "secret_version"."created_at" AS "version_created_at",
             "secret_version"."last_accessed_at" AS "version_last_accessed_at",
             COALESCE((
@@ -455,12 +1185,13 @@ pub fn get_secret_by_version_id(
         JOIN "secrets_versions" "secret_version"
             ON "secret_version"."secret_arn" = "secret"."arn"
             AND "secret_version"."version_id" = ?
-        WHERE "secret"."name" = ? OR "secret"."arn" = ?
-            OR (? = TRUE AND "secret"."arn" LIKE ?)
+        WHERE "secret"."region" = ? AND ("secret"."name" = ? OR "secret"."arn" = ?
+            OR (? = TRUE AND "secret"."arn" LIKE ?))
         LIMIT 1;
     "#,
         params![
             version_id,
+            region,
             secret_id,
             secret_id,
             partial_arn.is_some(),
@@ -471,10 +1202,11 @@ pub fn get_secret_by_version_id(
     .optional()
 }
 
-/// Get a secret where the name OR arn matches the `secret_id` and there is a version
+/// Get a secret in `region` where the name OR arn matches the `secret_id` and there is a version
 /// in `version_stage`
 pub fn get_secret_by_version_stage(
     db: &Connection,
+    region: &str,
     secret_id: &str,
     version_stage: &str,
 ) -> DbResult<Option<StoredSecret>> {
@@ -487,6 +1219,9 @@ pub fn get_secret_by_version_stage(
             "secret_version"."version_id",
             "secret_version"."secret_string",
             "secret_version"."secret_binary",
+            "secret_version"."kms_key_id" AS "version_kms_key_id",
+            "secret_version"."encrypted_data_key",
+            "secret_version"."encrypted_value",
             "secret_version"."created_at" AS "version_created_at",
             "secret_version"."last_accessed_at" AS "version_last_accessed_at",
             COALESCE((
@@ -514,13 +1249,14 @@ pub fn get_secret_by_version_stage(
             ON "version_stage"."secret_arn" = "secret_version"."secret_arn"
             AND "version_stage"."version_id" = "secret_version"."version_id"
             AND "version_stage"."value" = ?
-        WHERE "secret"."name" = ? OR "secret"."arn" = ?
-            OR (? = TRUE AND "secret"."arn" LIKE ?)
+        WHERE "secret"."region" = ? AND ("secret"."name" = ? OR "secret"."arn" = ?
+            OR (? = TRUE AND "secret"."arn" LIKE ?))
         ORDER BY "secret_version"."created_at" DESC
         LIMIT 1;
     "#,
         params![
             version_stage,
+            region,
             secret_id,
             secret_id,
             partial_arn.is_some(),
@@ -531,10 +1267,11 @@ pub fn get_secret_by_version_stage(
     .optional()
 }
 
-/// Get a secret where the name OR arn matches the `secret_id` and there is a version
+/// Get a secret in `region` where the name OR arn matches the `secret_id` and there is a version
 /// in `version_stage` with the version ID `version_id`
 pub fn get_secret_by_version_stage_and_id(
     db: &Connection,
+    region: &str,
     secret_id: &str,
     version_id: &str,
     version_stage: &str,
@@ -548,6 +1285,9 @@ pub fn get_secret_by_version_stage_and_id(
             "secret_version"."version_id",
             "secret_version"."secret_string",
             "secret_version"."secret_binary",
+            "secret_version"."kms_key_id" AS "version_kms_key_id",
+            "secret_version"."encrypted_data_key",
+            "secret_version"."encrypted_value",
             "secret_version"."created_at" AS "version_created_at",
             "secret_version"."last_accessed_at" AS "version_last_accessed_at",
             COALESCE((
@@ -575,13 +1315,14 @@ pub fn get_secret_by_version_stage_and_id(
             ON "version_stage"."secret_arn" = "secret_version"."secret_arn"
             AND "version_stage"."version_id" = "secret_version"."version_id"
             AND "version_stage"."value" = ?
-        WHERE "secret"."name" = ? OR "secret"."arn" = ?
-            OR (? = TRUE AND "secret"."arn" LIKE ?)
+        WHERE "secret"."region" = ? AND ("secret"."name" = ? OR "secret"."arn" = ?
+            OR (? = TRUE AND "secret"."arn" LIKE ?))
         LIMIT 1;
     "#,
         params![
             version_id,
             version_stage,
+            region,
             secret_id,
             secret_id,
             partial_arn.is_some(),
@@ -595,7 +1336,7 @@ pub fn get_secret_by_version_stage_and_id(
 /// Generates the WHERE portion of a filtered query appending it to `query` returning
 /// a list of parameters that need to be bound to the query
 ///
-/// Assumes `query` has already specified the WHERE and at least one clause like 1=1
+/// Assumes `query` has already specified the WHERE and at least one clause
 fn push_secret_filter_where(filters: &[Filter], query: &mut String) -> Vec<String> {
     let mut bound_values: Vec<String> = Vec::new();
 
@@ -712,6 +1453,37 @@ fn push_secret_filter_where(filters: &[Filter], query: &mut String) -> Vec<Strin
                 query.push(')');
             }
 
+            "primary-region" => {
+                // Replicas are matched by the region of their primary, primary secrets
+                // are matched by their own region when they have replicas
+                query.push_str(
+                    r#" AND (EXISTS (
+                    SELECT 1 FROM "secrets" AS "primary_secret"
+                    WHERE "primary_secret"."arn" = "secret"."primary_arn"
+                    AND (
+                "#,
+                );
+                write_condition_cs(
+                    query,
+                    &mut bound_values,
+                    r#""primary_secret"."region""#,
+                    &filter.values,
+                );
+                query.push_str(
+                    r#")) OR (EXISTS (
+                    SELECT 1 FROM "secrets_replicas" AS "secret_replica"
+                    WHERE "secret_replica"."secret_arn" = "secret"."arn"
+                ) AND ("#,
+                );
+                write_condition_cs(
+                    query,
+                    &mut bound_values,
+                    r#""secret"."region""#,
+                    &filter.values,
+                );
+                query.push_str(")))");
+            }
+
             "description" => {
                 query.push_str(" AND (");
                 write_condition_ci(
@@ -765,13 +1537,14 @@ fn push_secret_filter_where(filters: &[Filter], query: &mut String) -> Vec<Strin
     bound_values
 }
 
-/// Get secrets filtered using the provided `filters`, will only include secrets planned for deletion
-/// if `include_planned_deletions` is true.
+/// Get secrets in `region` filtered using the provided `filters`, will only include secrets planned
+/// for deletion if `include_planned_deletions` is true.
 ///
 /// Paginated using the provided `limit` and `offset` use `asc` to order the results by creation date
 /// in ascending order, false to order descending
 pub fn get_secrets_by_filter(
     db: &Connection,
+    region: &str,
     filters: &[Filter],
     include_planned_deletions: bool,
     limit: i64,
@@ -784,6 +1557,9 @@ pub fn get_secrets_by_filter(
             "secret_version"."version_id",
             "secret_version"."secret_string",
             "secret_version"."secret_binary",
+            "secret_version"."kms_key_id" AS "version_kms_key_id",
+            "secret_version"."encrypted_data_key",
+            "secret_version"."encrypted_value",
             "secret_version"."created_at" AS "version_created_at",
             "secret_version"."last_accessed_at" AS "version_last_accessed_at",
             COALESCE((
@@ -824,14 +1600,25 @@ pub fn get_secrets_by_filter(
                     AND "version_stage"."version_id" = "secret_version"."version_id"
                     AND "version_stage"."value" = 'AWSCURRENT'
                 WHERE "secret_version"."secret_arn" = "secret"."arn"
-            ), '[]') AS "versions"
+            ), '[]') AS "versions",
+            CASE
+                WHEN "secret"."primary_arn" IS NOT NULL THEN (
+                    SELECT "primary_secret"."region"
+                    FROM "secrets" "primary_secret"
+                    WHERE "primary_secret"."arn" = "secret"."primary_arn"
+                )
+                WHEN EXISTS (
+                    SELECT 1 FROM "secrets_replicas" "secret_replica"
+                    WHERE "secret_replica"."secret_arn" = "secret"."arn"
+                ) THEN "secret"."region"
+            END AS "primary_region"
         FROM "secrets" "secret"
         JOIN "secrets_versions" "secret_version" ON "secret_version"."secret_arn" = "secret"."arn"
         JOIN "secret_version_stages" "version_stage"
             ON "version_stage"."secret_arn" = "secret_version"."secret_arn"
             AND "version_stage"."version_id" = "secret_version"."version_id"
             AND "version_stage"."value" = 'AWSCURRENT'
-        WHERE 1=1
+        WHERE "secret"."region" = ?
     "#
     .to_string();
 
@@ -854,9 +1641,9 @@ pub fn get_secrets_by_filter(
     db.prepare(&query)?
         .query_map(
             params_from_iter(
-                bound_values
-                    .iter()
-                    .map(|value| value as &dyn ToSql)
+                [&region as &dyn ToSql]
+                    .into_iter()
+                    .chain(bound_values.iter().map(|value| value as &dyn ToSql))
                     .chain([&limit as &dyn ToSql, &offset as &dyn ToSql]),
             ),
             |row| StoredSecretWithVersionStages::try_from(row),
@@ -864,10 +1651,11 @@ pub fn get_secrets_by_filter(
         .try_collect()
 }
 
-/// Get the total number of secrets filtered using the provided `filters`, will only include secrets planned for deletion
-/// if `include_planned_deletions` is true.
+/// Get the total number of secrets in `region` filtered using the provided `filters`, will only
+/// include secrets planned for deletion if `include_planned_deletions` is true.
 pub fn get_secrets_count_by_filter(
     db: &Connection,
+    region: &str,
     filters: &[Filter],
     include_planned_deletions: bool,
 ) -> DbResult<i64> {
@@ -879,7 +1667,7 @@ pub fn get_secrets_count_by_filter(
             ON "version_stage"."secret_arn" = "secret_version"."secret_arn"
             AND "version_stage"."version_id" = "secret_version"."version_id"
             AND "version_stage"."value" = 'AWSCURRENT'
-        WHERE 1=1
+        WHERE "secret"."region" = ?
     "#
     .to_string();
 
@@ -891,7 +1679,11 @@ pub fn get_secrets_count_by_filter(
 
     db.query_one(
         &query,
-        params_from_iter(bound_values.iter().map(|value| value as &dyn ToSql)),
+        params_from_iter(
+            [&region as &dyn ToSql]
+                .into_iter()
+                .chain(bound_values.iter().map(|value| value as &dyn ToSql)),
+        ),
         |row| row.get::<_, i64>(0),
     )
 }
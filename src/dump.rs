@@ -0,0 +1,272 @@
+//! Export and import of the secrets within the database using a JSON dump format,
+//! values of encrypted secret versions are decrypted when exported and encrypted
+//! again using the KMS key of the secret when imported
+
+use crate::{
+    database::{
+        DbErr,
+        secrets::{
+            CreateSecret, CreateSecretVersion, add_secret_version_stage, create_secret,
+            create_secret_version, get_secret_arn, get_secret_by_version_id, get_secret_regions,
+            get_secret_versions, get_secrets_by_filter, put_secret_tag,
+            remove_secret_version_stage_any, update_secret_description,
+        },
+    },
+    kms::{decrypt_secret_value, encrypt_secret_value},
+    replication::sync_secret_replicas,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio_rusqlite::rusqlite::Connection;
+
+/// Number of secrets loaded at a time while exporting
+const EXPORT_PAGE_SIZE: i64 = 100;
+
+/// Dump of the secrets within the database
+#[derive(Default, Serialize, Deserialize)]
+pub struct SecretsDump {
+    pub secrets: Vec<DumpSecret>,
+}
+
+/// Secret within a [SecretsDump]
+#[derive(Serialize, Deserialize)]
+pub struct DumpSecret {
+    pub arn: String,
+    pub region: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// ARN of the KMS key new versions of the secret are encrypted with
+    #[serde(default)]
+    pub kms_key_id: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<DumpTag>,
+    /// Versions of the secret from oldest to newest
+    pub versions: Vec<DumpSecretVersion>,
+}
+
+/// Tag on a [DumpSecret]
+#[derive(Serialize, Deserialize)]
+pub struct DumpTag {
+    pub key: String,
+    pub value: String,
+}
+
+/// Version of a [DumpSecret]
+#[derive(Serialize, Deserialize)]
+pub struct DumpSecretVersion {
+    pub version_id: String,
+    #[serde(default)]
+    pub secret_string: Option<String>,
+    /// Base64 encoded secret binary
+    #[serde(default)]
+    pub secret_binary: Option<String>,
+    #[serde(default)]
+    pub version_stages: Vec<String>,
+}
+
+/// Outcome of importing a [SecretsDump]
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    /// Number of secrets that were created
+    pub secrets_created: usize,
+    /// Number of secret versions that were created
+    pub versions_created: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum DumpError {
+    #[error(transparent)]
+    Db(#[from] DbErr),
+
+    #[error("failed to decrypt version {1} of secret {0}")]
+    Decrypt(String, String),
+
+    #[error("failed to encrypt version {1} of secret {0}, does its KMS key exist?")]
+    Encrypt(String, String),
+
+    #[error("failed to update the replicas of secret {0}")]
+    Replicate(String),
+
+    #[error("secret {0} already exists in {1} with a different ARN")]
+    NameConflict(String, String),
+
+    #[error("version {1} of secret {0} already exists with a different value")]
+    VersionConflict(String, String),
+}
+
+/// Export all secrets within the database, replicas and secrets scheduled for
+/// deletion are not included
+pub fn export_secrets(db: &Connection) -> Result<SecretsDump, DumpError> {
+    let mut dump = SecretsDump::default();
+
+    for region in get_secret_regions(db)? {
+        let mut offset = 0;
+
+        loop {
+            let secrets =
+                get_secrets_by_filter(db, &region, &[], false, EXPORT_PAGE_SIZE, offset, true)?;
+            let is_last_page = (secrets.len() as i64) < EXPORT_PAGE_SIZE;
+            offset += EXPORT_PAGE_SIZE;
+
+            for secret in secrets {
+                // Replicas are recreated by replicating their primary secret
+                if secret.primary_arn.is_some() {
+                    continue;
+                }
+
+                let mut versions = Vec::new();
+
+                // Versions are loaded newest first
+                for version in get_secret_versions(db, &secret.arn)?.into_iter().rev() {
+                    let (secret_string, secret_binary) = if version.kms_key_id.is_some() {
+                        let mut stored = get_secret_by_version_id(
+                            db,
+                            &region,
+                            &secret.arn,
+                            &version.version_id,
+                        )?
+                        .ok_or(DbErr::QueryReturnedNoRows)?;
+
+                        decrypt_secret_value(db, &mut stored).map_err(|_| {
+                            DumpError::Decrypt(secret.name.clone(), version.version_id.clone())
+                        })?;
+
+                        (stored.secret_string, stored.secret_binary)
+                    } else {
+                        (version.secret_string, version.secret_binary)
+                    };
+
+                    versions.push(DumpSecretVersion {
+                        version_id: version.version_id,
+                        secret_string,
+                        secret_binary,
+                        version_stages: version.version_stages,
+                    });
+                }
+
+                dump.secrets.push(DumpSecret {
+                    arn: secret.arn,
+                    region: region.clone(),
+                    name: secret.name,
+                    description: secret.description,
+                    kms_key_id: secret.kms_key_id,
+                    tags: secret
+                        .version_tags
+                        .into_iter()
+                        .map(|tag| DumpTag {
+                            key: tag.key,
+                            value: tag.value,
+                        })
+                        .collect(),
+                    versions,
+                });
+            }
+
+            if is_last_page {
+                break;
+            }
+        }
+    }
+
+    Ok(dump)
+}
+
+/// Import the secrets from a `dump`, secrets and versions that already exist are left
+/// as is so the same dump can be imported multiple times. The description, tags and
+/// version stages of each secret are updated to match the dump.
+///
+/// Should be performed within a transaction so a failed import doesn't leave the
+/// database partially imported
+pub fn import_secrets(db: &Connection, dump: SecretsDump) -> Result<ImportSummary, DumpError> {
+    let mut summary = ImportSummary::default();
+
+    for secret in dump.secrets {
+        match get_secret_arn(db, &secret.region, &secret.name)? {
+            Some(arn) if arn.ne(&secret.arn) => {
+                return Err(DumpError::NameConflict(secret.name, secret.region));
+            }
+
+            Some(_) => {
+                if let Some(description) = &secret.description {
+                    update_secret_description(db, &secret.arn, description)?;
+                }
+            }
+
+            None => {
+                create_secret(
+                    db,
+                    CreateSecret {
+                        arn: secret.arn.clone(),
+                        region: secret.region.clone(),
+                        name: secret.name.clone(),
+                        description: secret.description.clone(),
+                        kms_key_id: secret.kms_key_id.clone(),
+                    },
+                )?;
+
+                summary.secrets_created += 1;
+            }
+        }
+
+        for version in &secret.versions {
+            let existing =
+                get_secret_by_version_id(db, &secret.region, &secret.arn, &version.version_id)?;
+
+            if let Some(mut existing) = existing {
+                decrypt_secret_value(db, &mut existing).map_err(|_| {
+                    DumpError::Decrypt(secret.name.clone(), version.version_id.clone())
+                })?;
+
+                if !existing.is_value_eq(&version.secret_string, &version.secret_binary) {
+                    return Err(DumpError::VersionConflict(
+                        secret.name,
+                        version.version_id.clone(),
+                    ));
+                }
+
+                continue;
+            }
+
+            let encrypted_value = encrypt_secret_value(
+                db,
+                secret.kms_key_id.as_deref(),
+                &secret.arn,
+                &version.version_id,
+                &version.secret_string,
+                &version.secret_binary,
+            )
+            .map_err(|_| DumpError::Encrypt(secret.name.clone(), version.version_id.clone()))?;
+
+            create_secret_version(
+                db,
+                CreateSecretVersion {
+                    secret_arn: secret.arn.clone(),
+                    version_id: version.version_id.clone(),
+                    secret_string: version.secret_string.clone(),
+                    secret_binary: version.secret_binary.clone(),
+                    encrypted_value,
+                },
+            )?;
+
+            summary.versions_created += 1;
+        }
+
+        // Move the version stages to the versions from the dump
+        for version in &secret.versions {
+            for version_stage in &version.version_stages {
+                remove_secret_version_stage_any(db, &secret.arn, version_stage)?;
+                add_secret_version_stage(db, &secret.arn, &version.version_id, version_stage)?;
+            }
+        }
+
+        for tag in &secret.tags {
+            put_secret_tag(db, &secret.arn, &tag.key, &tag.value)?;
+        }
+
+        sync_secret_replicas(db, &secret.arn)
+            .map_err(|_| DumpError::Replicate(secret.arn.clone()))?;
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,342 @@
+//! Fault injection for testing how clients handle Secrets Manager outages, faults
+//! are configured at runtime through the admin routes and applied to matching
+//! requests before they reach the handlers
+
+use crate::handlers::error::{self, IntoErrorResponse};
+use axum::{body::Body, http::Request, response::Response};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http_body_util::BodyExt;
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    mem::swap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+/// Maximum latency a single fault can inject (5 minutes)
+const MAX_FAULT_LATENCY_MS: u64 = 1000 * 60 * 5;
+
+/// Error responded with by a [Fault::Error] fault
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum FaultError {
+    ThrottlingException,
+    InternalServiceError,
+    LimitExceededException,
+}
+
+impl FaultError {
+    fn into_error_response(self) -> Response {
+        match self {
+            FaultError::ThrottlingException => error::ThrottlingException.into_error_response(),
+            FaultError::InternalServiceError => error::InternalServiceError.into_error_response(),
+            FaultError::LimitExceededException => {
+                error::LimitExceededException.into_error_response()
+            }
+        }
+    }
+}
+
+/// Fault to apply to a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fault {
+    /// Delay handling the request by `delay_ms` milliseconds
+    Latency { delay_ms: u64 },
+    /// Respond with the `error` instead of handling the request
+    Error { error: FaultError },
+    /// Close the connection without completing the response
+    DropConnection,
+}
+
+/// Rule describing a fault and the requests it applies to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaultRule {
+    /// Target the fault applies to (i.e secretsmanager.GetSecretValue), applies to
+    /// all targets when not specified
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Name of the secret the fault applies to, applies to all requests when not specified
+    #[serde(default)]
+    pub secret_name: Option<String>,
+    /// Probability from 0 to 1 of the fault being applied to a matching request
+    #[serde(default = "default_probability")]
+    pub probability: f64,
+    pub fault: Fault,
+}
+
+fn default_probability() -> f64 {
+    1.0
+}
+
+impl FaultRule {
+    /// Check that the probability and latency of the rule are within the allowed range
+    pub fn is_valid(&self) -> bool {
+        if !(0.0..=1.0).contains(&self.probability) {
+            return false;
+        }
+
+        match &self.fault {
+            Fault::Latency { delay_ms } => *delay_ms <= MAX_FAULT_LATENCY_MS,
+            Fault::Error { .. } | Fault::DropConnection => true,
+        }
+    }
+
+    /// Check if the rule applies to a request for the `target` against the `secret_id`
+    fn is_match(&self, target: Option<&str>, secret_id: Option<&str>) -> bool {
+        if let Some(rule_target) = &self.target
+            && target.is_none_or(|target| target != rule_target)
+        {
+            return false;
+        }
+
+        if let Some(secret_name) = &self.secret_name
+            && secret_id.is_none_or(|secret_id| !is_secret_id_match(secret_name, secret_id))
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Check if a `secret_id` (Name or ARN) identifies the secret named `secret_name`
+fn is_secret_id_match(secret_name: &str, secret_id: &str) -> bool {
+    if secret_id == secret_name {
+        return true;
+    }
+
+    // Secret ARNs end with the name followed by a hyphen and 6 random characters
+    secret_id
+        .split_once(":secret:")
+        .and_then(|(_, name)| name.get(..name.len().checked_sub(7)?))
+        .is_some_and(|name| name == secret_name)
+}
+
+/// Fault rule along with its ID
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredFaultRule {
+    pub id: String,
+    #[serde(flatten)]
+    pub rule: FaultRule,
+}
+
+/// Faults selected for a request
+#[derive(Default)]
+struct SelectedFaults {
+    /// Total latency to inject before handling the request
+    latency: Duration,
+    /// Fault that replaces the response
+    failure: Option<Fault>,
+}
+
+/// Collection of the active fault rules, shared between the [FaultMiddleware]
+/// and the admin routes that manage the rules
+#[derive(Clone, Default)]
+pub struct FaultStore {
+    rules: Arc<RwLock<Vec<StoredFaultRule>>>,
+}
+
+impl FaultStore {
+    /// Add a fault rule, provides the ID of the rule
+    pub fn add(&self, rule: FaultRule) -> String {
+        let id = Uuid::new_v4().to_string();
+
+        self.rules
+            .write()
+            .expect("fault store lock poisoned")
+            .push(StoredFaultRule {
+                id: id.clone(),
+                rule,
+            });
+
+        id
+    }
+
+    /// Get all the fault rules in the order they were added
+    pub fn list(&self) -> Vec<StoredFaultRule> {
+        self.rules
+            .read()
+            .expect("fault store lock poisoned")
+            .clone()
+    }
+
+    /// Remove the fault rule with the `id`, provides false if the rule didn't exist
+    pub fn remove(&self, id: &str) -> bool {
+        let mut rules = self.rules.write().expect("fault store lock poisoned");
+        let length = rules.len();
+        rules.retain(|rule| rule.id != id);
+        rules.len() != length
+    }
+
+    /// Remove all the fault rules
+    pub fn clear(&self) {
+        self.rules
+            .write()
+            .expect("fault store lock poisoned")
+            .clear();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rules
+            .read()
+            .expect("fault store lock poisoned")
+            .is_empty()
+    }
+
+    /// Select the faults to apply to a request for the `target` against the `secret_id`,
+    /// the latency of all matching latency faults is applied while only the first
+    /// matching error or dropped connection fault is applied
+    fn select(&self, target: Option<&str>, secret_id: Option<&str>) -> SelectedFaults {
+        let rules = self.rules.read().expect("fault store lock poisoned");
+        let mut rng = rand::rng();
+        let mut selected = SelectedFaults::default();
+
+        for StoredFaultRule { rule, .. } in rules.iter() {
+            if !rule.is_match(target, secret_id) || !rng.random_bool(rule.probability) {
+                continue;
+            }
+
+            match &rule.fault {
+                Fault::Latency { delay_ms } => {
+                    selected.latency += Duration::from_millis(*delay_ms);
+                }
+                fault => {
+                    selected.failure = Some(fault.clone());
+                    break;
+                }
+            }
+        }
+
+        selected
+    }
+}
+
+/// Fields of a request body used to determine the secret a request is for
+#[derive(Deserialize)]
+struct FaultRequest {
+    #[serde(rename = "SecretId")]
+    secret_id: Option<String>,
+    #[serde(rename = "Name")]
+    name: Option<String>,
+}
+
+/// Layer applying the faults from a [FaultStore] to requests
+#[derive(Clone)]
+pub struct FaultLayer {
+    faults: FaultStore,
+}
+
+impl FaultLayer {
+    /// Create a fault layer applying the faults from the `faults` store
+    pub fn new(faults: FaultStore) -> Self {
+        Self { faults }
+    }
+}
+
+impl<S> Layer<S> for FaultLayer {
+    type Service = FaultMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FaultMiddleware {
+            inner,
+            faults: self.faults.clone(),
+        }
+    }
+}
+
+/// Middleware structure
+#[derive(Clone)]
+pub struct FaultMiddleware<S> {
+    inner: S,
+    faults: FaultStore,
+}
+
+impl<S> Service<Request<Body>> for FaultMiddleware<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let faults = self.faults.clone();
+
+        // Swap to ensure we get the service that was ready and not the cloned one
+        swap(&mut inner, &mut self.inner);
+
+        // Nothing to apply when there are no faults
+        if faults.is_empty() {
+            return Box::pin(inner.call(req));
+        }
+
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+
+            let target = parts
+                .headers
+                .get("x-amz-target")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+
+            let body = match body.collect().await {
+                Ok(value) => value.to_bytes(),
+                Err(error) => {
+                    tracing::error!(?error, "failed to collect bytes");
+                    return Ok(error::InternalServiceError.into_error_response());
+                }
+            };
+
+            let secret_id = serde_json::from_slice::<FaultRequest>(&body)
+                .ok()
+                .and_then(|request| request.secret_id.or(request.name));
+
+            let selected = faults.select(target.as_deref(), secret_id.as_deref());
+
+            if !selected.latency.is_zero() {
+                tokio::time::sleep(selected.latency).await;
+            }
+
+            match selected.failure {
+                Some(Fault::Error { error }) => {
+                    tracing::debug!(?target, ?error, "injecting error fault");
+                    return Ok(error.into_error_response());
+                }
+                Some(Fault::DropConnection) => {
+                    tracing::debug!(?target, "injecting dropped connection fault");
+                    return Ok(dropped_connection_response());
+                }
+                Some(Fault::Latency { .. }) | None => {}
+            }
+
+            // Re-create the body since we consumed the previous one
+            let request = Request::from_parts(parts, Body::from(body));
+
+            inner.call(request).await
+        })
+    }
+}
+
+/// Create a response with a body that fails immediately, causing the server
+/// to close the connection without completing the response
+fn dropped_connection_response() -> Response {
+    let body = Body::from_stream(futures::stream::once(async {
+        Err::<Bytes, _>(std::io::Error::other("injected dropped connection fault"))
+    }));
+
+    Response::new(body)
+}
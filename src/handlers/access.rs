@@ -0,0 +1,169 @@
+use crate::{
+    database::secrets::{get_secret_latest_version, get_secret_resource_policy},
+    handlers::{
+        HandlerContext,
+        error::{AccessDeniedException, AwsError, InternalServiceError},
+    },
+    middleware::aws_sig_v4::AuthenticatedIdentity,
+    policy::{
+        PolicyDocument, PolicyKind,
+        evaluate::{Decision, RequestContext},
+    },
+};
+use std::collections::HashMap;
+use tokio_rusqlite::rusqlite::Connection;
+
+/// Authorize the identity making the request to perform `action` against
+/// the secret with the `secret_id` (or any resource when not specified)
+pub async fn authorize(
+    ctx: &HandlerContext,
+    action: &str,
+    secret_id: Option<&str>,
+) -> Result<(), AwsError> {
+    let identity = ctx.identity.clone();
+    let action = action.to_string();
+    let secret_id = secret_id.map(str::to_string);
+    let region = ctx.region.clone();
+    let secret_arn_prefix = ctx.secret_arn_prefix();
+
+    ctx.db
+        .call(move |db| {
+            authorize_secret(
+                db,
+                &identity,
+                &action,
+                secret_id.as_deref(),
+                &region,
+                &secret_arn_prefix,
+            )
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Authorize the `identity` to perform `action` against the secret in `region` with the
+/// `secret_id` by evaluating the identity policy and the resource policy of the secret, secrets
+/// that don't exist are identified by their name within the `secret_arn_prefix`
+///
+/// An explicit deny from either policy denies the request, otherwise an allow from either
+/// policy allows the request. Identities without an identity policy are allowed to perform
+/// any action not denied by a resource policy
+pub fn authorize_secret(
+    db: &Connection,
+    identity: &AuthenticatedIdentity,
+    action: &str,
+    secret_id: Option<&str>,
+    region: &str,
+    secret_arn_prefix: &str,
+) -> Result<(), AwsError> {
+    let secret = match secret_id {
+        Some(secret_id) => get_secret_latest_version(db, region, secret_id)
+            .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?,
+        None => None,
+    };
+
+    let resource_policy = match &secret {
+        Some(secret) => get_secret_resource_policy(db, &secret.arn)
+            .inspect_err(|error| tracing::error!(?error, "failed to get resource policy"))?,
+        None => None,
+    };
+
+    // Secrets that don't exist yet are matched by their name
+    let resource = match (&secret, secret_id) {
+        (Some(secret), _) => secret.arn.clone(),
+        (None, Some(secret_id)) if secret_id.starts_with("arn:") => secret_id.to_string(),
+        (None, Some(name)) => format!("{secret_arn_prefix}{name}"),
+        (None, None) => "*".to_string(),
+    };
+
+    let mut condition_values: HashMap<String, Vec<String>> = HashMap::from([(
+        "aws:principalarn".to_string(),
+        vec![identity.principal_arn.clone()],
+    )]);
+
+    if let Some(secret) = &secret {
+        condition_values.insert(
+            "secretsmanager:secretid".to_string(),
+            vec![secret.arn.clone()],
+        );
+
+        for tag in &secret.version_tags {
+            condition_values.insert(
+                format!(
+                    "secretsmanager:resourcetag/{}",
+                    tag.key.to_ascii_lowercase()
+                ),
+                vec![tag.value.clone()],
+            );
+        }
+    }
+
+    let request = RequestContext {
+        principal_arn: identity.principal_arn.clone(),
+        action: action.to_string(),
+        resource,
+        condition_values,
+    };
+
+    let identity_decision = match &identity.policy {
+        Some(policy) => policy.evaluate(&request),
+        None => Decision::Allow,
+    };
+
+    let resource_decision = match resource_policy
+        .as_deref()
+        .map(|policy| PolicyDocument::parse(policy, PolicyKind::Resource))
+    {
+        Some(Ok(policy)) => policy.evaluate(&request),
+        Some(Err(errors)) => {
+            // Fail closed rather than ignoring a policy that may deny the request
+            tracing::error!(?errors, "stored resource policy is invalid");
+            return Err(InternalServiceError.into());
+        }
+        None => Decision::NotApplicable,
+    };
+
+    match (identity_decision, resource_decision) {
+        (Decision::Deny, _) | (_, Decision::Deny) => {
+            tracing::debug!(%action, principal = %identity.principal_arn, "request explicitly denied");
+            Err(AccessDeniedException.into())
+        }
+        (Decision::Allow, _) | (_, Decision::Allow) => Ok(()),
+        _ => {
+            tracing::debug!(%action, principal = %identity.principal_arn, "request implicitly denied");
+            Err(AccessDeniedException.into())
+        }
+    }
+}
+
+/// Authorize the `identity` to perform `action` against a `resource` that isn't a
+/// secret, only the identity policy is evaluated as there is no resource policy
+pub fn authorize_identity(
+    identity: &AuthenticatedIdentity,
+    action: &str,
+    resource: &str,
+) -> Result<(), AccessDeniedException> {
+    let policy = match &identity.policy {
+        Some(policy) => policy,
+        None => return Ok(()),
+    };
+
+    let request = RequestContext {
+        principal_arn: identity.principal_arn.clone(),
+        action: action.to_string(),
+        resource: resource.to_string(),
+        condition_values: HashMap::from([(
+            "aws:principalarn".to_string(),
+            vec![identity.principal_arn.clone()],
+        )]),
+    };
+
+    match policy.evaluate(&request) {
+        Decision::Allow => Ok(()),
+        _ => {
+            tracing::debug!(%action, principal = %identity.principal_arn, "request denied");
+            Err(AccessDeniedException)
+        }
+    }
+}
@@ -7,13 +7,16 @@ use crate::{
         },
     },
     handlers::{
-        Handler,
+        Handler, HandlerContext,
+        access::authorize_secret,
         error::{
             AwsError, InternalServiceError, IntoErrorResponse, InvalidRequestException,
             ResourceNotFoundException,
         },
         models::{APIErrorType, Filter, PaginationToken},
     },
+    kms::{decrypt_secret_list_value, decrypt_secret_value},
+    middleware::aws_sig_v4::AuthenticatedIdentity,
     utils::date::datetime_to_f64,
 };
 use garde::Validate;
@@ -85,16 +88,36 @@ impl Handler for BatchGetSecretValueHandler {
     type Response = BatchGetSecretValueResponse;
 
     #[tracing::instrument(skip_all)]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         match (request.filters, request.secret_id_list) {
             // Find secret values based on filters
             (Some(filters), None) => {
-                batch_get_secrets_by_filter(db, filters, request.max_results, request.next_token)
-                    .await
+                batch_get_secrets_by_filter(
+                    &ctx.db,
+                    ctx.identity.clone(),
+                    ctx.region.clone(),
+                    ctx.secret_arn_prefix(),
+                    filters,
+                    request.max_results,
+                    request.next_token,
+                )
+                .await
             }
 
             // Finding secrets from a list of ARNs / names
-            (None, Some(secret_id_list)) => batch_get_secrets_by_ids(db, secret_id_list).await,
+            (None, Some(secret_id_list)) => {
+                batch_get_secrets_by_ids(
+                    &ctx.db,
+                    ctx.identity.clone(),
+                    ctx.region.clone(),
+                    ctx.secret_arn_prefix(),
+                    secret_id_list,
+                )
+                .await
+            }
 
             // Must only specify one or the other and not both
             // and cannot pick neither
@@ -107,6 +130,9 @@ impl Handler for BatchGetSecretValueHandler {
 
 async fn batch_get_secrets_by_ids(
     db: &DbHandle,
+    identity: AuthenticatedIdentity,
+    region: String,
+    secret_arn_prefix: String,
     secret_id_list: Vec<String>,
 ) -> Result<BatchGetSecretValueResponse, AwsError> {
     let response = db
@@ -115,11 +141,12 @@ async fn batch_get_secrets_by_ids(
             let mut secret_values: Vec<SecretValueEntry> = Vec::new();
 
             for secret_id in secret_id_list {
-                let secret = get_secret_latest_version(db, &secret_id).inspect_err(|error| {
-                    tracing::error!(?error, %secret_id, "failed to load secret");
-                })?;
+                let secret =
+                    get_secret_latest_version(db, &region, &secret_id).inspect_err(|error| {
+                        tracing::error!(?error, %secret_id, "failed to load secret");
+                    })?;
 
-                let secret = match secret {
+                let mut secret = match secret {
                     Some(value) => value,
                     None => {
                         errors.push(APIErrorType {
@@ -131,8 +158,40 @@ async fn batch_get_secrets_by_ids(
                     }
                 };
 
-                if let Err(error) = update_secret_version_last_accessed(db, &secret.arn, &secret.version_id) {
-                    tracing::error!(?error, name = %secret.name, "failed to update secret last accessed");
+                // Each secret must be accessible to the identity
+                if let Err(error) = authorize_secret(
+                    db,
+                    &identity,
+                    "secretsmanager:GetSecretValue",
+                    Some(&secret.arn),
+                    &region,
+                    &secret_arn_prefix,
+                ) {
+                    errors.push(APIErrorType {
+                        error_code: Some(error.type_name().to_string()),
+                        message: Some(error.to_string()),
+                        secret_id: Some(secret_id),
+                    });
+                    continue;
+                }
+
+                if let Err(error) = decrypt_secret_value(db, &mut secret) {
+                    errors.push(APIErrorType {
+                        error_code: Some(error.type_name().to_string()),
+                        message: Some(error.to_string()),
+                        secret_id: Some(secret.arn),
+                    });
+                    continue;
+                }
+
+                if let Err(error) =
+                    update_secret_version_last_accessed(db, &secret.arn, &secret.version_id)
+                {
+                    tracing::error!(
+                        ?error,
+                        name = %secret.name,
+                        "failed to update secret last accessed"
+                    );
                     errors.push(APIErrorType {
                         error_code: Some(InternalServiceError.type_name().to_string()),
                         message: Some(InternalServiceError.to_string()),
@@ -152,7 +211,6 @@ async fn batch_get_secrets_by_ids(
                 });
             }
 
-
             Ok::<_, AwsError>(BatchGetSecretValueResponse {
                 errors,
                 next_token: None,
@@ -166,6 +224,9 @@ async fn batch_get_secrets_by_ids(
 
 async fn batch_get_secrets_by_filter(
     db: &DbHandle,
+    identity: AuthenticatedIdentity,
+    region: String,
+    secret_arn_prefix: String,
     filters: Vec<Filter>,
     max_results: Option<i32>,
     next_token: Option<PaginationToken>,
@@ -182,10 +243,10 @@ async fn batch_get_secrets_by_filter(
 
     let (errors, next_token, secret_values) = db
         .call(move |db| {
-            let secrets = get_secrets_by_filter(db, &filters, false, limit, offset, false)
+            let secrets = get_secrets_by_filter(db, &region, &filters, false, limit, offset, false)
                 .inspect_err(|error| tracing::error!(?error, "failed to get secrets"))?;
 
-            let count = get_secrets_count_by_filter(db, &filters, false)
+            let count = get_secrets_count_by_filter(db, &region, &filters, false)
                 .inspect_err(|error| tracing::error!(?error, "failed to get secrets count"))?;
 
             let mut errors: Vec<APIErrorType> = Vec::new();
@@ -195,9 +256,41 @@ async fn batch_get_secrets_by_filter(
                 .get_next_page(count)
                 .map(|value| value.to_string());
 
-            for secret in secrets {
-                if let Err(error) = update_secret_version_last_accessed(db, &secret.arn, &secret.version_id) {
-                    tracing::error!(?error, name = %secret.name, "failed to update secret last accessed");
+            for mut secret in secrets {
+                // Each secret must be accessible to the identity
+                if let Err(error) = authorize_secret(
+                    db,
+                    &identity,
+                    "secretsmanager:GetSecretValue",
+                    Some(&secret.arn),
+                    &region,
+                    &secret_arn_prefix,
+                ) {
+                    errors.push(APIErrorType {
+                        error_code: Some(error.type_name().to_string()),
+                        message: Some(error.to_string()),
+                        secret_id: Some(secret.arn),
+                    });
+                    continue;
+                }
+
+                if let Err(error) = decrypt_secret_list_value(db, &mut secret) {
+                    errors.push(APIErrorType {
+                        error_code: Some(error.type_name().to_string()),
+                        message: Some(error.to_string()),
+                        secret_id: Some(secret.arn),
+                    });
+                    continue;
+                }
+
+                if let Err(error) =
+                    update_secret_version_last_accessed(db, &secret.arn, &secret.version_id)
+                {
+                    tracing::error!(
+                        ?error,
+                        name = %secret.name,
+                        "failed to update secret last accessed"
+                    );
                     errors.push(APIErrorType {
                         error_code: Some(InternalServiceError.type_name().to_string()),
                         message: Some(InternalServiceError.to_string()),
@@ -217,7 +310,6 @@ async fn batch_get_secrets_by_filter(
                 });
             }
 
-
             Ok::<_, AwsError>((errors, next_token, secret_values))
         })
         .await?;
@@ -0,0 +1,92 @@
+use crate::{
+    database::secrets::{
+        disable_secret_rotation, get_secret_by_version_stage, get_secret_latest_version,
+    },
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException, ResourceNotFoundException},
+        models::SecretId,
+    },
+    replication::sync_secret_replicas,
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_CancelRotateSecret.html
+pub struct CancelRotateSecretHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct CancelRotateSecretRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct CancelRotateSecretResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "VersionId")]
+    version_id: Option<String>,
+}
+
+impl Handler for CancelRotateSecretHandler {
+    type Request = CancelRotateSecretRequest;
+    type Response = CancelRotateSecretResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let region = ctx.region.clone();
+
+        let (secret, version_id) = ctx
+            .db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
+
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                // The AWSPENDING stage of an incomplete rotation is left in place
+                // and must be removed by the caller before rotating again
+                let version_id =
+                    get_secret_by_version_stage(db, &region, &secret.arn, "AWSPENDING")
+                        .inspect_err(|error| {
+                            tracing::error!(?error, "failed to get pending secret version")
+                        })?
+                        .map(|pending| pending.version_id)
+                        .filter(|version_id| version_id != &secret.version_id);
+
+                disable_secret_rotation(db, &secret.arn).inspect_err(|error| {
+                    tracing::error!(?error, "failed to disable secret rotation")
+                })?;
+
+                sync_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                    tracing::error!(?error, "failed to sync secret replicas")
+                })?;
+
+                Ok::<_, AwsError>((secret, version_id))
+            })
+            .await?;
+
+        Ok(CancelRotateSecretResponse {
+            arn: secret.arn,
+            name: secret.name,
+            version_id,
+        })
+    }
+}
@@ -8,15 +8,16 @@ use crate::{
         transaction,
     },
     handlers::{
-        Handler,
+        Handler, HandlerContext,
         error::{AwsError, InternalServiceError, InvalidRequestException, ResourceExistsException},
         models::{ClientRequestToken, SecretBinary, SecretName, SecretString, Tag},
     },
+    kms::{decrypt_secret_value, encrypt_secret_value, resolve_kms_key_id},
 };
 use garde::Validate;
 use rand::{RngExt, distr::Alphanumeric};
 use serde::{Deserialize, Serialize};
-use tokio_rusqlite::{Connection, rusqlite};
+use tokio_rusqlite::rusqlite;
 
 // https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_CreateSecret.html
 pub struct CreateSecretHandler;
@@ -35,6 +36,10 @@ pub struct CreateSecretRequest {
     #[garde(dive)]
     client_request_token: Option<ClientRequestToken>,
 
+    #[serde(rename = "KmsKeyId")]
+    #[garde(inner(length(min = 1, max = 2048)))]
+    kms_key_id: Option<String>,
+
     #[serde(rename = "SecretString")]
     #[garde(dive)]
     secret_string: Option<SecretString>,
@@ -60,29 +65,35 @@ pub struct CreateSecretResponse {
 
 /// Generate a new secret ARN
 ///
-/// Uses the mock prefix arn:aws:secretsmanager:us-east-1:1:secret:
-/// and provides a randomly generated suffix as is done by the
-/// official implementation
-fn create_secret_arn(name: &str) -> String {
+/// Uses the ARN `prefix` for the region of the request and provides a
+/// randomly generated suffix as is done by the official implementation
+pub(crate) fn create_secret_arn(prefix: &str, name: &str) -> String {
     let random_suffix: String = rand::rng()
         .sample_iter(&Alphanumeric)
         .take(6)
         .map(char::from)
         .collect();
 
-    format!("arn:aws:secretsmanager:us-east-1:1:secret:{name}-{random_suffix}")
+    format!("{prefix}{name}-{random_suffix}")
 }
 
 impl Handler for CreateSecretHandler {
     type Request = CreateSecretRequest;
     type Response = CreateSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.name.0)
+    }
+
     #[tracing::instrument(skip_all, fields(name = %request.name))]
-    async fn handle(db: &Connection, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let SecretName(name) = request.name;
         let ClientRequestToken(version_id) = request.client_request_token.unwrap_or_default();
 
-        let arn = create_secret_arn(&name);
+        let arn = create_secret_arn(&ctx.secret_arn_prefix(), &name);
 
         let tags = request.tags.unwrap_or_default();
         let secret_string = request.secret_string.map(SecretString::into_inner);
@@ -98,17 +109,29 @@ impl Handler for CreateSecretHandler {
             return Err(InvalidRequestException.into());
         }
 
-        let response = db
+        let region = ctx.region.clone();
+
+        let response = ctx
+            .db
             .call(move |db| {
+                let kms_key_id = match &request.kms_key_id {
+                    Some(key_id) => resolve_kms_key_id(db, &region, key_id)?,
+                    None => None,
+                };
+
                 transaction(db, move |db| {
                     // Create the secret
                     if let CreateSecretOutcome::AlreadyFulfilled(response) =
                         create_secret_check_existing(
                             db,
-                            arn.clone(),
-                            name.clone(),
+                            CreateSecret {
+                                arn: arn.clone(),
+                                region: region.clone(),
+                                name: name.clone(),
+                                description: request.description.clone(),
+                                kms_key_id: kms_key_id.clone(),
+                            },
                             version_id.clone(),
-                            request.description.clone(),
                             &secret_string,
                             &secret_binary,
                         )?
@@ -120,8 +143,10 @@ impl Handler for CreateSecretHandler {
                     if let CreateSecretOutcome::AlreadyFulfilled(response) =
                         create_secret_version_check_existing(
                             db,
+                            &region,
                             arn.clone(),
                             version_id.clone(),
+                            kms_key_id.as_deref(),
                             secret_string,
                             secret_binary,
                         )?
@@ -160,20 +185,14 @@ enum CreateSecretOutcome {
 /// [CreateSecretOutcome::AlreadyFulfilled] otherwise returns a [ResourceExistsException]
 fn create_secret_check_existing(
     db: &rusqlite::Connection,
-    //
-    arn: String,
-    name: String,
+    create: CreateSecret,
     version_id: String,
-    description: Option<String>,
     //
     secret_string: &Option<String>,
     secret_binary: &Option<String>,
 ) -> Result<CreateSecretOutcome, AwsError> {
-    let create = CreateSecret {
-        arn,
-        name: name.clone(),
-        description,
-    };
+    let region = create.region.clone();
+    let name = create.name.clone();
 
     let error = match create_secret(db, create) {
         Ok(_) => return Ok(CreateSecretOutcome::Success),
@@ -187,11 +206,13 @@ fn create_secret_check_existing(
     }
 
     // Check if the secret has been created
-    let secret = get_secret_by_version_id(db, &name, &version_id)
+    let mut secret = get_secret_by_version_id(db, &region, &name, &version_id)
         .inspect_err(|error| tracing::error!(?error, "failed to determine existing version"))?
         // This version we tried to store was not created so this is an already exists error
         .ok_or(ResourceExistsException)?;
 
+    decrypt_secret_value(db, &mut secret)?;
+
     // If the stored version data doesn't match this is an error that
     // the resource already exists
     if secret.secret_string.ne(secret_string) || secret.secret_binary.ne(secret_binary) {
@@ -213,13 +234,24 @@ fn create_secret_check_existing(
 /// [CreateSecretOutcome::AlreadyFulfilled] otherwise returns a [ResourceExistsException]
 fn create_secret_version_check_existing(
     db: &rusqlite::Connection,
+    region: &str,
     //
     arn: String,
     version_id: String,
+    kms_key_id: Option<&str>,
     //
     secret_string: Option<String>,
     secret_binary: Option<String>,
 ) -> Result<CreateSecretOutcome, AwsError> {
+    let encrypted_value = encrypt_secret_value(
+        db,
+        kms_key_id,
+        &arn,
+        &version_id,
+        &secret_string,
+        &secret_binary,
+    )?;
+
     // Create the initial secret version
     if let Err(error) = create_secret_version(
         db,
@@ -228,6 +260,7 @@ fn create_secret_version_check_existing(
             version_id: version_id.clone(),
             secret_string: secret_string.clone(),
             secret_binary: secret_binary.clone(),
+            encrypted_value,
         },
     ) {
         // Only constraint violations are recoverable
@@ -237,7 +270,7 @@ fn create_secret_version_check_existing(
         }
 
         // Check if the secret has been created
-        let secret = get_secret_by_version_id(db, &arn, &version_id)
+        let mut secret = get_secret_by_version_id(db, region, &arn, &version_id)
             .map_err(|error| {
                 tracing::error!(?error, "failed to determine existing version");
                 InternalServiceError
@@ -245,6 +278,8 @@ fn create_secret_version_check_existing(
             // Shouldn't be possible if we hit the unique violation
             .ok_or(InternalServiceError)?;
 
+        decrypt_secret_value(db, &mut secret)?;
+
         // If the stored version data doesn't match this is an error that
         // the resource already exists
         if secret.secret_string.ne(&secret_string) || secret.secret_binary.ne(&secret_binary) {
@@ -0,0 +1,77 @@
+use crate::{
+    database::secrets::{delete_secret_resource_policy, get_secret_latest_version},
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException, ResourceNotFoundException},
+        models::SecretId,
+    },
+    replication::sync_secret_replicas,
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_DeleteResourcePolicy.html
+pub struct DeleteResourcePolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct DeleteResourcePolicyRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct DeleteResourcePolicyResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+impl Handler for DeleteResourcePolicyHandler {
+    type Request = DeleteResourcePolicyRequest;
+    type Response = DeleteResourcePolicyResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let region = ctx.region.clone();
+
+        let secret = ctx
+            .db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
+
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                delete_secret_resource_policy(db, &secret.arn).inspect_err(|error| {
+                    tracing::error!(?error, "failed to delete resource policy")
+                })?;
+
+                sync_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                    tracing::error!(?error, "failed to sync secret replicas")
+                })?;
+
+                Ok::<_, AwsError>(secret)
+            })
+            .await?;
+
+        Ok(DeleteResourcePolicyResponse {
+            arn: secret.arn,
+            name: secret.name,
+        })
+    }
+}
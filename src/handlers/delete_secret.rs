@@ -1,11 +1,10 @@
 use crate::{
-    database::{
-        DbHandle,
-        secrets::{delete_secret, get_secret_latest_version, schedule_delete_secret},
+    database::secrets::{
+        delete_secret, get_secret_latest_version, get_secret_replicas, schedule_delete_secret,
     },
     handlers::{
-        Handler,
-        error::{AwsError, ResourceNotFoundException},
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException, ResourceNotFoundException},
         models::SecretId,
     },
     utils::date::datetime_to_f64,
@@ -52,8 +51,15 @@ impl Handler for DeleteSecretHandler {
     type Request = DeleteSecretRequest;
     type Response = DeleteSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let DeleteSecretRequest {
             force_delete_without_recovery,
             recovery_window_in_days,
@@ -62,14 +68,31 @@ impl Handler for DeleteSecretHandler {
 
         let SecretId(secret_id) = secret_id;
 
-        let (secret, deletion_date) = db
+        let region = ctx.region.clone();
+
+        let (secret, deletion_date) = ctx
+            .db
             .call(move |db| -> Result<_, AwsError> {
-                let secret = get_secret_latest_version(db, &secret_id)
+                let secret = get_secret_latest_version(db, &region, &secret_id)
                     //
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
                     //
                     .ok_or(ResourceNotFoundException)?;
 
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                // Replicas must be removed before their primary secret can be deleted
+                let replicas = get_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                    tracing::error!(?error, "failed to get secret replicas")
+                })?;
+
+                if !replicas.is_empty() {
+                    return Err(InvalidRequestException.into());
+                }
+
                 // Secret is already scheduled for deletion
                 if let Some(scheduled_deletion_date) = secret.scheduled_delete_at {
                     return Ok((secret, scheduled_deletion_date));
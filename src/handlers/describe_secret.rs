@@ -1,12 +1,10 @@
 use crate::{
-    database::{
-        DbHandle,
-        secrets::{get_secret_latest_version, get_secret_versions},
-    },
+    arn::ArnConfig,
+    database::secrets::{get_secret_latest_version, get_secret_replicas, get_secret_versions},
     handlers::{
-        Handler,
+        Handler, HandlerContext,
         error::{AwsError, ResourceNotFoundException},
-        models::{SecretId, Tag},
+        models::{ReplicationStatusType, RotationRules, SecretId, Tag},
     },
     utils::date::datetime_to_f64,
 };
@@ -51,13 +49,13 @@ pub struct DescribeSecretResponse {
     #[serde(rename = "PrimaryRegion")]
     primary_region: Option<String>,
     #[serde(rename = "ReplicationStatus")]
-    replication_status: Option<Vec<serde_json::Value>>,
+    replication_status: Option<Vec<ReplicationStatusType>>,
     #[serde(rename = "RotationEnabled")]
     rotation_enabled: bool,
     #[serde(rename = "RotationLambdaARN")]
     rotation_lambda_arn: Option<String>,
     #[serde(rename = "RotationRules")]
-    rotation_rules: Option<serde_json::Value>,
+    rotation_rules: Option<RotationRules>,
     #[serde(rename = "Tags")]
     tags: Vec<Tag>,
     #[serde(rename = "VersionIdsToStages")]
@@ -68,13 +66,23 @@ impl Handler for DescribeSecretHandler {
     type Request = DescribeSecretRequest;
     type Response = DescribeSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let SecretId(secret_id) = request.secret_id;
 
-        let (secret, versions) = db
+        let region = ctx.region.clone();
+
+        let (secret, versions, replicas) = ctx
+            .db
             .call(move |db| {
-                let secret = get_secret_latest_version(db, &secret_id)
+                let secret = get_secret_latest_version(db, &region, &secret_id)
                     //
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
                     //
@@ -84,7 +92,11 @@ impl Handler for DescribeSecretHandler {
                     tracing::error!(?error, "failed to get secret versions")
                 })?;
 
-                Ok::<_, AwsError>((secret, versions))
+                let replicas = get_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                    tracing::error!(?error, "failed to get secret replicas")
+                })?;
+
+                Ok::<_, AwsError>((secret, versions, replicas))
             })
             .await?;
 
@@ -102,6 +114,21 @@ impl Handler for DescribeSecretHandler {
             .chain(tags_updated_at)
             .max();
 
+        // Replicas report the region of their primary, primary secrets only report
+        // their region when they have been replicated
+        let primary_region = match &secret.primary_arn {
+            Some(primary_arn) => Some(ArnConfig::secret_arn_region(primary_arn).to_string()),
+            None if !replicas.is_empty() => Some(ctx.region.clone()),
+            None => None,
+        };
+
+        let replication_status = (!replicas.is_empty()).then(|| {
+            replicas
+                .into_iter()
+                .map(ReplicationStatusType::from)
+                .collect()
+        });
+
         let version_ids_to_stages = versions
             .into_iter()
             .map(|version| (version.version_id, version.version_stages))
@@ -112,18 +139,18 @@ impl Handler for DescribeSecretHandler {
             description: secret.description,
             created_date: datetime_to_f64(secret.created_at),
             deleted_date: secret.deleted_at.map(datetime_to_f64),
-            kms_key_id: None,
+            kms_key_id: secret.kms_key_id,
             last_accessed_date: most_recently_used.map(datetime_to_f64),
             last_changed_date: last_changed_date.map(datetime_to_f64),
-            last_rotated_date: None,
+            last_rotated_date: secret.last_rotated_at.map(datetime_to_f64),
             name: secret.name,
-            next_rotation_date: None,
+            next_rotation_date: secret.next_rotation_at.map(datetime_to_f64),
             owning_service: None,
-            primary_region: None,
-            replication_status: None,
-            rotation_enabled: false,
-            rotation_lambda_arn: None,
-            rotation_rules: None,
+            primary_region,
+            replication_status,
+            rotation_enabled: secret.rotation_enabled,
+            rotation_lambda_arn: secret.rotation_lambda_arn,
+            rotation_rules: secret.rotation_rules,
             tags: secret
                 .version_tags
                 .into_iter()
@@ -10,6 +10,11 @@ use thiserror::Error;
 
 use crate::database::DbErr;
 
+/// Type name of the error an error response was created from, available to
+/// outer services through the response extensions
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorType(pub &'static str);
+
 pub trait IntoErrorResponse {
     fn type_name(&self) -> &'static str;
 
@@ -47,26 +52,47 @@ impl AwsBasicError for InvalidClientTokenId {
     const STATUS_CODE: StatusCode = StatusCode::FORBIDDEN;
 }
 
+/// Signature error returned by the query protocol services (STS), the message
+/// describes why the signature was rejected
 #[derive(Debug, Error)]
-#[error(
-    "The request signature we calculated does not match the signature you provided. \
-    Check your AWS Secret Access Key and signing method. Consult the service documentation for details."
-)]
-pub struct SignatureDoesNotMatch;
+#[error("{0}")]
+pub struct SignatureDoesNotMatch(pub String);
 
 impl AwsBasicError for SignatureDoesNotMatch {
     const STATUS_CODE: StatusCode = StatusCode::FORBIDDEN;
 }
 
+/// Signature error returned by the JSON protocol services (Secrets Manager and KMS),
+/// the message describes why the signature was rejected
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub struct InvalidSignatureException(pub String);
+
+impl AwsBasicError for InvalidSignatureException {}
+
+#[derive(Debug, Error)]
+#[error("The security token included in the request is invalid.")]
+pub struct UnrecognizedClientException;
+
+impl AwsBasicError for UnrecognizedClientException {}
+
+#[derive(Debug, Error)]
+#[error("The security token included in the request is expired")]
+pub struct ExpiredTokenException;
+
+impl AwsBasicError for ExpiredTokenException {}
+
 #[derive(Debug, Error)]
 #[error("Missing Authentication Token")]
 pub struct MissingAuthenticationToken;
 
 impl AwsBasicError for MissingAuthenticationToken {}
 
+/// Signature is missing a component or has a malformed component, the message
+/// names the component
 #[derive(Debug, Error)]
-#[error("The request signature does not conform to AWS standards.")]
-pub struct IncompleteSignature;
+#[error("{0}")]
+pub struct IncompleteSignature(pub String);
 
 impl AwsBasicError for IncompleteSignature {}
 
@@ -94,6 +120,67 @@ pub struct ResourceExistsException;
 
 impl AwsBasicError for ResourceExistsException {}
 
+#[derive(Debug, Error)]
+#[error("You don't have permission to perform this action.")]
+pub struct AccessDeniedException;
+
+impl AwsBasicError for AccessDeniedException {}
+
+#[derive(Debug, Error)]
+#[error("The resource policy has syntax errors.")]
+pub struct MalformedPolicyDocumentException;
+
+impl AwsBasicError for MalformedPolicyDocumentException {}
+
+#[derive(Debug, Error)]
+#[error(
+    "The BlockPublicPolicy parameter is set to true, and the resource policy did not prevent broad access to the secret."
+)]
+pub struct PublicPolicyException;
+
+impl AwsBasicError for PublicPolicyException {}
+
+#[derive(Debug, Error)]
+#[error("Secrets Manager can't decrypt the protected secret text using the provided KMS key.")]
+pub struct DecryptionFailure;
+
+impl AwsBasicError for DecryptionFailure {}
+
+#[derive(Debug, Error)]
+#[error(
+    "Secrets Manager can't encrypt the protected secret text using the provided KMS key. \
+    Check that the KMS key is available, enabled, and not in an invalid state."
+)]
+pub struct EncryptionFailure;
+
+impl AwsBasicError for EncryptionFailure {}
+
+#[derive(Debug, Error)]
+#[error(
+    "The request was rejected because the state of the specified resource is not valid for this request."
+)]
+pub struct KMSInvalidStateException;
+
+impl AwsBasicError for KMSInvalidStateException {}
+
+#[derive(Debug, Error)]
+#[error("The request was rejected because the specified entity or resource could not be found.")]
+pub struct NotFoundException;
+
+impl AwsBasicError for NotFoundException {}
+
+#[derive(Debug, Error)]
+#[error("Rate exceeded")]
+pub struct ThrottlingException;
+
+impl AwsBasicError for ThrottlingException {}
+
+#[derive(Debug, Error)]
+#[error("The request failed because it would exceed one of the Secrets Manager quotas.")]
+pub struct LimitExceededException;
+
+impl AwsBasicError for LimitExceededException {}
+
 #[derive(Debug, Error)]
 #[error("This operation is not implemented in this server")]
 pub struct NotImplemented;
@@ -114,6 +201,15 @@ pub enum AwsError {
     #[error(transparent)]
     SignatureDoesNotMatch(#[from] SignatureDoesNotMatch),
 
+    #[error(transparent)]
+    InvalidSignatureException(#[from] InvalidSignatureException),
+
+    #[error(transparent)]
+    UnrecognizedClientException(#[from] UnrecognizedClientException),
+
+    #[error(transparent)]
+    ExpiredTokenException(#[from] ExpiredTokenException),
+
     #[error(transparent)]
     MissingAuthenticationToken(#[from] MissingAuthenticationToken),
 
@@ -132,6 +228,33 @@ pub enum AwsError {
     #[error(transparent)]
     ResourceExistsException(#[from] ResourceExistsException),
 
+    #[error(transparent)]
+    AccessDeniedException(#[from] AccessDeniedException),
+
+    #[error(transparent)]
+    MalformedPolicyDocumentException(#[from] MalformedPolicyDocumentException),
+
+    #[error(transparent)]
+    PublicPolicyException(#[from] PublicPolicyException),
+
+    #[error(transparent)]
+    DecryptionFailure(#[from] DecryptionFailure),
+
+    #[error(transparent)]
+    EncryptionFailure(#[from] EncryptionFailure),
+
+    #[error(transparent)]
+    KMSInvalidStateException(#[from] KMSInvalidStateException),
+
+    #[error(transparent)]
+    NotFoundException(#[from] NotFoundException),
+
+    #[error(transparent)]
+    ThrottlingException(#[from] ThrottlingException),
+
+    #[error(transparent)]
+    LimitExceededException(#[from] LimitExceededException),
+
     #[error(transparent)]
     NotImplemented(#[from] NotImplemented),
 
@@ -160,12 +283,24 @@ impl IntoErrorResponse for AwsError {
         match self {
             AwsError::InvalidClientTokenId(error) => error.type_name(),
             AwsError::SignatureDoesNotMatch(error) => error.type_name(),
+            AwsError::InvalidSignatureException(error) => error.type_name(),
+            AwsError::UnrecognizedClientException(error) => error.type_name(),
+            AwsError::ExpiredTokenException(error) => error.type_name(),
             AwsError::MissingAuthenticationToken(error) => error.type_name(),
             AwsError::IncompleteSignature(error) => error.type_name(),
             AwsError::InvalidRequestException(error) => error.type_name(),
             AwsError::InvalidParameterException(error) => error.type_name(),
             AwsError::ResourceNotFoundException(error) => error.type_name(),
             AwsError::ResourceExistsException(error) => error.type_name(),
+            AwsError::AccessDeniedException(error) => error.type_name(),
+            AwsError::MalformedPolicyDocumentException(error) => error.type_name(),
+            AwsError::PublicPolicyException(error) => error.type_name(),
+            AwsError::DecryptionFailure(error) => error.type_name(),
+            AwsError::EncryptionFailure(error) => error.type_name(),
+            AwsError::KMSInvalidStateException(error) => error.type_name(),
+            AwsError::NotFoundException(error) => error.type_name(),
+            AwsError::ThrottlingException(error) => error.type_name(),
+            AwsError::LimitExceededException(error) => error.type_name(),
             AwsError::NotImplemented(error) => error.type_name(),
             AwsError::InternalServiceError(error) => error.type_name(),
         }
@@ -175,12 +310,24 @@ impl IntoErrorResponse for AwsError {
         match self {
             AwsError::InvalidClientTokenId(error) => error.into_error_response(),
             AwsError::SignatureDoesNotMatch(error) => error.into_error_response(),
+            AwsError::InvalidSignatureException(error) => error.into_error_response(),
+            AwsError::UnrecognizedClientException(error) => error.into_error_response(),
+            AwsError::ExpiredTokenException(error) => error.into_error_response(),
             AwsError::MissingAuthenticationToken(error) => error.into_error_response(),
             AwsError::IncompleteSignature(error) => error.into_error_response(),
             AwsError::InvalidRequestException(error) => error.into_error_response(),
             AwsError::InvalidParameterException(error) => error.into_error_response(),
             AwsError::ResourceNotFoundException(error) => error.into_error_response(),
             AwsError::ResourceExistsException(error) => error.into_error_response(),
+            AwsError::AccessDeniedException(error) => error.into_error_response(),
+            AwsError::MalformedPolicyDocumentException(error) => error.into_error_response(),
+            AwsError::PublicPolicyException(error) => error.into_error_response(),
+            AwsError::DecryptionFailure(error) => error.into_error_response(),
+            AwsError::EncryptionFailure(error) => error.into_error_response(),
+            AwsError::KMSInvalidStateException(error) => error.into_error_response(),
+            AwsError::NotFoundException(error) => error.into_error_response(),
+            AwsError::ThrottlingException(error) => error.into_error_response(),
+            AwsError::LimitExceededException(error) => error.into_error_response(),
             AwsError::NotImplemented(error) => error.into_error_response(),
             AwsError::InternalServiceError(error) => error.into_error_response(),
         }
@@ -209,5 +356,6 @@ fn simple_error_response(
     response
         .headers_mut()
         .insert("x-amzn-errortype", HeaderValue::from_static(__type));
+    response.extensions_mut().insert(ErrorType(__type));
     response
 }
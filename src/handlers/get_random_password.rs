@@ -1,9 +1,6 @@
-use crate::{
-    database::DbHandle,
-    handlers::{
-        Handler,
-        error::{AwsError, InvalidRequestException},
-    },
+use crate::handlers::{
+    Handler, HandlerContext,
+    error::{AwsError, InvalidRequestException},
 };
 use garde::Validate;
 use rand::seq::{IndexedRandom, SliceRandom};
@@ -66,7 +63,10 @@ impl Handler for GetRandomPasswordHandler {
     type Response = GetRandomPasswordResponse;
 
     #[tracing::instrument(skip_all)]
-    async fn handle(_db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        _ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let GetRandomPasswordRequest {
             exclude_characters,
             exclude_lowercase,
@@ -0,0 +1,71 @@
+use crate::{
+    database::secrets::{get_secret_latest_version, get_secret_resource_policy},
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, ResourceNotFoundException},
+        models::SecretId,
+    },
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_GetResourcePolicy.html
+pub struct GetResourcePolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct GetResourcePolicyRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct GetResourcePolicyResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "ResourcePolicy")]
+    resource_policy: Option<String>,
+}
+
+impl Handler for GetResourcePolicyHandler {
+    type Request = GetResourcePolicyRequest;
+    type Response = GetResourcePolicyResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let region = ctx.region.clone();
+
+        let (secret, resource_policy) = ctx
+            .db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
+
+                let resource_policy =
+                    get_secret_resource_policy(db, &secret.arn).inspect_err(|error| {
+                        tracing::error!(?error, "failed to get resource policy")
+                    })?;
+
+                Ok::<_, AwsError>((secret, resource_policy))
+            })
+            .await?;
+
+        Ok(GetResourcePolicyResponse {
+            arn: secret.arn,
+            name: secret.name,
+            resource_policy,
+        })
+    }
+}
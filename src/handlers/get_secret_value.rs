@@ -1,17 +1,14 @@
 use crate::{
-    database::{
-        DbHandle,
-        secrets::{
-            get_secret_by_version_id, get_secret_by_version_stage,
-            get_secret_by_version_stage_and_id, get_secret_latest_version,
-            update_secret_version_last_accessed,
-        },
+    database::secrets::{
+        get_secret_by_version_id, get_secret_by_version_stage, get_secret_by_version_stage_and_id,
+        get_secret_latest_version, update_secret_version_last_accessed,
     },
     handlers::{
-        Handler,
+        Handler, HandlerContext,
         error::{AwsError, InvalidRequestException, ResourceNotFoundException},
         models::{SecretId, VersionId},
     },
+    kms::decrypt_secret_value,
     utils::date::datetime_to_f64,
 };
 use garde::Validate;
@@ -57,31 +54,42 @@ impl Handler for GetSecretValueHandler {
     type Request = GetSecretValueRequest;
     type Response = GetSecretValueResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let SecretId(secret_id) = request.secret_id;
         let version_id = request.version_id.map(VersionId::into_inner);
         let version_stage = request.version_stage;
 
-        let secret = db
+        let region = ctx.region.clone();
+
+        let secret = ctx
+            .db
             .call(move |db| {
                 let secret = match (&version_id, &version_stage) {
-                    (None, None) => get_secret_latest_version(db, &secret_id),
+                    (None, None) => get_secret_latest_version(db, &region, &secret_id),
                     (Some(version_id), Some(version_stage)) => get_secret_by_version_stage_and_id(
                         db,
+                        &region,
                         &secret_id,
                         version_id,
                         version_stage,
                     ),
                     (Some(version_id), None) => {
-                        get_secret_by_version_id(db, &secret_id, version_id)
+                        get_secret_by_version_id(db, &region, &secret_id, version_id)
                     }
                     (None, Some(version_stage)) => {
-                        get_secret_by_version_stage(db, &secret_id, version_stage)
+                        get_secret_by_version_stage(db, &region, &secret_id, version_stage)
                     }
                 };
 
-                let secret = secret
+                let mut secret = secret
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret value"))?
                     .ok_or(ResourceNotFoundException)?;
 
@@ -90,6 +98,8 @@ impl Handler for GetSecretValueHandler {
                     return Err(InvalidRequestException.into());
                 }
 
+                decrypt_secret_value(db, &mut secret)?;
+
                 // Update the access timestamp
                 update_secret_version_last_accessed(db, &secret.arn, &secret.version_id)
                     .inspect_err(|error| {
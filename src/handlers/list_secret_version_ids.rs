@@ -1,10 +1,9 @@
 use crate::{
-    database::{
-        DbHandle,
-        secrets::{count_secret_versions, get_secret_latest_version, get_secret_versions_page},
+    database::secrets::{
+        count_secret_versions, get_secret_latest_version, get_secret_versions_page,
     },
     handlers::{
-        Handler,
+        Handler, HandlerContext,
         error::{AwsError, InvalidRequestException, ResourceNotFoundException},
         models::{PaginationToken, SecretId},
     },
@@ -79,8 +78,15 @@ impl Handler for ListSecretVersionIdsHandler {
     type Request = ListSecretVersionIdsRequest;
     type Response = ListSecretVersionIdsResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let ListSecretVersionIdsRequest {
             include_deprecated,
             max_results,
@@ -91,9 +97,12 @@ impl Handler for ListSecretVersionIdsHandler {
         let SecretId(secret_id) = secret_id;
         let pagination_token = next_token.page_size(max_results);
 
-        let (secret, versions, next_token) = db
+        let region = ctx.region.clone();
+
+        let (secret, versions, next_token) = ctx
+            .db
             .call(move |db| {
-                let secret = get_secret_latest_version(db, &secret_id)
+                let secret = get_secret_latest_version(db, &region, &secret_id)
                     //
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
                     //
@@ -122,7 +131,7 @@ impl Handler for ListSecretVersionIdsHandler {
             .into_iter()
             .map(|version| SecretVersionsListEntry {
                 created_date: datetime_to_f64(version.created_at),
-                kms_key_ids: None,
+                kms_key_ids: version.kms_key_id.map(|kms_key_id| vec![kms_key_id]),
                 last_accessed_date: version.last_accessed_at.map(datetime_to_f64),
                 version_id: version.version_id,
                 version_stages: version.version_stages,
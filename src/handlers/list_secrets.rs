@@ -1,12 +1,9 @@
 use crate::{
-    database::{
-        DbHandle,
-        secrets::{get_secrets_by_filter, get_secrets_count_by_filter},
-    },
+    database::secrets::{get_secrets_by_filter, get_secrets_count_by_filter},
     handlers::{
-        Handler,
+        Handler, HandlerContext,
         error::{AwsError, InvalidRequestException},
-        models::{Filter, PaginationToken, Tag},
+        models::{Filter, PaginationToken, RotationRules, Tag},
     },
     utils::{date::datetime_to_f64, string::join_iter_string},
 };
@@ -84,7 +81,7 @@ pub struct SecretListEntry {
     #[serde(rename = "RotationLambdaARN")]
     rotation_lambda_arn: Option<String>,
     #[serde(rename = "RotationRules")]
-    rotation_rules: Option<serde_json::Value>,
+    rotation_rules: Option<RotationRules>,
     #[serde(rename = "SecretVersionsToStages")]
     secret_versions_to_stages: HashMap<String, Vec<String>>,
     #[serde(rename = "Tags")]
@@ -125,7 +122,10 @@ impl Handler for ListSecretsHandler {
     type Response = ListSecretsResponse;
 
     #[tracing::instrument(skip_all)]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let ListSecretsRequest {
             filters,
             include_planned_deletion,
@@ -141,11 +141,15 @@ impl Handler for ListSecretsHandler {
             .as_query_parts()
             .ok_or(InvalidRequestException)?;
 
-        let (secrets, count) = db
+        let region = ctx.region.clone();
+
+        let (secrets, count) = ctx
+            .db
             .call(move |db| {
                 Ok::<_, AwsError>((
                     get_secrets_by_filter(
                         db,
+                        &region,
                         &filters,
                         include_planned_deletion,
                         limit,
@@ -153,7 +157,7 @@ impl Handler for ListSecretsHandler {
                         asc,
                     )
                     .inspect_err(|error| tracing::error!(?error, "failed to get secrets"))?,
-                    get_secrets_count_by_filter(db, &filters, include_planned_deletion)
+                    get_secrets_count_by_filter(db, &region, &filters, include_planned_deletion)
                         .inspect_err(|error| {
                             tracing::error!(?error, "failed to get secrets count")
                         })?,
@@ -203,17 +207,17 @@ impl Handler for ListSecretsHandler {
                     description: secret.description,
                     created_date: datetime_to_f64(secret.created_at),
                     deleted_date: secret.deleted_at.map(datetime_to_f64),
-                    kms_key_id: None,
+                    kms_key_id: secret.kms_key_id,
                     last_accessed_date: most_recently_used.map(datetime_to_f64),
                     last_changed_date: last_changed_date.map(datetime_to_f64),
-                    last_rotated_date: None,
+                    last_rotated_date: secret.last_rotated_at.map(datetime_to_f64),
                     name: secret.name,
-                    next_rotation_date: None,
+                    next_rotation_date: secret.next_rotation_at.map(datetime_to_f64),
                     owning_service: None,
-                    primary_region: None,
-                    rotation_enabled: false,
-                    rotation_lambda_arn: None,
-                    rotation_rules: None,
+                    primary_region: secret.primary_region,
+                    rotation_enabled: secret.rotation_enabled,
+                    rotation_lambda_arn: secret.rotation_lambda_arn,
+                    rotation_rules: secret.rotation_rules,
                     tags,
                     secret_versions_to_stages,
                 }
@@ -1,22 +1,39 @@
 use crate::{
+    arn::ArnConfig,
     database::DbHandle,
     handlers::{
+        access::authorize,
         batch_get_secret_value::BatchGetSecretValueHandler,
+        cancel_rotate_secret::CancelRotateSecretHandler,
         create_secret::CreateSecretHandler,
+        delete_resource_policy::DeleteResourcePolicyHandler,
         delete_secret::DeleteSecretHandler,
         describe_secret::DescribeSecretHandler,
         error::{AwsError, IntoErrorResponse},
         get_random_password::GetRandomPasswordHandler,
+        get_resource_policy::GetResourcePolicyHandler,
         get_secret_value::GetSecretValueHandler,
         list_secret_version_ids::ListSecretVersionIdsHandler,
         list_secrets::ListSecretsHandler,
+        put_resource_policy::PutResourcePolicyHandler,
         put_secret_value::PutSecretValueHandler,
+        remove_regions_from_replication::RemoveRegionsFromReplicationHandler,
+        replicate_secret_to_regions::ReplicateSecretToRegionsHandler,
         restore_secret::RestoreSecretHandler,
+        rotate_secret::RotateSecretHandler,
+        stop_replication_to_replica::StopReplicationToReplicaHandler,
         tag_resource::TagResourceHandler,
         untag_resource::UntagResourceHandler,
         update_secret::UpdateSecretHandler,
         update_secret_version_stage::UpdateSecretVersionStageHandler,
+        validate_resource_policy::ValidateResourcePolicyHandler,
     },
+    kms::{
+        create_key::CreateKeyHandler, describe_key::DescribeKeyHandler,
+        disable_key::DisableKeyHandler, enable_key::EnableKeyHandler, list_keys::ListKeysHandler,
+    },
+    middleware::aws_sig_v4::AuthenticatedIdentity,
+    rotation::RotationRunner,
 };
 use axum::{
     Json,
@@ -34,23 +51,35 @@ use serde::{Serialize, de::DeserializeOwned};
 use std::{collections::HashMap, convert::Infallible, sync::Arc, task::Poll};
 use tower::Service;
 
+pub(crate) mod access;
 pub(crate) mod error;
 pub(crate) mod models;
 
 mod batch_get_secret_value;
+mod cancel_rotate_secret;
 mod create_secret;
+mod delete_resource_policy;
 mod delete_secret;
 mod describe_secret;
 mod get_random_password;
+mod get_resource_policy;
 mod get_secret_value;
 mod list_secret_version_ids;
 mod list_secrets;
+mod put_resource_policy;
 mod put_secret_value;
+mod remove_regions_from_replication;
+mod replicate_secret_to_regions;
 mod restore_secret;
+mod rotate_secret;
+mod stop_replication_to_replica;
 mod tag_resource;
 mod untag_resource;
 mod update_secret;
 mod update_secret_version_stage;
+mod validate_resource_policy;
+
+pub(crate) use create_secret::create_secret_arn;
 
 pub fn create_handlers() -> HandlerRouter {
     HandlerRouter::default()
@@ -77,6 +106,38 @@ pub fn create_handlers() -> HandlerRouter {
             "secretsmanager.BatchGetSecretValue",
             BatchGetSecretValueHandler,
         )
+        .add_handler("secretsmanager.RotateSecret", RotateSecretHandler)
+        .add_handler(
+            "secretsmanager.CancelRotateSecret",
+            CancelRotateSecretHandler,
+        )
+        .add_handler("secretsmanager.GetResourcePolicy", GetResourcePolicyHandler)
+        .add_handler("secretsmanager.PutResourcePolicy", PutResourcePolicyHandler)
+        .add_handler(
+            "secretsmanager.DeleteResourcePolicy",
+            DeleteResourcePolicyHandler,
+        )
+        .add_handler(
+            "secretsmanager.ValidateResourcePolicy",
+            ValidateResourcePolicyHandler,
+        )
+        .add_handler(
+            "secretsmanager.ReplicateSecretToRegions",
+            ReplicateSecretToRegionsHandler,
+        )
+        .add_handler(
+            "secretsmanager.RemoveRegionsFromReplication",
+            RemoveRegionsFromReplicationHandler,
+        )
+        .add_handler(
+            "secretsmanager.StopReplicationToReplica",
+            StopReplicationToReplicaHandler,
+        )
+        .add_handler("TrentService.CreateKey", CreateKeyHandler)
+        .add_handler("TrentService.DescribeKey", DescribeKeyHandler)
+        .add_handler("TrentService.ListKeys", ListKeysHandler)
+        .add_handler("TrentService.EnableKey", EnableKeyHandler)
+        .add_handler("TrentService.DisableKey", DisableKeyHandler)
 }
 
 #[derive(Default)]
@@ -86,9 +147,20 @@ pub struct HandlerRouter {
 
 impl HandlerRouter {
     fn add_handler<H: Handler>(mut self, target: &str, handler: H) -> Self {
+        // Targets are in the format "secretsmanager.CreateSecret" and map to the
+        // IAM action "secretsmanager:CreateSecret", KMS targets use the "TrentService"
+        // prefix and map to the "kms" IAM actions
+        let action = match target.split_once('.') {
+            Some(("TrentService", operation)) => format!("kms:{operation}"),
+            _ => target.replacen('.', ":", 1),
+        };
+
         self.handlers.insert(
             target.to_string(),
-            Box::new(HandlerBase { _handler: handler }),
+            Box::new(HandlerBase {
+                _handler: handler,
+                action,
+            }),
         );
         self
     }
@@ -97,6 +169,11 @@ impl HandlerRouter {
         self.handlers.get(target).map(|value| value.as_ref())
     }
 
+    /// Targets that have a handler registered
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(String::as_str)
+    }
+
     pub fn into_service(self) -> HandlerRouterService {
         HandlerRouterService {
             router: Arc::new(self),
@@ -131,6 +208,29 @@ impl Service<Request<Body>> for HandlerRouterService {
                 .get::<DbHandle>()
                 .expect("handler router service missing db pool");
 
+            let rotation = parts
+                .extensions
+                .get::<RotationRunner>()
+                .expect("handler router service missing rotation runner");
+
+            let identity = parts
+                .extensions
+                .get::<AuthenticatedIdentity>()
+                .expect("handler router service missing authenticated identity");
+
+            let arn = parts
+                .extensions
+                .get::<ArnConfig>()
+                .expect("handler router service missing arn config");
+
+            let ctx = HandlerContext {
+                db: db.clone(),
+                rotation: rotation.clone(),
+                identity: identity.clone(),
+                region: arn.request_region(&identity.region),
+                arn: arn.clone(),
+            };
+
             let target = match parts
                 .headers
                 .get("x-amz-target")
@@ -153,38 +253,67 @@ impl Service<Request<Body>> for HandlerRouterService {
             };
 
             Ok(match handler {
-                Some(value) => value.handle(db, &body).await,
+                Some(value) => value.handle(&ctx, &body).await,
                 None => NotImplemented.into_error_response(),
             })
         })
     }
 }
 
+/// Shared state available to handlers while handling a request
+pub struct HandlerContext {
+    /// Database connection
+    pub db: DbHandle,
+    /// Runner for performing secret rotations
+    pub rotation: RotationRunner,
+    /// Identity that made the request
+    pub identity: AuthenticatedIdentity,
+    /// Partition, region and account used when generating ARNs
+    pub arn: ArnConfig,
+    /// Region the request was made against
+    pub region: String,
+}
+
+impl HandlerContext {
+    /// Get the prefix of the ARNs for secrets in the region of the request
+    pub fn secret_arn_prefix(&self) -> String {
+        self.arn.secret_arn_prefix(&self.region)
+    }
+}
+
 /// Handler for handling a specific request
 pub trait Handler: Send + Sync + 'static {
     type Request: DeserializeOwned + Validate<Context = ()> + Send + 'static;
     type Response: Serialize + Send + 'static;
 
     fn handle<'d>(
-        db: &'d DbHandle,
+        ctx: &'d HandlerContext,
         request: Self::Request,
     ) -> impl Future<Output = Result<Self::Response, AwsError>> + Send + 'd;
+
+    /// Get the ID of the secret the request is performed against, used to
+    /// authorize the request against the policies of the secret
+    fn secret_id(_request: &Self::Request) -> Option<&str> {
+        None
+    }
 }
 
 /// Associated type erased [Handler] that takes a generic request and provides
 /// a generic response
 pub trait ErasedHandler: Send + Sync + 'static {
-    fn handle<'r>(&self, db: &'r DbHandle, request: &'r [u8]) -> BoxFuture<'r, Response>;
+    fn handle<'r>(&'r self, ctx: &'r HandlerContext, request: &'r [u8]) -> BoxFuture<'r, Response>;
 }
 
 /// Handler that takes care of the process of deserializing the request
 /// type and serializing the response type to create a generic [ErasedHandler]
 pub struct HandlerBase<H: Handler> {
     _handler: H,
+    /// IAM action performed by the handler
+    action: String,
 }
 
 impl<H: Handler> ErasedHandler for HandlerBase<H> {
-    fn handle<'r>(&self, db: &'r DbHandle, request: &'r [u8]) -> BoxFuture<'r, Response> {
+    fn handle<'r>(&'r self, ctx: &'r HandlerContext, request: &'r [u8]) -> BoxFuture<'r, Response> {
         Box::pin(async move {
             let request: H::Request = match serde_json::from_slice(request) {
                 Ok(value) => value,
@@ -199,7 +328,11 @@ impl<H: Handler> ErasedHandler for HandlerBase<H> {
                 return InvalidParameterException.into_error_response();
             }
 
-            match H::handle(db, request).await {
+            if let Err(error) = authorize(ctx, &self.action, H::secret_id(&request)).await {
+                return error.into_error_response();
+            }
+
+            match H::handle(ctx, request).await {
                 Ok(response) => Json(response).into_response(),
                 Err(error) => error.into_error_response(),
             }
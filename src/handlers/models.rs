@@ -4,7 +4,14 @@ use std::{fmt::Display, str::FromStr};
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::utils::string::join_iter_string;
+use crate::{
+    database::secrets::StoredSecretReplica,
+    utils::{
+        date::datetime_to_f64,
+        schedule::{ScheduleExpression, parse_rotation_window},
+        string::join_iter_string,
+    },
+};
 
 #[derive(Debug, Deserialize, Validate)]
 #[garde(transparent)]
@@ -99,6 +106,103 @@ pub struct Tag {
     pub value: String,
 }
 
+#[derive(Clone, Deserialize, Serialize, Validate)]
+pub struct RotationRules {
+    #[serde(rename = "AutomaticallyAfterDays")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[garde(inner(range(min = 1, max = 1000)))]
+    pub automatically_after_days: Option<i64>,
+
+    #[serde(rename = "Duration")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[garde(
+        inner(length(min = 2, max = 3)),
+        inner(custom(is_valid_rotation_window))
+    )]
+    pub duration: Option<String>,
+
+    #[serde(rename = "ScheduleExpression")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[garde(
+        inner(length(min = 1, max = 256)),
+        inner(custom(is_valid_schedule_expression))
+    )]
+    pub schedule_expression: Option<String>,
+}
+
+/// Checks if the provided value is a valid rotation window duration
+fn is_valid_rotation_window(value: &str, _context: &()) -> garde::Result {
+    if parse_rotation_window(value).is_none() {
+        return Err(garde::Error::new(
+            "rotation window must be between 1h and 24h",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks if the provided value is a valid rate() or cron() schedule expression
+fn is_valid_schedule_expression(value: &str, _context: &()) -> garde::Result {
+    if let Err(error) = value.parse::<ScheduleExpression>() {
+        return Err(garde::Error::new(error.to_string()));
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ReplicaRegionType {
+    #[serde(rename = "Region")]
+    #[garde(length(min = 1, max = 128), custom(is_valid_region))]
+    pub region: String,
+
+    #[serde(rename = "KmsKeyId")]
+    #[garde(inner(length(max = 2048)))]
+    pub kms_key_id: Option<String>,
+}
+
+/// Checks if the provided value looks like a region name (i.e us-east-1)
+fn is_valid_region(value: &str, _context: &()) -> garde::Result {
+    if !value
+        .chars()
+        .all(|char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '-')
+    {
+        return Err(garde::Error::new("region contains disallowed characters"));
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct ReplicationStatusType {
+    #[serde(rename = "Region")]
+    pub region: String,
+
+    #[serde(rename = "KmsKeyId")]
+    pub kms_key_id: Option<String>,
+
+    #[serde(rename = "Status")]
+    pub status: String,
+
+    #[serde(rename = "StatusMessage")]
+    pub status_message: Option<String>,
+
+    #[serde(rename = "LastAccessedDate")]
+    pub last_accessed_date: Option<f64>,
+}
+
+impl From<StoredSecretReplica> for ReplicationStatusType {
+    fn from(value: StoredSecretReplica) -> Self {
+        Self {
+            region: value.region,
+            kms_key_id: value.kms_key_id,
+            status: value.status,
+            status_message: value.status_message,
+            last_accessed_date: value.last_accessed_at.map(datetime_to_f64),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Validate)]
 pub struct Filter {
     #[serde(rename = "Key")]
@@ -221,6 +325,15 @@ pub struct APIErrorType {
     pub secret_id: Option<String>,
 }
 
+#[derive(Serialize)]
+pub struct ValidationErrorsEntry {
+    #[serde(rename = "CheckName")]
+    pub check_name: String,
+
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: String,
+}
+
 impl Display for PaginationToken {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}:{}", self.page_size, self.page_index)
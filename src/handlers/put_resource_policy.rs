@@ -0,0 +1,102 @@
+use crate::{
+    database::secrets::{get_secret_latest_version, put_secret_resource_policy},
+    handlers::{
+        Handler, HandlerContext,
+        error::{
+            AwsError, InvalidRequestException, MalformedPolicyDocumentException,
+            PublicPolicyException, ResourceNotFoundException,
+        },
+        models::SecretId,
+    },
+    policy::{PolicyDocument, PolicyKind},
+    replication::sync_secret_replicas,
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_PutResourcePolicy.html
+pub struct PutResourcePolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct PutResourcePolicyRequest {
+    #[serde(rename = "BlockPublicPolicy")]
+    #[serde(default)]
+    #[garde(skip)]
+    block_public_policy: bool,
+
+    #[serde(rename = "ResourcePolicy")]
+    #[garde(length(min = 1, max = 20480))]
+    resource_policy: String,
+
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct PutResourcePolicyResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+impl Handler for PutResourcePolicyHandler {
+    type Request = PutResourcePolicyRequest;
+    type Response = PutResourcePolicyResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let SecretId(secret_id) = request.secret_id;
+        let resource_policy = request.resource_policy;
+
+        let policy =
+            PolicyDocument::parse(&resource_policy, PolicyKind::Resource).map_err(|errors| {
+                tracing::debug!(?errors, "malformed resource policy");
+                MalformedPolicyDocumentException
+            })?;
+
+        // Reject policies that grant access to anyone
+        if request.block_public_policy && policy.is_public() {
+            return Err(PublicPolicyException.into());
+        }
+
+        let region = ctx.region.clone();
+
+        let secret = ctx
+            .db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
+
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                put_secret_resource_policy(db, &secret.arn, &resource_policy).inspect_err(
+                    |error| tracing::error!(?error, "failed to put resource policy"),
+                )?;
+
+                sync_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                    tracing::error!(?error, "failed to sync secret replicas")
+                })?;
+
+                Ok::<_, AwsError>(secret)
+            })
+            .await?;
+
+        Ok(PutResourcePolicyResponse {
+            arn: secret.arn,
+            name: secret.name,
+        })
+    }
+}
@@ -1,6 +1,5 @@
 use crate::{
     database::{
-        DbHandle,
         ext::SqlErrorExt,
         secrets::{
             CreateSecretVersion, add_secret_version_stage, create_secret_version,
@@ -9,13 +8,15 @@ use crate::{
         transaction,
     },
     handlers::{
-        Handler,
+        Handler, HandlerContext,
         error::{
             AwsError, InternalServiceError, InvalidRequestException, ResourceExistsException,
             ResourceNotFoundException,
         },
         models::{ClientRequestToken, SecretBinary, SecretId, SecretString},
     },
+    kms::{decrypt_secret_value, encrypt_secret_value},
+    replication::sync_secret_replicas,
 };
 use garde::Validate;
 use serde::{Deserialize, Serialize};
@@ -62,8 +63,15 @@ impl Handler for PutSecretValueHandler {
     type Request = PutSecretValueRequest;
     type Response = PutSecretValueResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let SecretId(secret_id) = request.secret_id;
         let ClientRequestToken(version_id) = request.client_request_token.unwrap_or_default();
 
@@ -92,11 +100,29 @@ impl Handler for PutSecretValueHandler {
             return Err(InvalidRequestException.into());
         }
 
-        let response = db
+        let region = ctx.region.clone();
+
+        let response = ctx
+            .db
             .call(move |db| {
-                let secret = get_secret_latest_version(db, &secret_id)
+                let secret = get_secret_latest_version(db, &region, &secret_id)
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
                     .ok_or(ResourceNotFoundException)?;
+
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                let encrypted_value = encrypt_secret_value(
+                    db,
+                    secret.kms_key_id.as_deref(),
+                    &secret.arn,
+                    &version_id,
+                    &secret_string,
+                    &secret_binary,
+                )?;
+
                 transaction(db, move |db| {
                     // Create the new secret version
                     if let Err(error) = create_secret_version(
@@ -106,6 +132,7 @@ impl Handler for PutSecretValueHandler {
                             version_id: version_id.clone(),
                             secret_string: secret_string.clone(),
                             secret_binary: secret_binary.clone(),
+                            encrypted_value,
                         },
                     ) {
                         // Only constraint violations are recoverable
@@ -115,12 +142,15 @@ impl Handler for PutSecretValueHandler {
                         }
 
                         // Check if the secret has been created
-                        let secret = get_secret_by_version_id(db, &secret.arn, &version_id)
-                            .inspect_err(|error| {
-                                tracing::error!(?error, "failed to determine existing version")
-                            })?
-                            // Unlikely but not impossible if we hit the unique violation
-                            .ok_or(InternalServiceError)?;
+                        let mut secret =
+                            get_secret_by_version_id(db, &region, &secret.arn, &version_id)
+                                .inspect_err(|error| {
+                                    tracing::error!(?error, "failed to determine existing version")
+                                })?
+                                // Unlikely but not impossible if we hit the unique violation
+                                .ok_or(InternalServiceError)?;
+
+                        decrypt_secret_value(db, &mut secret)?;
 
                         // If the stored version data doesn't match this is an error that
                         // the resource already exists
@@ -177,6 +207,10 @@ impl Handler for PutSecretValueHandler {
                             })?;
                     }
 
+                    sync_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                        tracing::error!(?error, "failed to sync secret replicas")
+                    })?;
+
                     Ok::<_, AwsError>(PutSecretValueResponse {
                         arn: secret.arn,
                         name: secret.name,
@@ -0,0 +1,103 @@
+use crate::{
+    arn::ArnConfig,
+    database::{
+        secrets::{
+            delete_secret, delete_secret_replica, get_secret_latest_version, get_secret_replicas,
+        },
+        transaction,
+    },
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException, ResourceNotFoundException},
+        models::{ReplicationStatusType, SecretId},
+    },
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_RemoveRegionsFromReplication.html
+pub struct RemoveRegionsFromReplicationHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct RemoveRegionsFromReplicationRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+
+    #[serde(rename = "RemoveReplicaRegions")]
+    #[garde(length(min = 1), inner(length(min = 1, max = 128)))]
+    remove_replica_regions: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct RemoveRegionsFromReplicationResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "ReplicationStatus")]
+    replication_status: Vec<ReplicationStatusType>,
+}
+
+impl Handler for RemoveRegionsFromReplicationHandler {
+    type Request = RemoveRegionsFromReplicationRequest;
+    type Response = RemoveRegionsFromReplicationResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let SecretId(secret_id) = request.secret_id;
+        let remove_replica_regions = request.remove_replica_regions;
+
+        let region = ctx.region.clone();
+
+        let (secret, replicas) = ctx
+            .db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
+
+                // Replication can only be changed from the primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                let replicas = transaction(db, |t| {
+                    for replica_region in remove_replica_regions {
+                        let replica_arn =
+                            ArnConfig::secret_arn_in_region(&secret.arn, &replica_region);
+
+                        let replica = get_secret_latest_version(t, &replica_region, &replica_arn)?;
+
+                        // Only remove the replica if it hasn't been promoted to a standalone secret
+                        if replica.is_some_and(|replica| {
+                            replica.primary_arn.as_deref() == Some(secret.arn.as_str())
+                        }) {
+                            delete_secret(t, &replica_arn)?;
+                        }
+
+                        delete_secret_replica(t, &secret.arn, &replica_region)?;
+                    }
+
+                    get_secret_replicas(t, &secret.arn)
+                })
+                .inspect_err(|error| tracing::error!(?error, "failed to remove replicas"))?;
+
+                Ok::<_, AwsError>((secret, replicas))
+            })
+            .await?;
+
+        Ok(RemoveRegionsFromReplicationResponse {
+            arn: secret.arn,
+            replication_status: replicas
+                .into_iter()
+                .map(ReplicationStatusType::from)
+                .collect(),
+        })
+    }
+}
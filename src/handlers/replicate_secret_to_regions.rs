@@ -0,0 +1,191 @@
+use crate::{
+    arn::ArnConfig,
+    database::{
+        secrets::{
+            CreateSecretReplica, StoredSecret, delete_secret, delete_secret_replica,
+            get_secret_latest_version, get_secret_replicas, put_secret_replica,
+        },
+        transaction,
+    },
+    handlers::{
+        Handler, HandlerContext,
+        error::{
+            AwsError, InvalidParameterException, InvalidRequestException, ResourceNotFoundException,
+        },
+        models::{ReplicaRegionType, ReplicationStatusType, SecretId},
+    },
+    kms::resolve_kms_key_id,
+    replication::sync_secret_replica,
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use tokio_rusqlite::rusqlite::Connection;
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_ReplicateSecretToRegions.html
+pub struct ReplicateSecretToRegionsHandler;
+
+/// Status of a replica that is up to date with its primary secret
+const STATUS_IN_SYNC: &str = "InSync";
+
+/// Status of a replica that could not be created
+const STATUS_FAILED: &str = "Failed";
+
+#[derive(Deserialize, Validate)]
+pub struct ReplicateSecretToRegionsRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+
+    #[serde(rename = "AddReplicaRegions")]
+    #[garde(length(min = 1), dive)]
+    add_replica_regions: Vec<ReplicaRegionType>,
+
+    #[serde(rename = "ForceOverwriteReplicaSecret")]
+    #[serde(default)]
+    #[garde(skip)]
+    force_overwrite_replica_secret: bool,
+}
+
+#[derive(Serialize)]
+pub struct ReplicateSecretToRegionsResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "ReplicationStatus")]
+    replication_status: Vec<ReplicationStatusType>,
+}
+
+impl Handler for ReplicateSecretToRegionsHandler {
+    type Request = ReplicateSecretToRegionsRequest;
+    type Response = ReplicateSecretToRegionsResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let SecretId(secret_id) = request.secret_id;
+        let add_replica_regions = request.add_replica_regions;
+        let force_overwrite = request.force_overwrite_replica_secret;
+
+        // Secrets cannot be replicated to their own region
+        if add_replica_regions
+            .iter()
+            .any(|replica| replica.region == ctx.region)
+        {
+            return Err(InvalidParameterException.into());
+        }
+
+        let region = ctx.region.clone();
+
+        let (secret, replicas) = ctx
+            .db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
+
+                // Replicas and secrets scheduled for deletion cannot be replicated
+                if secret.is_replica() || secret.scheduled_delete_at.is_some() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                let replicas = transaction(db, |t| {
+                    for replica in add_replica_regions {
+                        replicate_secret(t, &secret, replica, force_overwrite)?;
+                    }
+
+                    Ok::<_, AwsError>(get_secret_replicas(t, &secret.arn)?)
+                })
+                .inspect_err(|error| tracing::error!(?error, "failed to replicate secret"))?;
+
+                Ok::<_, AwsError>((secret, replicas))
+            })
+            .await?;
+
+        Ok(ReplicateSecretToRegionsResponse {
+            arn: secret.arn,
+            replication_status: replicas
+                .into_iter()
+                .map(ReplicationStatusType::from)
+                .collect(),
+        })
+    }
+}
+
+/// Replicate the `secret` to the region of the `replica`, an existing secret in the region
+/// with the same name is only replaced when `force_overwrite` is set otherwise the replication
+/// is marked as failed. The replication also fails when the KMS key of the replica can't be
+/// used in the region of the replica
+fn replicate_secret(
+    db: &Connection,
+    secret: &StoredSecret,
+    replica: ReplicaRegionType,
+    force_overwrite: bool,
+) -> Result<(), AwsError> {
+    let replica_arn = ArnConfig::secret_arn_in_region(&secret.arn, &replica.region);
+
+    let existing = get_secret_latest_version(db, &replica.region, &secret.name)?
+        .filter(|existing| existing.arn != replica_arn);
+
+    // Resolve the ARN of the replica KMS key, replicas without a key use the default key
+    let kms_key_arn = match replica.kms_key_id.as_deref() {
+        Some(key_id) => match resolve_kms_key_id(db, &replica.region, key_id) {
+            Ok(value) => Ok(value),
+            Err(
+                error @ (AwsError::EncryptionFailure(_) | AwsError::KMSInvalidStateException(_)),
+            ) => Err(error),
+            Err(error) => return Err(error),
+        },
+        None => Ok(None),
+    };
+
+    let (status, status_message, kms_key_id) = match (existing, kms_key_arn) {
+        (Some(_), _) if !force_overwrite => (
+            STATUS_FAILED,
+            "Replication failed: Secret name already exists in the destination region.",
+            replica.kms_key_id,
+        ),
+        (_, Err(_)) => (
+            STATUS_FAILED,
+            "Replication failed: KMS key is invalid or disabled in the destination region.",
+            replica.kms_key_id,
+        ),
+        (existing, Ok(kms_key_arn)) => {
+            if let Some(existing) = existing {
+                // The replaced secret may itself be the replica of another secret
+                if let Some(primary_arn) = &existing.primary_arn {
+                    delete_secret_replica(db, primary_arn, &replica.region)?;
+                }
+
+                delete_secret(db, &existing.arn)?;
+            }
+
+            sync_secret_replica(
+                db,
+                &secret.arn,
+                &replica_arn,
+                &replica.region,
+                kms_key_arn.as_deref(),
+            )?;
+
+            (STATUS_IN_SYNC, "Replication succeeded", kms_key_arn)
+        }
+    };
+
+    put_secret_replica(
+        db,
+        CreateSecretReplica {
+            secret_arn: secret.arn.clone(),
+            region: replica.region,
+            kms_key_id,
+            status: status.to_string(),
+            status_message: Some(status_message.to_string()),
+        },
+    )?;
+
+    Ok(())
+}
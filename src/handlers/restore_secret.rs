@@ -1,11 +1,8 @@
 use crate::{
-    database::{
-        DbHandle,
-        secrets::{cancel_delete_secret, get_secret_latest_version},
-    },
+    database::secrets::{cancel_delete_secret, get_secret_latest_version},
     handlers::{
-        Handler,
-        error::{AwsError, ResourceNotFoundException},
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException, ResourceNotFoundException},
         models::SecretId,
     },
 };
@@ -34,16 +31,31 @@ impl Handler for RestoreSecretHandler {
     type Request = RestoreSecretRequest;
     type Response = RestoreSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let SecretId(secret_id) = request.secret_id;
 
-        let secret = db
+        let region = ctx.region.clone();
+
+        let secret = ctx
+            .db
             .call(move |db| {
-                let secret = get_secret_latest_version(db, &secret_id)
+                let secret = get_secret_latest_version(db, &region, &secret_id)
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
                     .ok_or(ResourceNotFoundException)?;
 
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
                 cancel_delete_secret(db, &secret.arn)
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?;
 
@@ -0,0 +1,231 @@
+use crate::{
+    database::secrets::{
+        enable_secret_rotation, get_secret_by_version_id, get_secret_by_version_stage,
+        get_secret_latest_version,
+    },
+    handlers::{
+        Handler, HandlerContext,
+        error::{
+            AwsError, InvalidParameterException, InvalidRequestException, ResourceNotFoundException,
+        },
+        models::{ClientRequestToken, RotationRules, SecretId},
+    },
+    replication::sync_secret_replicas,
+    rotation::next_rotation_date,
+};
+use chrono::Utc;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_RotateSecret.html
+pub struct RotateSecretHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct RotateSecretRequest {
+    #[serde(rename = "ClientRequestToken")]
+    #[garde(dive)]
+    client_request_token: Option<ClientRequestToken>,
+
+    #[serde(rename = "RotateImmediately")]
+    #[serde(default = "default_rotate_immediately")]
+    #[garde(skip)]
+    rotate_immediately: bool,
+
+    #[serde(rename = "RotationLambdaARN")]
+    #[garde(inner(length(min = 1, max = 2048)))]
+    rotation_lambda_arn: Option<String>,
+
+    #[serde(rename = "RotationRules")]
+    #[garde(dive)]
+    rotation_rules: Option<RotationRules>,
+
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct RotateSecretResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "VersionId")]
+    version_id: Option<String>,
+}
+
+fn default_rotate_immediately() -> bool {
+    true
+}
+
+impl Handler for RotateSecretHandler {
+    type Request = RotateSecretRequest;
+    type Response = RotateSecretResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let RotateSecretRequest {
+            client_request_token,
+            rotate_immediately,
+            rotation_lambda_arn,
+            rotation_rules,
+            secret_id,
+        } = request;
+
+        let SecretId(secret_id) = secret_id;
+
+        // Rotation can only be scheduled by days or a schedule expression not both
+        if rotation_rules.as_ref().is_some_and(|rules| {
+            rules.automatically_after_days.is_some() && rules.schedule_expression.is_some()
+        }) {
+            return Err(InvalidParameterException.into());
+        }
+
+        // Rotation lambdas must have a configured webhook to invoke
+        if rotation_lambda_arn
+            .as_ref()
+            .is_some_and(|rotation_lambda_arn| !ctx.rotation.has_lambda(rotation_lambda_arn))
+        {
+            return Err(InvalidParameterException.into());
+        }
+
+        let region = ctx.region.clone();
+        let rotation = ctx.rotation.clone();
+
+        let (secret, version_id, rotation_lambda_arn, rotation_rules, guard) = ctx
+            .db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
+
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                // Cannot rotate a secret that is scheduled for deletion
+                if secret.scheduled_delete_at.is_some() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                let pending = get_secret_by_version_stage(db, &region, &secret.arn, "AWSPENDING")
+                    .inspect_err(|error| {
+                        tracing::error!(?error, "failed to get pending secret version")
+                    })?
+                    .filter(|pending| pending.version_id != secret.version_id);
+
+                let version_id = match (pending, client_request_token) {
+                    // A previous rotation isn't complete, it will be reattempted
+                    (Some(pending), None) => pending.version_id,
+                    (Some(pending), Some(ClientRequestToken(version_id))) => {
+                        // Cannot start a new rotation while another is pending
+                        if pending.version_id != version_id {
+                            return Err(InvalidRequestException.into());
+                        }
+
+                        version_id
+                    }
+                    (None, client_request_token) => {
+                        let ClientRequestToken(version_id) =
+                            client_request_token.unwrap_or_default();
+
+                        // The token of a new rotation cannot name an existing version,
+                        // completing the rotation would promote that version
+                        if get_secret_by_version_id(db, &region, &secret.arn, &version_id)
+                            .inspect_err(|error| {
+                                tracing::error!(?error, "failed to determine existing version")
+                            })?
+                            .is_some()
+                        {
+                            return Err(InvalidRequestException.into());
+                        }
+
+                        version_id
+                    }
+                };
+
+                // Cannot start a rotation while the previous rotation is still running
+                let guard = if rotate_immediately {
+                    Some(
+                        rotation
+                            .begin_rotation(&secret.arn)
+                            .ok_or(InvalidRequestException)?,
+                    )
+                } else {
+                    None
+                };
+
+                let rotation_lambda_arn =
+                    rotation_lambda_arn.or_else(|| secret.rotation_lambda_arn.clone());
+                let rotation_rules = rotation_rules.or_else(|| secret.rotation_rules.clone());
+
+                // When not rotating immediately the next rotation is scheduled from the
+                // last rotation (or now if the secret has never been rotated)
+                let next_rotation_at = if rotate_immediately {
+                    None
+                } else {
+                    let from = secret.last_rotated_at.unwrap_or_else(Utc::now);
+                    rotation_rules
+                        .as_ref()
+                        .and_then(|rules| next_rotation_date(rules, from))
+                };
+
+                enable_secret_rotation(
+                    db,
+                    &secret.arn,
+                    rotation_lambda_arn.as_deref(),
+                    rotation_rules.as_ref(),
+                    next_rotation_at,
+                )
+                .inspect_err(|error| tracing::error!(?error, "failed to enable secret rotation"))?;
+
+                sync_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                    tracing::error!(?error, "failed to sync secret replicas")
+                })?;
+
+                Ok::<_, AwsError>((
+                    secret,
+                    version_id,
+                    rotation_lambda_arn,
+                    rotation_rules,
+                    guard,
+                ))
+            })
+            .await?;
+
+        let guard = match guard {
+            Some(value) => value,
+            None => {
+                return Ok(RotateSecretResponse {
+                    arn: secret.arn,
+                    name: secret.name,
+                    version_id: None,
+                });
+            }
+        };
+
+        // Like AWS the rotation steps are performed after responding, a failed step
+        // leaves the pending version in place for the rotation to be reattempted
+        ctx.rotation.spawn_rotation(
+            guard,
+            ctx.db.clone(),
+            version_id.clone(),
+            rotation_lambda_arn,
+            rotation_rules,
+        );
+
+        Ok(RotateSecretResponse {
+            arn: secret.arn,
+            name: secret.name,
+            version_id: Some(version_id),
+        })
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    database::{
+        DbErr,
+        secrets::{delete_secret_replica, get_secret_latest_version, promote_secret_replica},
+        transaction,
+    },
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException, ResourceNotFoundException},
+        models::SecretId,
+    },
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_StopReplicationToReplica.html
+pub struct StopReplicationToReplicaHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct StopReplicationToReplicaRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct StopReplicationToReplicaResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+}
+
+impl Handler for StopReplicationToReplicaHandler {
+    type Request = StopReplicationToReplicaRequest;
+    type Response = StopReplicationToReplicaResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let region = ctx.region.clone();
+
+        let secret = ctx
+            .db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
+
+                // Only replicas can be promoted
+                let primary_arn = secret.primary_arn.clone().ok_or(InvalidRequestException)?;
+
+                transaction(db, |t| {
+                    promote_secret_replica(t, &secret.arn)?;
+                    delete_secret_replica(t, &primary_arn, &region)?;
+                    Ok::<_, DbErr>(())
+                })
+                .inspect_err(|error| tracing::error!(?error, "failed to promote replica"))?;
+
+                Ok::<_, AwsError>(secret)
+            })
+            .await?;
+
+        Ok(StopReplicationToReplicaResponse { arn: secret.arn })
+    }
+}
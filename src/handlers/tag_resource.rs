@@ -1,14 +1,14 @@
 use crate::{
     database::{
-        DbErr, DbHandle,
         secrets::{get_secret_latest_version, put_secret_tag},
         transaction,
     },
     handlers::{
-        Handler,
-        error::{AwsError, ResourceNotFoundException},
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException, ResourceNotFoundException},
         models::{SecretId, Tag},
     },
+    replication::sync_secret_replicas,
 };
 use garde::Validate;
 use serde::{Deserialize, Serialize};
@@ -34,29 +34,49 @@ impl Handler for TagResourceHandler {
     type Request = TagResourceRequest;
     type Response = TagResourceResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let SecretId(secret_id) = request.secret_id;
         let tags = request.tags;
 
-        db.call(move |db| {
-            let secret = get_secret_latest_version(db, &secret_id)
-                .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
-                .ok_or(ResourceNotFoundException)?;
+        let region = ctx.region.clone();
+
+        ctx.db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
 
-            transaction(db, move |t| {
-                // Attach all the secrets
-                for tag in tags {
-                    put_secret_tag(t, &secret.arn, &tag.key, &tag.value)
-                        .inspect_err(|error| tracing::error!(?error, "failed to set secret tag"))?;
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
                 }
 
-                Ok::<_, DbErr>(())
-            })?;
+                transaction(db, move |t| {
+                    // Attach all the secrets
+                    for tag in tags {
+                        put_secret_tag(t, &secret.arn, &tag.key, &tag.value).inspect_err(
+                            |error| tracing::error!(?error, "failed to set secret tag"),
+                        )?;
+                    }
+
+                    sync_secret_replicas(t, &secret.arn).inspect_err(|error| {
+                        tracing::error!(?error, "failed to sync secret replicas")
+                    })?;
+
+                    Ok::<_, AwsError>(())
+                })?;
 
-            Ok::<_, AwsError>(())
-        })
-        .await?;
+                Ok::<_, AwsError>(())
+            })
+            .await?;
 
         Ok(TagResourceResponse {})
     }
@@ -1,14 +1,15 @@
 use crate::{
     database::{
-        DbConnection, DbErr, DbHandle,
+        DbConnection,
         secrets::{get_secret_latest_version, remove_secret_tag},
         transaction,
     },
     handlers::{
-        Handler,
-        error::{AwsError, ResourceNotFoundException},
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException, ResourceNotFoundException},
         models::SecretId,
     },
+    replication::sync_secret_replicas,
 };
 use garde::Validate;
 use serde::{Deserialize, Serialize};
@@ -34,21 +35,36 @@ impl Handler for UntagResourceHandler {
     type Request = UntagResourceRequest;
     type Response = UntagResourceResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let SecretId(secret_id) = request.secret_id;
         let tag_keys = request.tag_keys;
 
-        db.call(move |db| {
-            let secret = get_secret_latest_version(db, &secret_id)
-                .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
-                .ok_or(ResourceNotFoundException)?;
+        let region = ctx.region.clone();
+
+        ctx.db
+            .call(move |db| {
+                let secret = get_secret_latest_version(db, &region, &secret_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                    .ok_or(ResourceNotFoundException)?;
 
-            transaction(db, move |db| remove_secret_tags(db, &secret.arn, &tag_keys))?;
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
 
-            Ok::<_, AwsError>(())
-        })
-        .await?;
+                transaction(db, move |db| remove_secret_tags(db, &secret.arn, &tag_keys))?;
+
+                Ok::<_, AwsError>(())
+            })
+            .await?;
 
         Ok(UntagResourceResponse {})
     }
@@ -58,11 +74,14 @@ fn remove_secret_tags(
     db: &DbConnection,
     secret_arn: &str,
     tag_keys: &[String],
-) -> Result<(), DbErr> {
+) -> Result<(), AwsError> {
     for key in tag_keys {
         remove_secret_tag(db, secret_arn, key)
             .inspect_err(|error| tracing::error!(?error, "failed to remove secret tag"))?;
     }
 
+    sync_secret_replicas(db, secret_arn)
+        .inspect_err(|error| tracing::error!(?error, "failed to sync secret replicas"))?;
+
     Ok(())
 }
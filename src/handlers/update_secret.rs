@@ -1,21 +1,22 @@
 use crate::{
     database::{
-        DbHandle,
         ext::SqlErrorExt,
         secrets::{
             CreateSecretVersion, add_secret_version_stage, create_secret_version,
             get_secret_latest_version, remove_secret_version_stage,
-            remove_secret_version_stage_any, update_secret_description,
+            remove_secret_version_stage_any, update_secret_description, update_secret_kms_key_id,
         },
         transaction,
     },
     handlers::{
-        Handler,
+        Handler, HandlerContext,
         error::{
             AwsError, InternalServiceError, InvalidRequestException, ResourceNotFoundException,
         },
         models::{ClientRequestToken, SecretBinary, SecretId, SecretString},
     },
+    kms::{encrypt_secret_value, resolve_kms_key_id},
+    replication::sync_secret_replicas,
 };
 use garde::Validate;
 use serde::{Deserialize, Serialize};
@@ -33,6 +34,10 @@ pub struct UpdateSecretRequest {
     #[garde(inner(length(max = 2048)))]
     description: Option<String>,
 
+    #[serde(rename = "KmsKeyId")]
+    #[garde(inner(length(min = 1, max = 2048)))]
+    kms_key_id: Option<String>,
+
     #[serde(rename = "SecretId")]
     #[garde(dive)]
     secret_id: SecretId,
@@ -60,17 +65,26 @@ impl Handler for UpdateSecretHandler {
     type Request = UpdateSecretRequest;
     type Response = UpdateSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let UpdateSecretRequest {
             client_request_token,
             description,
+            kms_key_id,
             secret_id,
             secret_string,
             secret_binary,
         } = request;
 
         let SecretId(secret_id) = secret_id;
+        let ClientRequestToken(version_id) = client_request_token.unwrap_or_default();
         let secret_string = secret_string.map(SecretString::into_inner);
         let secret_binary = secret_binary.map(SecretBinary::into_inner);
 
@@ -79,12 +93,38 @@ impl Handler for UpdateSecretHandler {
             return Err(InvalidRequestException.into());
         }
 
-        let (secret, version_id) = db
+        let region = ctx.region.clone();
+
+        let (secret, version_id) = ctx
+            .db
             .call(move |db| {
-                let secret = get_secret_latest_version(db, &secret_id)
+                let secret = get_secret_latest_version(db, &region, &secret_id)
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
                     .ok_or(ResourceNotFoundException)?;
 
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
+                // Resolve the KMS key new versions should be encrypted with
+                let kms_key_id = match kms_key_id {
+                    Some(kms_key_id) => Some(resolve_kms_key_id(db, &region, &kms_key_id)?),
+                    None => None,
+                };
+
+                let encrypted_value = encrypt_secret_value(
+                    db,
+                    match &kms_key_id {
+                        Some(kms_key_id) => kms_key_id.as_deref(),
+                        None => secret.kms_key_id.as_deref(),
+                    },
+                    &secret.arn,
+                    &version_id,
+                    &secret_string,
+                    &secret_binary,
+                )?;
+
                 transaction(db, move |db| {
                     if let Some(description) = description {
                         update_secret_description(db, &secret.arn, &description).inspect_err(
@@ -97,10 +137,14 @@ impl Handler for UpdateSecretHandler {
                         )?;
                     }
 
-                    let version_id = if secret_string.is_some() || secret_binary.is_some() {
-                        let ClientRequestToken(version_id) =
-                            client_request_token.unwrap_or_default();
+                    if let Some(kms_key_id) = &kms_key_id {
+                        update_secret_kms_key_id(db, &secret.arn, kms_key_id.as_deref())
+                            .inspect_err(|error| {
+                                tracing::error!(?error, "failed to update secret kms key")
+                            })?;
+                    }
 
+                    let version_id = if secret_string.is_some() || secret_binary.is_some() {
                         // Create a new current secret version
                         if let Err(error) = create_secret_version(
                             db,
@@ -109,6 +153,7 @@ impl Handler for UpdateSecretHandler {
                                 version_id: version_id.clone(),
                                 secret_string,
                                 secret_binary,
+                                encrypted_value,
                             },
                         ) {
                             if error.is_constraint_violation() {
@@ -160,6 +205,10 @@ impl Handler for UpdateSecretHandler {
                         None
                     };
 
+                    sync_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                        tracing::error!(?error, "failed to sync secret replicas")
+                    })?;
+
                     Ok::<_, AwsError>((secret, version_id))
                 })
             })
@@ -1,6 +1,5 @@
 use crate::{
     database::{
-        DbHandle,
         ext::SqlErrorExt,
         secrets::{
             add_secret_version_stage, get_secret_latest_version, remove_secret_version_stage,
@@ -9,12 +8,13 @@ use crate::{
         transaction,
     },
     handlers::{
-        Handler,
+        Handler, HandlerContext,
         error::{
             AwsError, InternalServiceError, InvalidRequestException, ResourceNotFoundException,
         },
         models::{SecretId, VersionId},
     },
+    replication::sync_secret_replicas,
 };
 use garde::Validate;
 use serde::{Deserialize, Serialize};
@@ -53,17 +53,32 @@ impl Handler for UpdateSecretVersionStageHandler {
     type Request = UpdateSecretVersionStageRequest;
     type Response = UpdateSecretVersionStageResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbHandle, request: Self::Request) -> Result<Self::Response, AwsError> {
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
         let SecretId(secret_id) = request.secret_id;
         let version_stage = request.version_stage;
 
-        let secret = db
+        let region = ctx.region.clone();
+
+        let secret = ctx
+            .db
             .call(move |db| {
-                let secret = get_secret_latest_version(db, &secret_id)
+                let secret = get_secret_latest_version(db, &region, &secret_id)
                     .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
                     .ok_or(ResourceNotFoundException)?;
 
+                // Replicas can only be changed through their primary secret
+                if secret.is_replica() {
+                    return Err(InvalidRequestException.into());
+                }
+
                 transaction(db, move |db| {
                     // Handle removing from a version
                     if let Some(VersionId(source_version_id)) = request.remove_from_version_id {
@@ -126,6 +141,10 @@ impl Handler for UpdateSecretVersionStageHandler {
                         return Err(InternalServiceError.into());
                     }
 
+                    sync_secret_replicas(db, &secret.arn).inspect_err(|error| {
+                        tracing::error!(?error, "failed to sync secret replicas")
+                    })?;
+
                     Ok::<_, AwsError>(secret)
                 })
             })
@@ -0,0 +1,83 @@
+use crate::{
+    database::secrets::get_secret_latest_version,
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, ResourceNotFoundException},
+        models::{SecretId, ValidationErrorsEntry},
+    },
+    policy::{PolicyDocument, PolicyKind},
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_ValidateResourcePolicy.html
+pub struct ValidateResourcePolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct ValidateResourcePolicyRequest {
+    #[serde(rename = "ResourcePolicy")]
+    #[garde(length(min = 1, max = 20480))]
+    resource_policy: String,
+
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: Option<SecretId>,
+}
+
+#[derive(Serialize)]
+pub struct ValidateResourcePolicyResponse {
+    #[serde(rename = "PolicyValidationPassed")]
+    policy_validation_passed: bool,
+    #[serde(rename = "ValidationErrors")]
+    validation_errors: Vec<ValidationErrorsEntry>,
+}
+
+impl Handler for ValidateResourcePolicyHandler {
+    type Request = ValidateResourcePolicyRequest;
+    type Response = ValidateResourcePolicyResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        request
+            .secret_id
+            .as_ref()
+            .map(|SecretId(secret_id)| secret_id.as_str())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        // Policy is validated against the secret when one is provided
+        if let Some(SecretId(secret_id)) = request.secret_id {
+            let region = ctx.region.clone();
+
+            ctx.db
+                .call(move |db| {
+                    get_secret_latest_version(db, &region, &secret_id)
+                        .inspect_err(|error| tracing::error!(?error, "failed to get secret"))?
+                        .ok_or(ResourceNotFoundException)?;
+
+                    Ok::<_, AwsError>(())
+                })
+                .await?;
+        }
+
+        let validation_errors =
+            match PolicyDocument::parse(&request.resource_policy, PolicyKind::Resource) {
+                Ok(_) => Vec::new(),
+                Err(errors) => errors
+                    .into_iter()
+                    .map(|error| ValidationErrorsEntry {
+                        check_name: error.check_name.to_string(),
+                        error_message: error.message,
+                    })
+                    .collect(),
+            };
+
+        Ok(ValidateResourcePolicyResponse {
+            policy_validation_passed: validation_errors.is_empty(),
+            validation_errors,
+        })
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{
+    database::kms::get_kms_key_by_arn,
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, InternalServiceError},
+    },
+    kms::{generate_kms_key, models::KeyMetadata},
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_CreateKey.html
+pub struct CreateKeyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateKeyRequest {
+    #[serde(rename = "Description")]
+    #[garde(inner(length(max = 8192)))]
+    description: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CreateKeyResponse {
+    #[serde(rename = "KeyMetadata")]
+    key_metadata: KeyMetadata,
+}
+
+impl Handler for CreateKeyHandler {
+    type Request = CreateKeyRequest;
+    type Response = CreateKeyResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let key_id = Uuid::new_v4().to_string();
+        let arn = ctx.arn.kms_key_arn(&ctx.region, &key_id);
+        let region = ctx.region.clone();
+
+        let key = ctx
+            .db
+            .call(move |db| {
+                generate_kms_key(db, key_id, arn.clone(), region, request.description)
+                    .inspect_err(|error| tracing::error!(?error, "failed to create kms key"))
+                    .map_err(|_| InternalServiceError)?;
+
+                let key = get_kms_key_by_arn(db, &arn)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get kms key"))?
+                    .ok_or(InternalServiceError)?;
+
+                Ok::<_, AwsError>(key)
+            })
+            .await?;
+
+        Ok(CreateKeyResponse {
+            key_metadata: KeyMetadata::new(key, &ctx.arn.account_id),
+        })
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{
+    database::kms::get_kms_key,
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, NotFoundException},
+    },
+    kms::models::{KeyId, KeyMetadata},
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_DescribeKey.html
+pub struct DescribeKeyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct DescribeKeyRequest {
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: KeyId,
+}
+
+#[derive(Serialize)]
+pub struct DescribeKeyResponse {
+    #[serde(rename = "KeyMetadata")]
+    key_metadata: KeyMetadata,
+}
+
+impl Handler for DescribeKeyHandler {
+    type Request = DescribeKeyRequest;
+    type Response = DescribeKeyResponse;
+
+    #[tracing::instrument(skip_all, fields(key_id = %request.key_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let KeyId(key_id) = request.key_id;
+        let region = ctx.region.clone();
+
+        let key = ctx
+            .db
+            .call(move |db| {
+                let key = get_kms_key(db, &region, &key_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get kms key"))?
+                    .ok_or(NotFoundException)?;
+
+                Ok::<_, AwsError>(key)
+            })
+            .await?;
+
+        Ok(DescribeKeyResponse {
+            key_metadata: KeyMetadata::new(key, &ctx.arn.account_id),
+        })
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{
+    database::kms::{get_kms_key, set_kms_key_enabled},
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, NotFoundException},
+    },
+    kms::models::KeyId,
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_DisableKey.html
+pub struct DisableKeyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct DisableKeyRequest {
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: KeyId,
+}
+
+#[derive(Serialize)]
+pub struct DisableKeyResponse {}
+
+impl Handler for DisableKeyHandler {
+    type Request = DisableKeyRequest;
+    type Response = DisableKeyResponse;
+
+    #[tracing::instrument(skip_all, fields(key_id = %request.key_id))]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let KeyId(key_id) = request.key_id;
+        let region = ctx.region.clone();
+
+        ctx.db
+            .call(move |db| {
+                let key = get_kms_key(db, &region, &key_id)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get kms key"))?
+                    .ok_or(NotFoundException)?;
+
+                set_kms_key_enabled(db, &key.arn, false)
+                    .inspect_err(|error| tracing::error!(?error, "failed to disable kms key"))?;
+
+                Ok::<_, AwsError>(())
+            })
+            .await?;
+
+        Ok(DisableKeyResponse {})
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    database::kms::get_kms_keys_page,
+    handlers::{
+        Handler, HandlerContext,
+        error::{AwsError, InvalidRequestException},
+        models::PaginationToken,
+    },
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_ListKeys.html
+pub struct ListKeysHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct ListKeysRequest {
+    #[serde(rename = "Limit")]
+    #[serde(default = "default_limit")]
+    #[garde(range(min = 1, max = 1000))]
+    limit: i32,
+
+    #[serde(rename = "Marker")]
+    #[serde(default = "default_marker")]
+    #[garde(dive)]
+    marker: PaginationToken,
+}
+
+#[derive(Serialize)]
+pub struct ListKeysResponse {
+    #[serde(rename = "Keys")]
+    keys: Vec<KeyListEntry>,
+    #[serde(rename = "NextMarker")]
+    next_marker: Option<String>,
+    #[serde(rename = "Truncated")]
+    truncated: bool,
+}
+
+#[derive(Serialize)]
+pub struct KeyListEntry {
+    #[serde(rename = "KeyArn")]
+    key_arn: String,
+    #[serde(rename = "KeyId")]
+    key_id: String,
+}
+
+fn default_limit() -> i32 {
+    100
+}
+
+fn default_marker() -> PaginationToken {
+    PaginationToken {
+        page_size: 100,
+        page_index: 0,
+    }
+}
+
+impl Handler for ListKeysHandler {
+    type Request = ListKeysRequest;
+    type Response = ListKeysResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        ctx: &HandlerContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, AwsError> {
+        let pagination_token = request.marker.page_size(request.limit);
+        let region = ctx.region.clone();
+
+        let (keys, next_marker) = ctx
+            .db
+            .call(move |db| {
+                let (limit, offset) = pagination_token
+                    .as_query_parts()
+                    .ok_or(InvalidRequestException)?;
+
+                // Request an additional key to determine if there is another page
+                let mut keys = get_kms_keys_page(db, &region, limit + 1, offset)
+                    .inspect_err(|error| tracing::error!(?error, "failed to get kms keys"))?;
+
+                let next_marker = if keys.len() as i64 > limit {
+                    keys.truncate(limit as usize);
+                    pagination_token
+                        .get_next_page(i64::MAX)
+                        .map(|value| value.to_string())
+                } else {
+                    None
+                };
+
+                Ok::<_, AwsError>((keys, next_marker))
+            })
+            .await?;
+
+        Ok(ListKeysResponse {
+            keys: keys
+                .into_iter()
+                .map(|key| KeyListEntry {
+                    key_arn: key.arn,
+                    key_id: key.key_id,
+                })
+                .collect(),
+            truncated: next_marker.is_some(),
+            next_marker,
+        })
+    }
+}
@@ -0,0 +1,263 @@
+//! Local stand-in for the AWS Key Management Service (KMS), secrets that use a KMS key
+//! are envelope encrypted using a data key that is encrypted by the key material of
+//! the KMS key
+
+use crate::{
+    database::{
+        DbErr,
+        kms::{CreateKmsKey, StoredKmsKey, create_kms_key, get_kms_key, get_kms_key_by_arn},
+        secrets::{EncryptedSecretValue, StoredSecret, StoredSecretWithVersionStages},
+    },
+    handlers::error::{
+        AwsError, DecryptionFailure, EncryptionFailure, InternalServiceError,
+        KMSInvalidStateException,
+    },
+};
+use aws_lc_rs::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    error::Unspecified,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio_rusqlite::rusqlite::Connection;
+
+pub(crate) mod create_key;
+pub(crate) mod describe_key;
+pub(crate) mod disable_key;
+pub(crate) mod enable_key;
+pub(crate) mod list_keys;
+pub(crate) mod models;
+
+/// Alias of the AWS managed key used by secrets that don't specify a KMS key,
+/// secrets using this key are stored without any additional encryption
+pub const DEFAULT_KEY_ALIAS: &str = "alias/aws/secretsmanager";
+
+/// Length of the key material and data keys in bytes (AES-256)
+const KEY_LENGTH: usize = 32;
+
+/// Plain text value of a secret version that is encrypted by the data key
+#[derive(Serialize, Deserialize)]
+struct SecretValue {
+    secret_string: Option<String>,
+    secret_binary: Option<String>,
+}
+
+/// Generate new random key material for a KMS key or data key
+pub fn generate_key_material() -> Result<Vec<u8>, Unspecified> {
+    let mut key = vec![0; KEY_LENGTH];
+    aws_lc_rs::rand::fill(&mut key)?;
+    Ok(key)
+}
+
+#[derive(Debug, Error)]
+pub enum GenerateKmsKeyError {
+    #[error("failed to generate key material")]
+    KeyMaterial,
+
+    #[error(transparent)]
+    Db(#[from] DbErr),
+}
+
+/// Create a KMS key with the `key_id` in `region` using newly generated key material,
+/// nothing is created when a key with the `key_id` already exists
+pub fn generate_kms_key(
+    db: &Connection,
+    key_id: String,
+    arn: String,
+    region: String,
+    description: Option<String>,
+) -> Result<usize, GenerateKmsKeyError> {
+    let key_material = generate_key_material().map_err(|_| GenerateKmsKeyError::KeyMaterial)?;
+
+    let created = create_kms_key(
+        db,
+        CreateKmsKey {
+            key_id,
+            arn,
+            region,
+            description,
+            key_material,
+        },
+    )?;
+
+    Ok(created)
+}
+
+/// Resolve the `key_id` provided by a client (Key ID or ARN) to the ARN of the KMS key
+/// in `region`, the default AWS managed key resolves to [None]
+pub fn resolve_kms_key_id(
+    db: &Connection,
+    region: &str,
+    key_id: &str,
+) -> Result<Option<String>, AwsError> {
+    if key_id == DEFAULT_KEY_ALIAS {
+        return Ok(None);
+    }
+
+    let key = get_kms_key(db, region, key_id)
+        .inspect_err(|error| tracing::error!(?error, "failed to get kms key"))?
+        .ok_or(EncryptionFailure)?;
+
+    if !key.enabled {
+        return Err(KMSInvalidStateException.into());
+    }
+
+    Ok(Some(key.arn))
+}
+
+/// Encrypt the value of a new secret version using the KMS key with the `kms_key_arn`,
+/// values are not encrypted when the secret doesn't use a KMS key. The value is bound
+/// to the `secret_arn` and `version_id` so it can't be decrypted as any other version
+pub fn encrypt_secret_value(
+    db: &Connection,
+    kms_key_arn: Option<&str>,
+    secret_arn: &str,
+    version_id: &str,
+    secret_string: &Option<String>,
+    secret_binary: &Option<String>,
+) -> Result<Option<EncryptedSecretValue>, AwsError> {
+    let kms_key_arn = match kms_key_arn {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let key = get_usable_kms_key(db, kms_key_arn)?.ok_or(EncryptionFailure)?;
+
+    let value = serde_json::to_vec(&SecretValue {
+        secret_string: secret_string.clone(),
+        secret_binary: secret_binary.clone(),
+    })
+    .map_err(|error| {
+        tracing::error!(?error, "failed to serialize secret value");
+        InternalServiceError
+    })?;
+
+    let aad = version_aad(secret_arn, version_id);
+    let encrypted = generate_key_material().and_then(|data_key| {
+        let encrypted_value = seal(&data_key, &value, &aad)?;
+        let encrypted_data_key = seal(&key.key_material, &data_key, &aad)?;
+        Ok((encrypted_data_key, encrypted_value))
+    });
+
+    let (encrypted_data_key, encrypted_value) = encrypted.map_err(|_| {
+        tracing::error!("failed to encrypt secret value");
+        EncryptionFailure
+    })?;
+
+    Ok(Some(EncryptedSecretValue {
+        kms_key_id: key.arn,
+        encrypted_data_key,
+        encrypted_value,
+    }))
+}
+
+/// Decrypt the value of the version loaded for the `secret` replacing its
+/// `secret_string` and `secret_binary`, versions that aren't encrypted are left as is
+pub fn decrypt_secret_value(db: &Connection, secret: &mut StoredSecret) -> Result<(), AwsError> {
+    if let Some(encrypted) = &secret.encrypted_value {
+        (secret.secret_string, secret.secret_binary) =
+            decrypt_value(db, &secret.arn, &secret.version_id, encrypted)?;
+    }
+
+    Ok(())
+}
+
+/// Decrypt the value of the current version loaded for the `secret`, see [decrypt_secret_value]
+pub fn decrypt_secret_list_value(
+    db: &Connection,
+    secret: &mut StoredSecretWithVersionStages,
+) -> Result<(), AwsError> {
+    if let Some(encrypted) = &secret.encrypted_value {
+        (secret.secret_string, secret.secret_binary) =
+            decrypt_value(db, &secret.arn, &secret.version_id, encrypted)?;
+    }
+
+    Ok(())
+}
+
+/// Decrypt the `encrypted` value of the version with the `version_id` of the secret
+/// with the `secret_arn` into its secret string and binary
+pub fn decrypt_value(
+    db: &Connection,
+    secret_arn: &str,
+    version_id: &str,
+    encrypted: &EncryptedSecretValue,
+) -> Result<(Option<String>, Option<String>), AwsError> {
+    let key = get_usable_kms_key(db, &encrypted.kms_key_id)?.ok_or(DecryptionFailure)?;
+
+    let aad = version_aad(secret_arn, version_id);
+    let value = open(&key.key_material, &encrypted.encrypted_data_key, &aad)
+        .and_then(|data_key| open(&data_key, &encrypted.encrypted_value, &aad))
+        .map_err(|_| {
+            tracing::error!("failed to decrypt secret value");
+            DecryptionFailure
+        })?;
+
+    let value: SecretValue = serde_json::from_slice(&value).map_err(|error| {
+        tracing::error!(?error, "failed to deserialize secret value");
+        DecryptionFailure
+    })?;
+
+    Ok((value.secret_string, value.secret_binary))
+}
+
+/// Get the KMS key with the `kms_key_arn` ensuring the key is enabled
+fn get_usable_kms_key(
+    db: &Connection,
+    kms_key_arn: &str,
+) -> Result<Option<StoredKmsKey>, AwsError> {
+    let key = match get_kms_key_by_arn(db, kms_key_arn)
+        .inspect_err(|error| tracing::error!(?error, "failed to get kms key"))?
+    {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    if !key.enabled {
+        return Err(KMSInvalidStateException.into());
+    }
+
+    Ok(Some(key))
+}
+
+/// Additional authenticated data binding an encrypted value to the version with the
+/// `version_id` of the secret with the `secret_arn`
+fn version_aad(secret_arn: &str, version_id: &str) -> Vec<u8> {
+    format!("{secret_arn}\n{version_id}").into_bytes()
+}
+
+/// Encrypt `value` using AES-256-GCM with the `key` authenticating the `aad`, the
+/// random nonce is prepended to the output
+fn seal(key: &[u8], value: &[u8], aad: &[u8]) -> Result<Vec<u8>, Unspecified> {
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key)?);
+
+    let mut nonce = [0; NONCE_LEN];
+    aws_lc_rs::rand::fill(&mut nonce)?;
+
+    let mut output = value.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(aad),
+        &mut output,
+    )?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&output);
+    Ok(sealed)
+}
+
+/// Decrypt a `value` created by [seal] using the `key` and the same `aad`
+fn open(key: &[u8], value: &[u8], aad: &[u8]) -> Result<Vec<u8>, Unspecified> {
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key)?);
+
+    if value.len() < NONCE_LEN {
+        return Err(Unspecified);
+    }
+
+    let (nonce, value) = value.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce)?;
+
+    let mut output = value.to_vec();
+    let value = key.open_in_place(nonce, Aad::from(aad), &mut output)?;
+    Ok(value.to_vec())
+}
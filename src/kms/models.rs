@@ -0,0 +1,69 @@
+use crate::{database::kms::StoredKmsKey, utils::date::datetime_to_f64};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Identifier of a KMS key, either the key ID or key ARN
+#[derive(Debug, Deserialize, Validate)]
+#[garde(transparent)]
+pub struct KeyId(#[garde(length(min = 1, max = 2048))] pub String);
+
+impl Display for KeyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Serialize)]
+pub struct KeyMetadata {
+    #[serde(rename = "AWSAccountId")]
+    aws_account_id: String,
+    #[serde(rename = "KeyId")]
+    key_id: String,
+    #[serde(rename = "Arn")]
+    arn: String,
+    #[serde(rename = "CreationDate")]
+    creation_date: f64,
+    #[serde(rename = "Enabled")]
+    enabled: bool,
+    #[serde(rename = "Description")]
+    description: String,
+    #[serde(rename = "KeyUsage")]
+    key_usage: &'static str,
+    #[serde(rename = "KeyState")]
+    key_state: &'static str,
+    #[serde(rename = "Origin")]
+    origin: &'static str,
+    #[serde(rename = "KeyManager")]
+    key_manager: &'static str,
+    #[serde(rename = "KeySpec")]
+    key_spec: &'static str,
+    #[serde(rename = "CustomerMasterKeySpec")]
+    customer_master_key_spec: &'static str,
+    #[serde(rename = "EncryptionAlgorithms")]
+    encryption_algorithms: Vec<&'static str>,
+    #[serde(rename = "MultiRegion")]
+    multi_region: bool,
+}
+
+impl KeyMetadata {
+    pub fn new(key: StoredKmsKey, account_id: &str) -> Self {
+        Self {
+            aws_account_id: account_id.to_string(),
+            key_id: key.key_id,
+            arn: key.arn,
+            creation_date: datetime_to_f64(key.created_at),
+            enabled: key.enabled,
+            description: key.description.unwrap_or_default(),
+            // Only symmetric encryption keys are supported
+            key_usage: "ENCRYPT_DECRYPT",
+            key_state: if key.enabled { "Enabled" } else { "Disabled" },
+            origin: "AWS_KMS",
+            key_manager: "CUSTOMER",
+            key_spec: "SYMMETRIC_DEFAULT",
+            customer_master_key_spec: "SYMMETRIC_DEFAULT",
+            encryption_algorithms: vec!["SYMMETRIC_DEFAULT"],
+            multi_region: false,
+        }
+    }
+}
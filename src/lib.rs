@@ -1,4 +1,16 @@
+pub mod admin;
+pub mod arn;
 pub mod database;
+pub mod dump;
+pub mod fault;
 pub mod handlers;
+pub mod kms;
+pub mod metrics;
 pub mod middleware;
+pub mod policy;
+pub mod replication;
+pub mod rotation;
+pub mod seed;
+pub mod sts;
+pub mod throttle;
 mod utils;
@@ -8,6 +8,15 @@ pub fn init_logging() {
         .init();
 }
 
+/// Initialize logging for the administrative commands, logs are written to stderr
+/// to keep them separate from the command output
+pub fn init_cli_logging() {
+    tracing_subscriber::registry()
+        .with(filter())
+        .with(fmt_layer().with_writer(std::io::stderr))
+        .init();
+}
+
 fn fmt_layer<S>() -> Layer<S> {
     tracing_subscriber::fmt::layer()
         // Display source code file paths
@@ -1,39 +1,90 @@
 #![forbid(unsafe_code)]
 
 use crate::{
-    background::perform_background_tasks, config::Config, middleware::aws_sig_v4::AwsSigV4AuthLayer,
+    admin::{SnapshotStore, admin_router},
+    background::perform_background_tasks,
+    cli::Command,
+    config::Config,
+    database::{DbHandle, transaction},
+    fault::{FaultLayer, FaultStore},
+    kms::{GenerateKmsKeyError, generate_kms_key},
+    metrics::{Metrics, MetricsLayer, metrics_handler},
+    middleware::aws_sig_v4::AwsSigV4AuthLayer,
+    rotation::RotationRunner,
+    seed::{apply_seed, load_seed_file},
+    sts::StsLayer,
+    throttle::ThrottleLayer,
+};
+use axum::{
+    Extension, Router,
+    http::StatusCode,
+    routing::{get, post_service},
 };
-use axum::{Extension, Router, http::StatusCode, routing::post_service};
 use axum_server::tls_rustls::RustlsConfig;
 use std::{error::Error, net::SocketAddr};
+use tower::Layer;
 use tower_http::trace::TraceLayer;
 
 pub mod database;
 pub mod middleware;
 
+mod admin;
+mod arn;
 mod background;
+mod cli;
 mod config;
+mod dump;
+mod fault;
 mod handlers;
+mod kms;
 mod logging;
+mod metrics;
+mod policy;
+mod replication;
+mod rotation;
+mod seed;
+mod sts;
+mod throttle;
 mod utils;
 
 fn main() -> Result<(), Box<dyn Error>> {
     _ = dotenvy::dotenv();
 
-    logging::init_logging();
+    let command = match cli::parse_command(std::env::args().skip(1)) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("error: {error}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
 
-    tokio::runtime::Builder::new_current_thread()
+    let runtime = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
-        .expect("Failed building the Runtime")
-        .block_on(async move {
-            if let Err(error) = server().await {
-                tracing::error!(?error, message = %error, "error running server");
-                return Err(error);
-            }
-
-            Ok(())
-        })
+        .expect("Failed building the Runtime");
+
+    // Administrative commands run without starting the server
+    if !matches!(command, Command::Server) {
+        logging::init_cli_logging();
+
+        if let Err(error) = runtime.block_on(cli::run_command(command)) {
+            eprintln!("error: {error}");
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    logging::init_logging();
+
+    runtime.block_on(async move {
+        if let Err(error) = server().await {
+            tracing::error!(?error, message = %error, "error running server");
+            return Err(error);
+        }
+
+        Ok(())
+    })
 }
 
 async fn server() -> Result<(), Box<dyn Error>> {
@@ -45,19 +96,113 @@ async fn server() -> Result<(), Box<dyn Error>> {
         }
     };
 
+    // Key the database is encrypted with once any rekeying is complete
+    let database_key = config
+        .new_encryption_key
+        .clone()
+        .or_else(|| config.encryption_key.clone());
+
     // Setup database
-    let db = database::create_database(config.encryption_key, config.database_path).await?;
+    let db = match config.encryption_key {
+        Some(encryption_key) => {
+            database::create_database(
+                encryption_key,
+                config.new_encryption_key,
+                config.database_path,
+            )
+            .await?
+        }
+        // Only in-memory databases are created without an encryption key
+        None => {
+            tracing::warn!(
+                "using an in-memory database, all data will be lost when the server is stopped"
+            );
+            database::create_memory_database().await?
+        }
+    };
+
+    // Calls made through the database handle are recorded in the metrics
+    let metrics = Metrics::default();
+    let db = DbHandle::new(db, metrics.clone());
+
+    // Create the configured KMS keys
+    let kms_keys = config.kms_keys;
+    let arn = config.arn.clone();
+    db.call(move |db| {
+        for key_id in kms_keys {
+            let key_arn = arn.kms_key_arn(&arn.region, &key_id);
+            generate_kms_key(db, key_id, key_arn, arn.region.clone(), None)?;
+        }
 
-    // Setup the handlers
+        Ok::<_, GenerateKmsKeyError>(())
+    })
+    .await?;
+
+    // Apply the seed file before accepting requests
+    if let Some(seed_file) = config.seed_file {
+        let seed = load_seed_file(&seed_file)?;
+        let arn = config.arn.clone();
+        let seed_mode = config.seed_mode;
+
+        let summary = db
+            .call(move |db| transaction(db, move |t| apply_seed(t, &arn, seed_mode, seed)))
+            .await?;
+
+        tracing::info!(
+            secrets_created = summary.secrets_created,
+            versions_created = summary.versions_created,
+            ?seed_mode,
+            "applied seed file"
+        );
+    }
+
+    // Capture the initial state of the database for resetting through the admin routes
+    let snapshots = SnapshotStore::create(&db, database_key).await?;
+
+    // Setup the rotation runner
+    let rotation = RotationRunner::new(config.rotation_lambdas);
+
+    // Setup the handlers, requests are throttled and faults configured through
+    // the admin routes are applied before requests reach the handlers
+    let faults = FaultStore::default();
     let handlers = handlers::create_handlers();
-    let handlers_service = handlers.into_service();
+    let metrics_layer = MetricsLayer::new(metrics.clone(), handlers.targets());
+    let handlers_service = FaultLayer::new(faults.clone()).layer(handlers.into_service());
+    let handlers_service = ThrottleLayer::new(config.throttle_limits).layer(handlers_service);
+
+    // Setup the authentication layer
+    let auth_layer = AwsSigV4AuthLayer::from_access_keys(config.access_keys)
+        .with_debug_signatures(config.debug_signatures)
+        .with_replay_protection(config.replay_cache_size)
+        .with_metrics(metrics.clone());
+    let access_keys = auth_layer.access_keys();
 
-    // Setup router
+    // Setup router, STS query protocol requests are handled alongside the handlers and
+    // the admin routes are authenticated using the same access keys, the health and
+    // metrics routes are not authenticated
     let app = Router::new()
         .route_service("/", post_service(handlers_service))
-        .layer(AwsSigV4AuthLayer::new(config.credentials))
-        .route("/health", axum::routing::get(health))
+        .merge(admin_router())
+        .layer(StsLayer)
+        .layer(auth_layer)
+        .layer(metrics_layer)
+        .route("/health", get(health));
+
+    // Metrics are only served when enabled as the route isn't authenticated
+    let app = if config.metrics {
+        app.route("/metrics", get(metrics_handler))
+    } else {
+        app
+    };
+
+    let app = app
+        .layer(Extension(access_keys))
+        .layer(Extension(config.arn))
         .layer(Extension(db.clone()))
+        .layer(Extension(rotation.clone()))
+        .layer(Extension(snapshots))
+        .layer(Extension(faults))
+        .layer(Extension(metrics.clone()))
         .layer(TraceLayer::new_for_http());
 
     // Development mode CORS access for local browser testing
@@ -65,7 +210,7 @@ async fn server() -> Result<(), Box<dyn Error>> {
     let app = app.layer(tower_http::cors::CorsLayer::very_permissive());
 
     // Spawn the background task runner
-    tokio::spawn(perform_background_tasks(db.clone()));
+    tokio::spawn(perform_background_tasks(db.clone(), rotation, metrics));
 
     let handle = axum_server::Handle::default();
 
@@ -0,0 +1,432 @@
+//! Prometheus metrics describing the requests handled by the server, the metrics
+//! are served in the Prometheus text format from `/metrics`
+
+use crate::{
+    database::{DbHandle, secrets::get_secret_counts},
+    handlers::error::ErrorType,
+};
+use axum::{
+    Extension,
+    body::Body,
+    http::{Request, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use futures::future::BoxFuture;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+    mem::swap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+/// Upper bounds (in seconds) of the latency histogram buckets
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+/// Target label used for requests to targets that aren't handled by the server,
+/// prevents clients from creating an unbounded number of metrics
+const UNKNOWN_TARGET: &str = "unknown";
+
+/// Content type of the Prometheus text format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Histogram of durations using the [LATENCY_BUCKETS]
+#[derive(Default)]
+struct Histogram {
+    /// Number of observations within each bucket, buckets include the
+    /// observations of the smaller buckets
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    /// Sum of all the observed durations in seconds
+    sum: f64,
+    /// Total number of observations
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+
+        for (bucket, upper_bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= upper_bound {
+                *bucket += 1;
+            }
+        }
+
+        self.sum += seconds;
+        self.count += 1;
+    }
+
+    /// Write the samples of the histogram `name` with the `labels` to the `output`
+    fn write(&self, output: &mut String, name: &str, labels: &str) {
+        let separator = if labels.is_empty() { "" } else { "," };
+
+        for (bucket, upper_bound) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            _ = writeln!(
+                output,
+                "{name}_bucket{{{labels}{separator}le=\"{upper_bound}\"}} {bucket}"
+            );
+        }
+
+        _ = writeln!(
+            output,
+            "{name}_bucket{{{labels}{separator}le=\"+Inf\"}} {}",
+            self.count
+        );
+
+        // Samples without labels are written without the braces
+        let label_set = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+
+        _ = writeln!(output, "{name}_sum{label_set} {}", self.sum);
+        _ = writeln!(output, "{name}_count{label_set} {}", self.count);
+    }
+}
+
+/// Values of the collected metrics
+#[derive(Default)]
+struct MetricsState {
+    /// Request latency keyed by target and error type
+    requests: BTreeMap<(String, &'static str), Histogram>,
+    /// Number of authentication failures keyed by reason
+    auth_failures: BTreeMap<&'static str, u64>,
+    /// Number of background task runs keyed by task and status
+    background_task_runs: BTreeMap<(&'static str, &'static str), u64>,
+    /// Background task durations keyed by task
+    background_task_durations: BTreeMap<&'static str, Histogram>,
+    /// Latency of the database calls
+    db_calls: Histogram,
+}
+
+/// Collection of metrics shared between the layers, database handle and
+/// background tasks that record them
+#[derive(Clone, Default)]
+pub struct Metrics {
+    state: Arc<Mutex<MetricsState>>,
+}
+
+impl Metrics {
+    fn state(&self) -> MutexGuard<'_, MetricsState> {
+        self.state.lock().expect("metrics lock poisoned")
+    }
+
+    /// Record a request to the `target` that took `duration` to handle, `error_type`
+    /// is empty for successful requests
+    pub fn record_request(&self, target: &str, error_type: &'static str, duration: Duration) {
+        self.state()
+            .requests
+            .entry((target.to_string(), error_type))
+            .or_default()
+            .observe(duration);
+    }
+
+    /// Record a request that failed authentication for the `reason`
+    pub fn record_auth_failure(&self, reason: &'static str) {
+        *self.state().auth_failures.entry(reason).or_default() += 1;
+    }
+
+    /// Record a run of the background `task` that took `duration`
+    pub fn record_background_task(&self, task: &'static str, success: bool, duration: Duration) {
+        let status = if success { "success" } else { "error" };
+        let mut state = self.state();
+
+        *state
+            .background_task_runs
+            .entry((task, status))
+            .or_default() += 1;
+        state
+            .background_task_durations
+            .entry(task)
+            .or_default()
+            .observe(duration);
+    }
+
+    /// Record a database call that took `duration`
+    pub fn record_db_call(&self, duration: Duration) {
+        self.state().db_calls.observe(duration);
+    }
+
+    /// Render the metrics in the Prometheus text format, the secret and version
+    /// count gauges are only included when the `secret_counts` are known
+    pub fn render(&self, secret_counts: Option<(i64, i64)>) -> String {
+        let state = self.state();
+        let mut output = String::new();
+
+        output.push_str("# HELP loker_requests_total Number of requests handled.\n");
+        output.push_str("# TYPE loker_requests_total counter\n");
+        for ((target, error_type), histogram) in &state.requests {
+            _ = writeln!(
+                output,
+                "loker_requests_total{{{}}} {}",
+                request_labels(target, error_type),
+                histogram.count
+            );
+        }
+
+        output.push_str("# HELP loker_request_duration_seconds Time taken to handle requests.\n");
+        output.push_str("# TYPE loker_request_duration_seconds histogram\n");
+        for ((target, error_type), histogram) in &state.requests {
+            histogram.write(
+                &mut output,
+                "loker_request_duration_seconds",
+                &request_labels(target, error_type),
+            );
+        }
+
+        output.push_str(
+            "# HELP loker_auth_failures_total Number of requests that failed authentication.\n",
+        );
+        output.push_str("# TYPE loker_auth_failures_total counter\n");
+        for (reason, count) in &state.auth_failures {
+            _ = writeln!(
+                output,
+                "loker_auth_failures_total{{reason=\"{reason}\"}} {count}"
+            );
+        }
+
+        if let Some((secrets, versions)) = secret_counts {
+            output.push_str("# HELP loker_secrets Number of secrets stored.\n");
+            output.push_str("# TYPE loker_secrets gauge\n");
+            _ = writeln!(output, "loker_secrets {secrets}");
+
+            output.push_str("# HELP loker_secret_versions Number of secret versions stored.\n");
+            output.push_str("# TYPE loker_secret_versions gauge\n");
+            _ = writeln!(output, "loker_secret_versions {versions}");
+        }
+
+        output
+            .push_str("# HELP loker_background_task_runs_total Number of background task runs.\n");
+        output.push_str("# TYPE loker_background_task_runs_total counter\n");
+        for ((task, status), count) in &state.background_task_runs {
+            _ = writeln!(
+                output,
+                "loker_background_task_runs_total{{task=\"{task}\",status=\"{status}\"}} {count}"
+            );
+        }
+
+        output.push_str(
+            "# HELP loker_background_task_duration_seconds Time taken to run background tasks.\n",
+        );
+        output.push_str("# TYPE loker_background_task_duration_seconds histogram\n");
+        for (task, histogram) in &state.background_task_durations {
+            histogram.write(
+                &mut output,
+                "loker_background_task_duration_seconds",
+                &format!("task=\"{task}\""),
+            );
+        }
+
+        output.push_str("# HELP loker_db_call_duration_seconds Time taken by database calls.\n");
+        output.push_str("# TYPE loker_db_call_duration_seconds histogram\n");
+        state
+            .db_calls
+            .write(&mut output, "loker_db_call_duration_seconds", "");
+
+        output
+    }
+}
+
+/// Create the labels of a request metric
+fn request_labels(target: &str, error_type: &str) -> String {
+    format!(
+        "target=\"{}\",error_type=\"{error_type}\"",
+        escape_label_value(target)
+    )
+}
+
+/// Escape a label value for the Prometheus text format
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Handler serving the metrics in the Prometheus text format
+pub async fn metrics_handler(
+    Extension(metrics): Extension<Metrics>,
+    Extension(db): Extension<DbHandle>,
+) -> Response {
+    let secret_counts = match db.call(|db| get_secret_counts(db)).await {
+        Ok(value) => Some(value),
+        Err(error) => {
+            tracing::error!(?error, "failed to count secrets for metrics");
+            None
+        }
+    };
+
+    (
+        [(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        metrics.render(secret_counts),
+    )
+        .into_response()
+}
+
+/// Layer recording the count and latency of requests
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: Metrics,
+    /// Targets handled by the server
+    targets: Arc<HashSet<String>>,
+}
+
+impl MetricsLayer {
+    /// Create a metrics layer recording to the `metrics`, requests to targets other
+    /// than the known `targets` are recorded under a single "unknown" target
+    pub fn new<T: Into<String>>(metrics: Metrics, targets: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            metrics,
+            targets: Arc::new(targets.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsMiddleware {
+            inner,
+            metrics: self.metrics.clone(),
+            targets: self.targets.clone(),
+        }
+    }
+}
+
+/// Middleware structure
+#[derive(Clone)]
+pub struct MetricsMiddleware<S> {
+    inner: S,
+    metrics: Metrics,
+    targets: Arc<HashSet<String>>,
+}
+
+impl<S> Service<Request<Body>> for MetricsMiddleware<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let metrics = self.metrics.clone();
+
+        // Swap to ensure we get the service that was ready and not the cloned one
+        swap(&mut inner, &mut self.inner);
+
+        // Requests without a target (STS and the admin routes) use an empty target
+        let target = match req
+            .headers()
+            .get("x-amz-target")
+            .and_then(|value| value.to_str().ok())
+        {
+            Some(value) if self.targets.contains(value) => value.to_string(),
+            Some(_) => UNKNOWN_TARGET.to_string(),
+            None => String::new(),
+        };
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let response = inner.call(req).await?;
+
+            let error_type = response
+                .extensions()
+                .get::<ErrorType>()
+                .map(|value| value.0)
+                .unwrap_or_default();
+
+            metrics.record_request(&target, error_type, start.elapsed());
+
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Metrics;
+    use std::time::Duration;
+
+    /// Tests that recorded metrics are rendered in the Prometheus text format
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.record_request("secretsmanager.CreateSecret", "", Duration::from_millis(3));
+        metrics.record_request(
+            "secretsmanager.CreateSecret",
+            "ResourceExistsException",
+            Duration::from_millis(20),
+        );
+        metrics.record_auth_failure("signature_mismatch");
+        metrics.record_background_task("scheduled_rotations", true, Duration::from_millis(1));
+        metrics.record_db_call(Duration::from_secs(10));
+
+        let output = metrics.render(Some((2, 3)));
+
+        assert!(output.contains(
+            "loker_requests_total{target=\"secretsmanager.CreateSecret\",error_type=\"\"} 1\n"
+        ));
+        assert!(output.contains(
+            "loker_request_duration_seconds_bucket{target=\"secretsmanager.CreateSecret\",\
+            error_type=\"ResourceExistsException\",le=\"0.025\"} 1\n"
+        ));
+        assert!(output.contains(
+            "loker_request_duration_seconds_bucket{target=\"secretsmanager.CreateSecret\",\
+            error_type=\"ResourceExistsException\",le=\"0.01\"} 0\n"
+        ));
+        assert!(output.contains("loker_auth_failures_total{reason=\"signature_mismatch\"} 1\n"));
+        assert!(output.contains("loker_secrets 2\n"));
+        assert!(output.contains("loker_secret_versions 3\n"));
+        assert!(output.contains(
+            "loker_background_task_runs_total{task=\"scheduled_rotations\",status=\"success\"} 1\n"
+        ));
+        assert!(output.contains("loker_db_call_duration_seconds_bucket{le=\"5\"} 0\n"));
+        assert!(output.contains("loker_db_call_duration_seconds_bucket{le=\"+Inf\"} 1\n"));
+    }
+
+    /// Tests that metrics without labels are rendered without braces and that the
+    /// secret counts are omitted when they aren't known
+    #[test]
+    fn test_render_without_labels() {
+        let metrics = Metrics::default();
+        metrics.record_db_call(Duration::from_millis(250));
+        metrics.record_db_call(Duration::from_millis(500));
+
+        let output = metrics.render(None);
+
+        assert!(output.contains("# TYPE loker_db_call_duration_seconds histogram\n"));
+        assert!(output.contains("loker_db_call_duration_seconds_sum 0.75\n"));
+        assert!(output.contains("loker_db_call_duration_seconds_count 2\n"));
+        assert!(!output.contains("loker_secrets"));
+        assert!(!output.contains("loker_secret_versions"));
+        assert!(output.ends_with('\n'));
+    }
+
+    /// Tests that label values are escaped
+    #[test]
+    fn test_render_escaped_labels() {
+        let metrics = Metrics::default();
+        metrics.record_request("quote\"back\\slash\nline", "", Duration::from_millis(1));
+
+        let output = metrics.render(None);
+
+        assert!(output.contains(
+            "loker_requests_total{target=\"quote\\\"back\\\\slash\\nline\",error_type=\"\"} 1\n"
+        ));
+    }
+}
@@ -1,42 +1,227 @@
 use crate::{
+    arn::ArnConfig,
     handlers::error::{
-        IncompleteSignature, InternalServiceError, IntoErrorResponse, InvalidClientTokenId,
-        InvalidRequestException, MissingAuthenticationToken, SignatureDoesNotMatch,
+        AwsError, ExpiredTokenException, IncompleteSignature, InternalServiceError,
+        IntoErrorResponse, InvalidClientTokenId, InvalidRequestException,
+        InvalidSignatureException, MissingAuthenticationToken, SignatureDoesNotMatch,
+        UnrecognizedClientException,
     },
+    metrics::Metrics,
+    middleware::replay::{ReplayCache, is_mutating_admin_request, is_mutating_target},
+    policy::PolicyDocument,
     utils::{
-        aws_sig_v4::parse_auth_header,
-        date::{chrono_to_system_time, parse_amz_date, parse_http_date},
+        aws_sig_v4::{
+            AwsSigV4Auth, InvalidSignedHeader, SigningAlgorithm, canonical_request, has_auth_query,
+            hex_encode, parse_auth_header, parse_auth_query, string_to_sign, strip_auth_query,
+        },
+        aws_sig_v4a::{derive_key_pair, verify_signature as verify_v4a},
+        date::{chrono_to_system_time, format_amz_date, parse_amz_date, parse_http_date},
     },
 };
 use aws_credential_types::Credentials;
+use aws_lc_rs::digest::{SHA256, digest};
 use aws_sigv4::{
-    http_request::{SignableBody, SignableRequest, SigningSettings, sign},
+    http_request::{SignableBody, SignableRequest, SignatureLocation, SigningSettings, sign},
     sign::v4::SigningParams,
 };
 use axum::{
     body::Body,
-    http::{
-        Request,
-        header::{AUTHORIZATION, ToStrError},
-    },
+    http::{HeaderMap, Request, header::AUTHORIZATION, request::Parts},
     response::Response,
 };
-use chrono::Utc;
+use chrono::{DateTime, TimeDelta, Utc};
 use futures::future::BoxFuture;
 use http_body_util::BodyExt;
-use std::mem::swap;
+use std::{
+    collections::HashMap,
+    mem::swap,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
 use tower::{Layer, Service};
 
+/// Header containing the session token for temporary credentials
+const SECURITY_TOKEN_HEADER: &str = "x-amz-security-token";
+
+/// Header containing the regions a SigV4A signature is valid for
+const REGION_SET_HEADER: &str = "x-amz-region-set";
+
+/// Content hash used by requests that don't sign their payload
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Allowed difference between the request date and the server time (5 minutes)
+const MAX_CLOCK_SKEW_SECONDS: i64 = 60 * 5;
+
+/// Maximum time a presigned request can be valid for (7 days)
+const MAX_PRESIGNED_EXPIRES_SECONDS: u64 = 60 * 60 * 24 * 7;
+
+/// Message of the error returned when the request signature is not the
+/// signature the server calculated
+const SIGNATURE_MISMATCH_MESSAGE: &str = "The request signature we calculated does not match the \
+    signature you provided. Check your AWS Secret Access Key and signing method. Consult the \
+    service documentation for details.";
+
+/// Message of the error returned when a signature is used again by a request to
+/// a target that changes state
+const SIGNATURE_REPLAYED_MESSAGE: &str = "The request signature has already been used. Requests \
+    that change state must be signed again each time they are sent.";
+
+/// Access key that can be used to sign requests along with the identity
+/// the access key belongs to
+#[derive(Clone)]
+pub struct AccessKey {
+    /// Credentials used to verify the request signature, temporary credentials
+    /// include the session token and expiry time
+    pub credentials: Credentials,
+    /// ARN of the principal the access key belongs to
+    pub principal_arn: String,
+    /// Identity policy of the principal
+    pub policy: Option<Arc<PolicyDocument>>,
+}
+
+impl AccessKey {
+    /// Create an access key for the root principal of the account in the `arn`
+    /// config without an identity policy
+    pub fn new(credentials: Credentials, arn: &ArnConfig) -> Self {
+        Self {
+            credentials,
+            principal_arn: arn.iam_arn("root"),
+            policy: None,
+        }
+    }
+}
+
+/// Identity of the caller authenticated by the [AwsSigV4AuthMiddleware], available
+/// to inner services through the request extensions
+#[derive(Clone)]
+pub struct AuthenticatedIdentity {
+    /// Access key ID used to sign the request
+    pub access_key_id: String,
+    /// ARN of the principal the access key belongs to
+    pub principal_arn: String,
+    /// Identity policy of the principal, principals without an identity
+    /// policy are allowed to perform any action
+    pub policy: Option<Arc<PolicyDocument>>,
+    /// Region from the credential scope the request was signed for
+    pub region: String,
+    /// Time the access key expires, only present for temporary credentials
+    pub expiry: Option<SystemTime>,
+}
+
+/// Collection of access keys indexed by their access key ID, shared between the
+/// [AwsSigV4AuthMiddleware] and the STS handlers that issue temporary credentials
+#[derive(Clone, Default)]
+pub struct AccessKeyStore {
+    access_keys: Arc<RwLock<HashMap<String, AccessKey>>>,
+}
+
+impl AccessKeyStore {
+    /// Create a store containing the provided `access_keys`
+    pub fn new(access_keys: impl IntoIterator<Item = AccessKey>) -> Self {
+        let access_keys = access_keys
+            .into_iter()
+            .map(|access_key| {
+                (
+                    access_key.credentials.access_key_id().to_string(),
+                    access_key,
+                )
+            })
+            .collect();
+
+        Self {
+            access_keys: Arc::new(RwLock::new(access_keys)),
+        }
+    }
+
+    /// Get the access key with the provided `access_key_id`
+    pub fn get(&self, access_key_id: &str) -> Option<AccessKey> {
+        self.access_keys
+            .read()
+            .expect("access key store lock poisoned")
+            .get(access_key_id)
+            .cloned()
+    }
+
+    /// Add an access key to the store, expired temporary credentials are
+    /// removed from the store while adding the new key
+    pub fn insert(&self, access_key: AccessKey) {
+        let now = SystemTime::now();
+        let mut access_keys = self
+            .access_keys
+            .write()
+            .expect("access key store lock poisoned");
+
+        access_keys.retain(|_, access_key| {
+            access_key
+                .credentials
+                .expiry()
+                .is_none_or(|expiry| expiry > now)
+        });
+
+        access_keys.insert(
+            access_key.credentials.access_key_id().to_string(),
+            access_key,
+        );
+    }
+}
+
 /// Middleware provider layer
 #[derive(Clone)]
 pub struct AwsSigV4AuthLayer {
-    credentials: Credentials,
+    access_keys: AccessKeyStore,
+    debug_signatures: bool,
+    replay_cache: Option<ReplayCache>,
+    metrics: Metrics,
 }
 
 impl AwsSigV4AuthLayer {
-    /// Create a new AWS SigV4 layer using the provided credentials
-    pub fn new(credentials: Credentials) -> Self {
-        Self { credentials }
+    /// Create a new AWS SigV4 layer using the provided credentials, requests are
+    /// made as the root principal of the account in the `arn` config
+    pub fn new(credentials: Credentials, arn: &ArnConfig) -> Self {
+        Self::from_access_keys([AccessKey::new(credentials, arn)])
+    }
+
+    /// Create a new AWS SigV4 layer accepting requests signed by any of the
+    /// provided `access_keys`
+    pub fn from_access_keys(access_keys: impl IntoIterator<Item = AccessKey>) -> Self {
+        Self::from_store(AccessKeyStore::new(access_keys))
+    }
+
+    /// Create a new AWS SigV4 layer accepting requests signed by any of the
+    /// access keys within the `access_keys` store
+    pub fn from_store(access_keys: AccessKeyStore) -> Self {
+        Self {
+            access_keys,
+            debug_signatures: false,
+            replay_cache: None,
+            metrics: Metrics::default(),
+        }
+    }
+
+    /// Include the canonical request and string to sign the server expected in the
+    /// error returned for requests with a signature that doesn't match
+    pub fn with_debug_signatures(mut self, debug_signatures: bool) -> Self {
+        self.debug_signatures = debug_signatures;
+        self
+    }
+
+    /// Reject exact replays of requests to targets that change state by remembering
+    /// up to `cache_size` recently used signatures, replay protection is disabled
+    /// when no `cache_size` is provided
+    pub fn with_replay_protection(mut self, cache_size: Option<usize>) -> Self {
+        self.replay_cache = cache_size.map(ReplayCache::new);
+        self
+    }
+
+    /// Record the reason requests fail authentication to the `metrics`
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Get the store of access keys accepted by the layer
+    pub fn access_keys(&self) -> AccessKeyStore {
+        self.access_keys.clone()
     }
 }
 
@@ -46,7 +231,10 @@ impl<S> Layer<S> for AwsSigV4AuthLayer {
     fn layer(&self, inner: S) -> Self::Service {
         AwsSigV4AuthMiddleware {
             inner,
-            credentials: self.credentials.clone(),
+            access_keys: self.access_keys.clone(),
+            debug_signatures: self.debug_signatures,
+            replay_cache: self.replay_cache.clone(),
+            metrics: self.metrics.clone(),
         }
     }
 }
@@ -55,7 +243,10 @@ impl<S> Layer<S> for AwsSigV4AuthLayer {
 #[derive(Clone)]
 pub struct AwsSigV4AuthMiddleware<S> {
     inner: S,
-    credentials: Credentials,
+    access_keys: AccessKeyStore,
+    debug_signatures: bool,
+    replay_cache: Option<ReplayCache>,
+    metrics: Metrics,
 }
 
 impl<S> Service<Request<Body>> for AwsSigV4AuthMiddleware<S>
@@ -76,188 +267,238 @@ where
 
     fn call(&mut self, req: Request<Body>) -> Self::Future {
         let mut inner = self.inner.clone();
-        let credential = self.credentials.clone();
+        let access_keys = self.access_keys.clone();
+        let debug_signatures = self.debug_signatures;
+        let replay_cache = self.replay_cache.clone();
+        let metrics = self.metrics.clone();
 
         // Swap to ensure we get the service that was ready and not the cloned one
         swap(&mut inner, &mut self.inner);
 
         Box::pin(async move {
-            let (parts, body) = req.into_parts();
-
-            let authorization = match parts.headers.get(AUTHORIZATION) {
-                Some(value) => match value.to_str() {
-                    Ok(value) => value,
-                    // Invalid auth header
-                    Err(_) => {
-                        return Ok(InvalidRequestException.into_error_response());
-                    }
-                },
-                None => {
-                    // Unauthorized missing header
-                    return Ok(MissingAuthenticationToken.into_error_response());
-                }
+            // Record the reason for rejecting the request and create the error response
+            let reject = |reason: &'static str, error: AwsError| {
+                metrics.record_auth_failure(reason);
+                Ok::<_, S::Error>(error.into_error_response())
             };
 
-            // Extract the AWS specific date header
-            let amz_date = match parts.headers.get("x-amz-date") {
-                Some(value) => {
-                    let value = match value.to_str() {
-                        Ok(value) => value,
-                        Err(_) => {
-                            // Date header is invalid
-                            return Ok(InvalidRequestException.into_error_response());
-                        }
-                    };
+            let (mut parts, body) = req.into_parts();
 
-                    let value = match parse_amz_date(value) {
-                        Ok(value) => value,
-                        Err(_) => {
-                            // Date header is invalid
-                            return Ok(InvalidRequestException.into_error_response());
-                        }
-                    };
+            // Decoded query parameters, only parsed for requests that aren't
+            // signed using the Authorization header
+            let query_params: Vec<(String, String)>;
 
-                    Some(value)
-                }
-                None => None,
+            let signature = if parts.headers.contains_key(AUTHORIZATION) {
+                header_signature(&parts.headers)
+            } else {
+                // Query strings that can't be decoded are treated as missing
+                // the authentication parameters
+                query_params = parts
+                    .uri
+                    .query()
+                    .and_then(|query| serde_urlencoded::from_str(query).ok())
+                    .unwrap_or_default();
+
+                query_signature(&query_params)
             };
 
-            // Extract the generic Date header
-            let http_date = match parts.headers.get("date") {
-                Some(value) => {
-                    let value = match value.to_str() {
-                        Ok(value) => value,
-                        Err(_) => {
-                            // Date header is invalid
-                            return Ok(InvalidRequestException.into_error_response());
-                        }
-                    };
+            let signature = match signature {
+                Ok(value) => value,
+                Err(error) => return reject(auth_failure_reason(&error), error),
+            };
 
-                    let value = match parse_http_date(value) {
-                        Ok(value) => value,
-                        Err(_) => {
-                            // Date header is invalid
-                            return Ok(InvalidRequestException.into_error_response());
-                        }
-                    };
+            if let Err(message) = validate_signature_date(&signature, Utc::now()) {
+                return reject(
+                    "invalid_signature_date",
+                    signature_error(&parts.headers, message),
+                );
+            }
 
-                    Some(value)
-                }
-                None => None,
-            };
+            let auth = &signature.auth;
 
-            let date = match amz_date.or(http_date) {
-                Some(value) => value,
-                None => {
-                    // No date present on the request
-                    return Ok(InvalidRequestException.into_error_response());
-                }
-            };
+            // Missing the aws4_request portion of the credential
+            if auth.signing_scope.aws4_request != "aws4_request" {
+                return reject(
+                    "incomplete_signature",
+                    IncompleteSignature(format!(
+                        "Credential should be scoped with a valid terminator: 'aws4_request', not '{}'.",
+                        auth.signing_scope.aws4_request
+                    ))
+                    .into(),
+                );
+            }
 
-            let now = Utc::now();
-            let time_diff_now = now.timestamp().saturating_sub(date.timestamp()).abs();
-            if time_diff_now > 60 * 5 {
-                // Request date is not within the expected 5 minute tolerance window
-                // of the server time
-                return Ok(InvalidRequestException.into_error_response());
+            // Missing the regions of a SigV4A signature
+            if auth.algorithm == SigningAlgorithm::EcdsaP256Sha256 && signature.region_set.is_none()
+            {
+                return reject(
+                    "incomplete_signature",
+                    IncompleteSignature(
+                        "SigV4A signatures require the 'X-Amz-Region-Set' header or query-string \
+                        parameter."
+                            .to_string(),
+                    )
+                    .into(),
+                );
             }
 
-            let auth = match parse_auth_header(authorization) {
-                Ok(value) => value,
-                Err(_) => {
-                    return Ok(IncompleteSignature.into_error_response());
+            // The region set decides the region the request runs against so it
+            // must be signed, presigned requests always sign their query string
+            if auth.algorithm == SigningAlgorithm::EcdsaP256Sha256
+                && signature.expires_in.is_none()
+                && !auth.signed_headers.contains(&REGION_SET_HEADER)
+            {
+                return reject(
+                    "incomplete_signature",
+                    IncompleteSignature(format!(
+                        "SigV4A signatures must include the '{REGION_SET_HEADER}' header in the \
+                        signed headers. SignedHeaders={}",
+                        auth.signed_headers.join(";")
+                    ))
+                    .into(),
+                );
+            }
+
+            let access_key = match access_keys.get(auth.signing_scope.access_key_id) {
+                Some(value) => value,
+                None => {
+                    // Invalid access key
+                    return reject("unknown_access_key", InvalidClientTokenId.into());
                 }
             };
 
-            // Missing the aws4_request portion of the credential
-            if auth.signing_scope.aws4_request != "aws4_request" {
-                return Ok(IncompleteSignature.into_error_response());
+            // Temporary credentials must provide their session token, long-term
+            // credentials must not provide one
+            if access_key.credentials.session_token() != signature.session_token {
+                return reject("invalid_session_token", UnrecognizedClientException.into());
             }
 
-            if auth.signing_scope.access_key_id != credential.access_key_id() {
-                // Invalid access key
-                return Ok(InvalidClientTokenId.into_error_response());
+            if access_key
+                .credentials
+                .expiry()
+                .is_some_and(|expiry| expiry <= SystemTime::now())
+            {
+                // Temporary credentials have expired
+                return reject("expired_token", ExpiredTokenException.into());
             }
 
             let body = match body.collect().await {
                 Ok(value) => value.to_bytes(),
                 Err(_) => {
                     // Failed to ready body
-                    return Ok(InvalidRequestException.into_error_response());
+                    return reject("invalid_request", InvalidRequestException.into());
                 }
             };
 
             // Convert request date into a [SystemTime] timestamp for AWS-SigV4
-            let time = match chrono_to_system_time(date) {
+            let time = match chrono_to_system_time(signature.date) {
                 Some(value) => value,
                 None => {
-                    return Ok(InvalidRequestException.into_error_response());
+                    return reject("invalid_request", InvalidRequestException.into());
                 }
             };
 
-            // Setup the signing settings
-            let identity = credential.into();
-            let signing_settings = SigningSettings::default();
-            let signing_params = match SigningParams::builder()
-                .identity(&identity)
-                .region(auth.signing_scope.region)
-                .name(auth.signing_scope.service)
-                .time(time)
-                .settings(signing_settings)
-                .build()
-            {
-                Ok(value) => value.into(),
-                Err(_error) => {
-                    return Ok(InternalServiceError.into_error_response());
-                }
+            let AccessKey {
+                credentials,
+                principal_arn,
+                policy,
+            } = access_key;
+
+            let access_key_id = credentials.access_key_id().to_string();
+            let expiry = credentials.expiry();
+
+            let mut unsigned_payload = parts
+                .headers
+                .get("x-amz-content-sha256")
+                .is_some_and(|value| value == UNSIGNED_PAYLOAD);
+
+            let mut is_valid_signature = match verify_signature(
+                &parts,
+                &signature,
+                &credentials,
+                time,
+                &body,
+                unsigned_payload,
+            ) {
+                Ok(value) => value,
+                Err(error) => return reject(auth_failure_reason(&error), error),
             };
 
-            // Collect request headers that were included in the signed request
-            let headers =
-                match parts
+            // Presigned URLs are commonly created before the body is known so
+            // they may be signed without the payload
+            if !is_valid_signature && !unsigned_payload && signature.expires_in.is_some() {
+                is_valid_signature =
+                    match verify_signature(&parts, &signature, &credentials, time, &body, true) {
+                        Ok(value) => value,
+                        Err(error) => return reject(auth_failure_reason(&error), error),
+                    };
+                unsigned_payload = is_valid_signature;
+            }
+
+            if !is_valid_signature {
+                // Verify failure, bad signature
+                let mut message = SIGNATURE_MISMATCH_MESSAGE.to_string();
+
+                if debug_signatures
+                    && let Ok(expected) =
+                        expected_signing(&parts, &signature, &body, unsigned_payload)
+                {
+                    message.push_str(&format!(
+                        "\n\nThe Canonical String for this request should have been\n'{}'\n\n\
+                        The String-to-Sign should have been\n'{}'\n",
+                        expected.canonical_request, expected.string_to_sign
+                    ));
+                }
+
+                return reject(
+                    "signature_mismatch",
+                    signature_error(&parts.headers, message),
+                );
+            }
+
+            // Reject exact replays of requests that change state
+            if let Some(replay_cache) = &replay_cache
+                && (parts
                     .headers
-                    .iter()
-                    .try_fold(Vec::new(), |mut headers, (name, value)| {
-                        let name = name.as_str();
-                        if auth.signed_headers.contains(&name) {
-                            let value = value.to_str()?;
-                            headers.push((name, value));
-                        }
-
-                        Ok::<_, ToStrError>(headers)
-                    }) {
-                    Ok(value) => value,
-                    Err(_error) => {
-                        return Ok(InvalidRequestException.into_error_response());
-                    }
-                };
-
-            // Create the signable request
-            let signable_request = match SignableRequest::new(
-                parts.method.as_str(),
-                parts.uri.to_string(),
-                headers.into_iter(),
-                SignableBody::Bytes(&body),
-            ) {
-                Ok(value) => value,
-                Err(_error) => {
-                    //
-                    return Ok(InvalidRequestException.into_error_response());
+                    .get("x-amz-target")
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(is_mutating_target)
+                    || is_mutating_admin_request(&parts.method, parts.uri.path()))
+            {
+                let replay_key =
+                    match replay_key(&parts, &signature, &access_key_id, &body, unsigned_payload) {
+                        Ok(value) => value,
+                        Err(error) => return reject(auth_failure_reason(&error), error),
+                    };
+
+                if !replay_cache.insert(&replay_key, signature_expires_at(&signature), Utc::now()) {
+                    return reject(
+                        "replayed_signature",
+                        signature_error(&parts.headers, SIGNATURE_REPLAYED_MESSAGE.to_string()),
+                    );
                 }
-            };
+            }
 
-            let (_signing_instructions, signature) = match sign(signable_request, &signing_params) {
-                Ok(value) => value.into_parts(),
-                Err(_error) => {
-                    //
-                    return Ok(InternalServiceError.into_error_response());
+            let region = match (auth.algorithm, signature.region_set) {
+                // SigV4A signatures only provide a region when signed for a single
+                // region, otherwise the default region is used
+                (SigningAlgorithm::EcdsaP256Sha256, Some(region_set))
+                    if !region_set.contains([',', '*']) =>
+                {
+                    region_set.to_string()
                 }
+                (SigningAlgorithm::EcdsaP256Sha256, _) => String::new(),
+                (SigningAlgorithm::HmacSha256, _) => auth.signing_scope.region.to_string(),
             };
 
-            if signature != auth.signature {
-                // Verify failure, bad signature
-                return Ok(SignatureDoesNotMatch.into_error_response());
-            }
+            // Provide the authenticated identity to the inner services
+            parts.extensions.insert(AuthenticatedIdentity {
+                access_key_id,
+                principal_arn,
+                policy,
+                region,
+                expiry,
+            });
 
             // Re-create the body since we consumed the previous one
             let body = Body::from(body);
@@ -268,3 +509,472 @@ where
         })
     }
 }
+
+/// Signature details of a request signed using either the Authorization
+/// header or the query string (presigned URL)
+struct RequestSignature<'a> {
+    auth: AwsSigV4Auth<'a>,
+    /// Date the request was signed
+    date: DateTime<Utc>,
+    /// Session token provided for temporary credentials
+    session_token: Option<&'a str>,
+    /// Time the signature is valid for after the `date`, only present
+    /// for presigned requests
+    expires_in: Option<Duration>,
+    /// Regions a SigV4A signature is valid for
+    region_set: Option<&'a str>,
+}
+
+/// Extract the signature of a request signed using the Authorization header
+fn header_signature(headers: &HeaderMap) -> Result<RequestSignature<'_>, AwsError> {
+    let authorization = match headers.get(AUTHORIZATION) {
+        Some(value) => match value.to_str() {
+            Ok(value) => value,
+            // Invalid auth header
+            Err(_) => {
+                return Err(IncompleteSignature(
+                    "Authorization header contains characters that are not visible ASCII."
+                        .to_string(),
+                )
+                .into());
+            }
+        },
+        None => {
+            // Unauthorized missing header
+            return Err(MissingAuthenticationToken.into());
+        }
+    };
+
+    // Extract the AWS specific date header
+    let amz_date = match headers.get("x-amz-date") {
+        Some(value) => {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            match parse_amz_date(&value) {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    // Date header is invalid
+                    return Err(IncompleteSignature(invalid_amz_date_message(&value)).into());
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Extract the generic Date header
+    let http_date = match headers.get("date") {
+        Some(value) => {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            match parse_http_date(&value) {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    // Date header is invalid
+                    return Err(IncompleteSignature(format!(
+                        "Date header must be an HTTP date. Got '{value}'."
+                    ))
+                    .into());
+                }
+            }
+        }
+        None => None,
+    };
+
+    let date = match amz_date.or(http_date) {
+        Some(value) => value,
+        None => {
+            // No date present on the request
+            return Err(IncompleteSignature(format!(
+                "Authorization header requires existence of either a 'X-Amz-Date' or a 'Date' \
+                header. Authorization={authorization}"
+            ))
+            .into());
+        }
+    };
+
+    let auth = match parse_auth_header(authorization) {
+        Ok(value) => value,
+        Err(error) => {
+            return Err(
+                IncompleteSignature(format!("{error} Authorization={authorization}")).into(),
+            );
+        }
+    };
+
+    let session_token = match headers.get(SECURITY_TOKEN_HEADER) {
+        Some(value) => match value.to_str() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                // Session token header is invalid
+                return Err(UnrecognizedClientException.into());
+            }
+        },
+        None => None,
+    };
+
+    let region_set = match headers.get(REGION_SET_HEADER) {
+        Some(value) => match value.to_str() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                // Region set header is invalid
+                return Err(IncompleteSignature(
+                    "X-Amz-Region-Set header contains characters that are not visible ASCII."
+                        .to_string(),
+                )
+                .into());
+            }
+        },
+        None => None,
+    };
+
+    Ok(RequestSignature {
+        auth,
+        date,
+        session_token,
+        expires_in: None,
+        region_set,
+    })
+}
+
+/// Extract the signature of a request presigned using the query string from
+/// the decoded query `params`
+fn query_signature(params: &[(String, String)]) -> Result<RequestSignature<'_>, AwsError> {
+    if !has_auth_query(params) {
+        // Unauthorized missing header and query string authentication
+        return Err(MissingAuthenticationToken.into());
+    }
+
+    let query = match parse_auth_query(params) {
+        Ok(value) => value,
+        Err(error) => return Err(IncompleteSignature(error.to_string()).into()),
+    };
+
+    let date = match parse_amz_date(query.amz_date) {
+        Ok(value) => value,
+        Err(_) => {
+            // Date parameter is invalid
+            return Err(IncompleteSignature(invalid_amz_date_message(query.amz_date)).into());
+        }
+    };
+
+    let expires_in = match query.expires.parse::<u64>() {
+        Ok(value) if (1..=MAX_PRESIGNED_EXPIRES_SECONDS).contains(&value) => {
+            Duration::from_secs(value)
+        }
+        _ => {
+            // Expiry must be within the range allowed by AWS
+            return Err(IncompleteSignature(format!(
+                "X-Amz-Expires must be a number of seconds between 1 and \
+                {MAX_PRESIGNED_EXPIRES_SECONDS} (7 days). Got '{}'.",
+                query.expires
+            ))
+            .into());
+        }
+    };
+
+    Ok(RequestSignature {
+        auth: query.auth,
+        date,
+        session_token: query.security_token,
+        expires_in: Some(expires_in),
+        region_set: query.region_set,
+    })
+}
+
+/// Message for a request date that isn't in the X-Amz-Date format
+fn invalid_amz_date_message(value: &str) -> String {
+    format!(
+        "Date must be in ISO-8601 'basic format'. Got '{value}'. \
+        See http://en.wikipedia.org/wiki/ISO_8601"
+    )
+}
+
+/// Check the `signature` date is within the time the signature is valid for at
+/// the server time `now`, provides the reason the signature was rejected otherwise
+fn validate_signature_date(
+    signature: &RequestSignature<'_>,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let max_skew = TimeDelta::seconds(MAX_CLOCK_SKEW_SECONDS);
+    let date = signature.date;
+
+    // Request date must not be further in the future than the allowed skew
+    let latest = now + max_skew;
+    if date > latest {
+        return Err(format!(
+            "Signature not yet current: {} is still later than {} ({} + 5 min.)",
+            format_amz_date(date),
+            format_amz_date(latest),
+            format_amz_date(now)
+        ));
+    }
+
+    match signature.expires_in {
+        // Presigned requests are valid from when they were signed until
+        // they expire
+        Some(expires_in) => {
+            let expires_at = date + TimeDelta::seconds(expires_in.as_secs() as i64);
+            if expires_at < now {
+                return Err(format!(
+                    "Signature expired: {} is now earlier than {}",
+                    format_amz_date(expires_at),
+                    format_amz_date(now)
+                ));
+            }
+        }
+        // Request date must be within the expected 5 minute tolerance window
+        // of the server time
+        None => {
+            let earliest = now - max_skew;
+            if date < earliest {
+                return Err(format!(
+                    "Signature expired: {} is now earlier than {} ({} - 5 min.)",
+                    format_amz_date(date),
+                    format_amz_date(earliest),
+                    format_amz_date(now)
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the time the `signature` stops being accepted, header signed requests are
+/// accepted until the request date is outside the allowed clock skew
+fn signature_expires_at(signature: &RequestSignature<'_>) -> DateTime<Utc> {
+    let valid_for = match signature.expires_in {
+        Some(expires_in) => TimeDelta::seconds(expires_in.as_secs() as i64),
+        None => TimeDelta::seconds(MAX_CLOCK_SKEW_SECONDS),
+    };
+
+    signature.date + valid_for
+}
+
+/// Create the key identifying a signed request in the replay cache, the key is the
+/// SHA256 hash of the `access_key_id` and the string to sign rather than the
+/// signature as the same request can have many valid signatures (SigV4A
+/// signatures can be re-encoded or negated and hex is case insensitive)
+fn replay_key(
+    parts: &Parts,
+    signature: &RequestSignature<'_>,
+    access_key_id: &str,
+    body: &[u8],
+    unsigned_payload: bool,
+) -> Result<String, AwsError> {
+    let expected = expected_signing(parts, signature, body, unsigned_payload)?;
+    let key = format!("{access_key_id}\n{}", expected.string_to_sign);
+    Ok(hex_encode(digest(&SHA256, key.as_bytes()).as_ref()))
+}
+
+/// Create the error for a rejected signature, JSON protocol requests (which
+/// provide the X-Amz-Target header) use InvalidSignatureException while
+/// query protocol requests use SignatureDoesNotMatch
+fn signature_error(headers: &HeaderMap, message: String) -> AwsError {
+    if headers.contains_key("x-amz-target") {
+        InvalidSignatureException(message).into()
+    } else {
+        SignatureDoesNotMatch(message).into()
+    }
+}
+
+/// Reason recorded in the authentication failure metrics for an `error` rejecting
+/// the request
+fn auth_failure_reason(error: &AwsError) -> &'static str {
+    match error {
+        AwsError::MissingAuthenticationToken(_) => "missing_authentication",
+        AwsError::IncompleteSignature(_) => "incomplete_signature",
+        AwsError::UnrecognizedClientException(_) => "invalid_session_token",
+        AwsError::InvalidRequestException(_) => "invalid_request",
+        AwsError::SignatureDoesNotMatch(_) | AwsError::InvalidSignatureException(_) => {
+            "signature_mismatch"
+        }
+        _ => "internal_error",
+    }
+}
+
+/// Verify the `signature` of a request was created using the `credentials`, when
+/// `unsigned_payload` is true the signature is verified without the `body`
+fn verify_signature(
+    parts: &Parts,
+    signature: &RequestSignature<'_>,
+    credentials: &Credentials,
+    time: SystemTime,
+    body: &[u8],
+    unsigned_payload: bool,
+) -> Result<bool, AwsError> {
+    match signature.auth.algorithm {
+        SigningAlgorithm::HmacSha256 => {
+            verify_v4_signature(parts, signature, credentials, time, body, unsigned_payload)
+        }
+        SigningAlgorithm::EcdsaP256Sha256 => {
+            verify_v4a_signature(parts, signature, credentials, body, unsigned_payload)
+        }
+    }
+}
+
+/// Verify a SigV4 (AWS4-HMAC-SHA256) signature by signing the request again
+fn verify_v4_signature(
+    parts: &Parts,
+    signature: &RequestSignature<'_>,
+    credentials: &Credentials,
+    time: SystemTime,
+    body: &[u8],
+    unsigned_payload: bool,
+) -> Result<bool, AwsError> {
+    let auth = &signature.auth;
+
+    // Setup the signing settings, presigned requests are signed using
+    // the query string
+    let mut signing_settings = SigningSettings::default();
+    if let Some(expires_in) = signature.expires_in {
+        signing_settings.signature_location = SignatureLocation::QueryParams;
+        signing_settings.expires_in = Some(expires_in);
+    }
+
+    let identity = credentials.clone().into();
+    let signing_params = SigningParams::builder()
+        .identity(&identity)
+        .region(auth.signing_scope.region)
+        .name(auth.signing_scope.service)
+        .time(time)
+        .settings(signing_settings)
+        .build()
+        .map_err(|_| InternalServiceError)?
+        .into();
+
+    // The authentication parameters of presigned requests are added back
+    // by the signer so they are removed from the signed URI
+    let uri = match (signature.expires_in, parts.uri.query()) {
+        (Some(_), Some(query)) => {
+            let query = strip_auth_query(query);
+            if query.is_empty() {
+                parts.uri.path().to_string()
+            } else {
+                format!("{}?{query}", parts.uri.path())
+            }
+        }
+        _ => parts.uri.to_string(),
+    };
+
+    // Collect request headers that were included in the signed request
+    let headers = parts
+        .headers
+        .iter()
+        .try_fold(Vec::new(), |mut headers, (name, value)| {
+            let name = name.as_str();
+            if auth.signed_headers.contains(&name) {
+                let value = value
+                    .to_str()
+                    .map_err(|_| InvalidSignedHeader(name.to_string()))?;
+                headers.push((name, value));
+            }
+
+            Ok::<_, InvalidSignedHeader>(headers)
+        })
+        .map_err(|error| IncompleteSignature(error.to_string()))?;
+
+    let body = if unsigned_payload {
+        SignableBody::UnsignedPayload
+    } else {
+        SignableBody::Bytes(body)
+    };
+
+    // Create the signable request
+    let signable_request =
+        SignableRequest::new(parts.method.as_str(), uri, headers.into_iter(), body)
+            .map_err(|_| InvalidRequestException)?;
+
+    let (_signing_instructions, expected_signature) = sign(signable_request, &signing_params)
+        .map_err(|_| InternalServiceError)?
+        .into_parts();
+
+    Ok(expected_signature == auth.signature)
+}
+
+/// Verify a SigV4A (AWS4-ECDSA-P256-SHA256) signature against the public key
+/// derived from the `credentials`
+fn verify_v4a_signature(
+    parts: &Parts,
+    signature: &RequestSignature<'_>,
+    credentials: &Credentials,
+    body: &[u8],
+    unsigned_payload: bool,
+) -> Result<bool, AwsError> {
+    let expected = expected_signing(parts, signature, body, unsigned_payload)?;
+
+    let key_pair = derive_key_pair(credentials.access_key_id(), credentials.secret_access_key())
+        .map_err(|_| InternalServiceError)?;
+
+    verify_v4a(
+        &key_pair,
+        &expected.string_to_sign,
+        signature.auth.signature,
+    )
+    .map_err(|_| {
+        IncompleteSignature(format!(
+            "Signature '{}' is not a hex encoded ECDSA signature.",
+            signature.auth.signature
+        ))
+        .into()
+    })
+}
+
+/// Canonical request and string to sign the server expects a request
+/// signature to be created from
+struct ExpectedSigning {
+    canonical_request: String,
+    string_to_sign: String,
+}
+
+/// Create the canonical request and string to sign for the `signature` of a
+/// request, when `unsigned_payload` is true the `body` is not included
+fn expected_signing(
+    parts: &Parts,
+    signature: &RequestSignature<'_>,
+    body: &[u8],
+    unsigned_payload: bool,
+) -> Result<ExpectedSigning, AwsError> {
+    let auth = &signature.auth;
+
+    let payload_hash = if unsigned_payload {
+        UNSIGNED_PAYLOAD.to_string()
+    } else {
+        hex_encode(digest(&SHA256, body).as_ref())
+    };
+
+    let canonical_request = canonical_request(
+        parts.method.as_str(),
+        parts.uri.path(),
+        parts.uri.query(),
+        &parts.headers,
+        &auth.signed_headers,
+        &payload_hash,
+    )
+    .map_err(|error| IncompleteSignature(error.to_string()))?;
+
+    let date_yyyymmdd = signature.date.format("%Y%m%d");
+    let scope = match auth.algorithm {
+        SigningAlgorithm::HmacSha256 => format!(
+            "{date_yyyymmdd}/{}/{}/aws4_request",
+            auth.signing_scope.region, auth.signing_scope.service
+        ),
+        // SigV4A scopes don't include the region
+        SigningAlgorithm::EcdsaP256Sha256 => {
+            format!(
+                "{date_yyyymmdd}/{}/aws4_request",
+                auth.signing_scope.service
+            )
+        }
+    };
+
+    let string_to_sign = string_to_sign(
+        auth.algorithm,
+        &format_amz_date(signature.date),
+        &scope,
+        &canonical_request,
+    );
+
+    Ok(ExpectedSigning {
+        canonical_request,
+        string_to_sign,
+    })
+}
@@ -1 +1,2 @@
 pub mod aws_sig_v4;
+pub mod replay;
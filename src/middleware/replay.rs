@@ -0,0 +1,194 @@
+//! Optional replay protection for signed requests, requests to targets that change
+//! state are remembered until the request date leaves the window the signature is
+//! accepted within so exact replays can be rejected
+
+use axum::http::Method;
+use chrono::{DateTime, Utc};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+/// Default number of requests remembered by the replay cache
+pub const DEFAULT_REPLAY_CACHE_SIZE: usize = 10_000;
+
+/// Targets that change state, replays of requests to these targets are rejected
+const MUTATING_TARGETS: &[&str] = &[
+    "secretsmanager.CreateSecret",
+    "secretsmanager.PutSecretValue",
+    "secretsmanager.UpdateSecret",
+    "secretsmanager.UpdateSecretVersionStage",
+    "secretsmanager.DeleteSecret",
+    "secretsmanager.RestoreSecret",
+    "secretsmanager.TagResource",
+    "secretsmanager.UntagResource",
+    "secretsmanager.RotateSecret",
+    "secretsmanager.CancelRotateSecret",
+    "secretsmanager.PutResourcePolicy",
+    "secretsmanager.DeleteResourcePolicy",
+    "secretsmanager.ReplicateSecretToRegions",
+    "secretsmanager.RemoveRegionsFromReplication",
+    "secretsmanager.StopReplicationToReplica",
+    "TrentService.CreateKey",
+    "TrentService.EnableKey",
+    "TrentService.DisableKey",
+];
+
+/// Prefix of the paths of the administrative routes
+const ADMIN_PATH_PREFIX: &str = "/_loker/";
+
+/// Check if requests to the `target` change state
+pub fn is_mutating_target(target: &str) -> bool {
+    MUTATING_TARGETS.contains(&target)
+}
+
+/// Check if a request to the administrative route at `path` changes state, all
+/// administrative routes other than GET routes change state
+pub fn is_mutating_admin_request(method: &Method, path: &str) -> bool {
+    path.starts_with(ADMIN_PATH_PREFIX) && method != Method::GET
+}
+
+/// Requests remembered by the [ReplayCache]
+#[derive(Default)]
+struct ReplayEntries {
+    /// Time the signature of each request stops being accepted, keyed by the replay key
+    expires_at: HashMap<String, DateTime<Utc>>,
+    /// Replay keys ordered by the time their signature stops being accepted, soonest first
+    expiry: BinaryHeap<Reverse<(DateTime<Utc>, String)>>,
+}
+
+impl ReplayEntries {
+    /// Time the request expiring soonest stops being accepted
+    fn soonest_expiry(&self) -> Option<DateTime<Utc>> {
+        self.expiry
+            .peek()
+            .map(|Reverse((expires_at, _))| *expires_at)
+    }
+
+    /// Remove the request expiring soonest
+    fn pop_soonest(&mut self) {
+        if let Some(Reverse((_, key))) = self.expiry.pop() {
+            self.expires_at.remove(&key);
+        }
+    }
+}
+
+/// Bounded cache of recently signed requests, once the cache is full the requests
+/// closest to no longer being accepted are forgotten to make room for new ones
+#[derive(Clone)]
+pub struct ReplayCache {
+    /// Maximum number of requests remembered
+    capacity: usize,
+    entries: Arc<Mutex<ReplayEntries>>,
+}
+
+impl ReplayCache {
+    /// Create a cache remembering up to `capacity` requests
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Default::default(),
+        }
+    }
+
+    /// Record a request identified by the replay `key` whose signature is accepted
+    /// until `expires_at`, provides false when the request was already seen and is
+    /// still accepted at `now`
+    pub fn insert(&self, key: &str, expires_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let mut entries = self.entries.lock().expect("replay cache lock poisoned");
+
+        // Forget the requests that are no longer accepted
+        while entries
+            .soonest_expiry()
+            .is_some_and(|expires_at| expires_at <= now)
+        {
+            entries.pop_soonest();
+        }
+
+        // Remaining requests are all still accepted
+        if entries.expires_at.contains_key(key) {
+            return false;
+        }
+
+        while entries.expires_at.len() >= self.capacity {
+            entries.pop_soonest();
+        }
+
+        entries.expires_at.insert(key.to_string(), expires_at);
+        entries.expiry.push(Reverse((expires_at, key.to_string())));
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ReplayCache, is_mutating_target};
+    use chrono::{TimeDelta, Utc};
+
+    /// Tests that a signature is rejected when used again before it expires
+    #[test]
+    fn test_replay_rejected() {
+        let cache = ReplayCache::new(10);
+        let now = Utc::now();
+        let expires_at = now + TimeDelta::minutes(5);
+
+        assert!(cache.insert("a", expires_at, now));
+        assert!(!cache.insert("a", expires_at, now + TimeDelta::minutes(1)));
+        assert!(cache.insert("b", expires_at, now));
+    }
+
+    /// Tests that a signature can be used again once it has expired
+    #[test]
+    fn test_expired_signature_forgotten() {
+        let cache = ReplayCache::new(10);
+        let now = Utc::now();
+        let expires_at = now + TimeDelta::minutes(5);
+
+        assert!(cache.insert("a", expires_at, now));
+        assert!(cache.insert("a", expires_at, expires_at));
+    }
+
+    /// Tests that the signatures expiring soonest are forgotten once the cache is full
+    #[test]
+    fn test_capacity_bounded() {
+        let cache = ReplayCache::new(2);
+        let now = Utc::now();
+
+        assert!(cache.insert("a", now + TimeDelta::minutes(5), now));
+        assert!(cache.insert("b", now + TimeDelta::minutes(6), now));
+        assert!(cache.insert("c", now + TimeDelta::minutes(7), now));
+
+        assert!(!cache.insert("c", now + TimeDelta::minutes(7), now));
+        assert!(!cache.insert("b", now + TimeDelta::minutes(6), now));
+        assert!(cache.insert("a", now + TimeDelta::minutes(5), now));
+    }
+
+    /// Tests that a long lived presigned signature doesn't prevent expired signatures
+    /// behind it from being forgotten, or get forgotten before them
+    #[test]
+    fn test_expired_signatures_forgotten_before_presigned() {
+        let cache = ReplayCache::new(2);
+        let now = Utc::now();
+
+        assert!(cache.insert("presigned", now + TimeDelta::days(7), now));
+        assert!(cache.insert("a", now + TimeDelta::minutes(5), now));
+
+        // "a" has expired so makes room for "b" without forgetting the presigned request
+        let later = now + TimeDelta::minutes(10);
+        assert!(cache.insert("b", later + TimeDelta::minutes(5), later));
+
+        assert!(!cache.insert("presigned", now + TimeDelta::days(7), later));
+        assert!(!cache.insert("b", later + TimeDelta::minutes(5), later));
+    }
+
+    /// Tests that only targets that change state are protected
+    #[test]
+    fn test_is_mutating_target() {
+        assert!(is_mutating_target("secretsmanager.CreateSecret"));
+        assert!(is_mutating_target("TrentService.DisableKey"));
+        assert!(!is_mutating_target("secretsmanager.GetSecretValue"));
+        assert!(!is_mutating_target("TrentService.ListKeys"));
+    }
+}
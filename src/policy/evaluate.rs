@@ -0,0 +1,240 @@
+use super::{OneOrMany, PolicyDocument, PolicyEffect, PolicyStatement, Principal};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Outcome of evaluating a policy against a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// A statement explicitly allowed the request
+    Allow,
+    /// A statement explicitly denied the request
+    Deny,
+    /// No statement applied to the request
+    NotApplicable,
+}
+
+/// Details about the request being authorized
+pub struct RequestContext {
+    /// ARN of the principal making the request
+    pub principal_arn: String,
+    /// Action being performed (i.e secretsmanager:GetSecretValue)
+    pub action: String,
+    /// ARN of the resource the action is performed against or "*"
+    pub resource: String,
+    /// Condition key values available to the policy, keys are lowercase
+    pub condition_values: HashMap<String, Vec<String>>,
+}
+
+impl RequestContext {
+    /// Get the values for a condition `key`, condition keys are case insensitive
+    fn condition_value(&self, key: &str) -> Option<&Vec<String>> {
+        self.condition_values.get(&key.to_ascii_lowercase())
+    }
+}
+
+impl PolicyDocument {
+    /// Evaluate the policy against the request, explicit denies take priority
+    /// over any allows
+    pub fn evaluate(&self, request: &RequestContext) -> Decision {
+        let mut decision = Decision::NotApplicable;
+
+        for statement in self.statement.iter() {
+            if !statement.applies_to(request) {
+                continue;
+            }
+
+            match statement.effect {
+                PolicyEffect::Deny => return Decision::Deny,
+                PolicyEffect::Allow => decision = Decision::Allow,
+            }
+        }
+
+        decision
+    }
+}
+
+impl PolicyStatement {
+    /// Check whether the statement applies to the request
+    fn applies_to(&self, request: &RequestContext) -> bool {
+        // Identity policies have no principal and always apply to the principal
+        let principal_matches = match (&self.principal, &self.not_principal) {
+            (Some(principal), _) => principal.matches(&request.principal_arn),
+            (None, Some(not_principal)) => !not_principal.matches(&request.principal_arn),
+            (None, None) => true,
+        };
+
+        let action_matches = match (&self.action, &self.not_action) {
+            (Some(action), _) => any_matches(action, &request.action, true),
+            (None, Some(not_action)) => !any_matches(not_action, &request.action, true),
+            (None, None) => false,
+        };
+
+        let resource_matches = match (&self.resource, &self.not_resource) {
+            (Some(resource), _) => any_matches(resource, &request.resource, false),
+            (None, Some(not_resource)) => !any_matches(not_resource, &request.resource, false),
+            (None, None) => false,
+        };
+
+        principal_matches && action_matches && resource_matches && self.conditions_match(request)
+    }
+
+    /// Check that every condition in the statement is satisfied
+    fn conditions_match(&self, request: &RequestContext) -> bool {
+        let conditions = match &self.condition {
+            Some(value) => value,
+            None => return true,
+        };
+
+        conditions.iter().all(|(operator, keys)| {
+            keys.iter().all(|(key, expected)| {
+                condition_matches(operator, request.condition_value(key), expected)
+            })
+        })
+    }
+}
+
+impl Principal {
+    /// Check whether the principal matches the `principal_arn`
+    fn matches(&self, principal_arn: &str) -> bool {
+        match self {
+            Principal::Any(value) => value == "*",
+            Principal::Typed(principals) => principals.get("AWS").is_some_and(|values| {
+                values
+                    .iter()
+                    .any(|value| principal_matches(value, principal_arn))
+            }),
+        }
+    }
+}
+
+/// Check whether a single principal `value` from a policy matches the `principal_arn`,
+/// account IDs and account root ARNs match any principal within the account
+fn principal_matches(value: &str, principal_arn: &str) -> bool {
+    if value == "*" || value == principal_arn {
+        return true;
+    }
+
+    let account_id = match principal_arn.split(':').nth(4) {
+        Some(value) => value,
+        None => return false,
+    };
+
+    if value == account_id {
+        return true;
+    }
+
+    value
+        .strip_prefix("arn:")
+        .and_then(|value| value.split_once(":iam::"))
+        .is_some_and(|(_, value)| value == format!("{account_id}:root"))
+}
+
+/// Check if any of the `patterns` match the `value`
+fn any_matches(patterns: &OneOrMany<String>, value: &str, ignore_case: bool) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| wildcard_matches(pattern, value, ignore_case))
+}
+
+/// Match `value` against a `pattern` that can contain the "*" (any sequence) and
+/// "?" (any single character) wildcards
+pub fn wildcard_matches(pattern: &str, value: &str, ignore_case: bool) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    let chars_eq = |a: char, b: char| {
+        if ignore_case {
+            a.eq_ignore_ascii_case(&b)
+        } else {
+            a == b
+        }
+    };
+
+    let (mut p, mut v) = (0, 0);
+    // Position of the last "*" in the pattern and the value position it matched from
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || chars_eq(pattern[p], value[v])) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last "*" consume one more character
+            p = star + 1;
+            v = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    // Remaining pattern must only be "*"
+    pattern[p..].iter().all(|char| *char == '*')
+}
+
+/// Check a single condition `operator` against the `actual` request values
+fn condition_matches(
+    operator: &str,
+    actual: Option<&Vec<String>>,
+    expected: &OneOrMany<Value>,
+) -> bool {
+    let expected: Vec<String> = expected
+        .iter()
+        .map(|value| match value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        })
+        .collect();
+
+    // Null checks for the presence of the key
+    if operator == "Null" {
+        let is_null = actual.is_none_or(|values| values.is_empty());
+        return expected
+            .iter()
+            .any(|expected| expected.eq_ignore_ascii_case(&is_null.to_string()));
+    }
+
+    let (operator, if_exists) = match operator.strip_suffix("IfExists") {
+        Some(operator) => (operator, true),
+        None => (operator, false),
+    };
+
+    let negated = operator.contains("Not");
+
+    let actual = match actual {
+        Some(values) if !values.is_empty() => values,
+        // Missing keys only satisfy negated and "IfExists" operators
+        _ => return if_exists || negated,
+    };
+
+    let compare = |actual: &str, expected: &str| -> Option<bool> {
+        Some(match operator {
+            "StringEquals" | "StringNotEquals" | "ArnEquals" | "ArnNotEquals" => actual == expected,
+            "StringEqualsIgnoreCase" | "StringNotEqualsIgnoreCase" => {
+                actual.eq_ignore_ascii_case(expected)
+            }
+            "StringLike" | "StringNotLike" | "ArnLike" | "ArnNotLike" => {
+                wildcard_matches(expected, actual, false)
+            }
+            "Bool" => actual.eq_ignore_ascii_case(expected),
+            // Unsupported operators are rejected when the policy is parsed
+            _ => return None,
+        })
+    };
+
+    let mut matched = false;
+    for actual in actual {
+        for expected in &expected {
+            match compare(actual, expected) {
+                Some(true) => matched = true,
+                Some(false) => {}
+                None => return false,
+            }
+        }
+    }
+
+    if negated { !matched } else { matched }
+}
@@ -0,0 +1,482 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+pub mod evaluate;
+
+/// Supported policy language versions
+const POLICY_VERSIONS: [&str; 2] = ["2012-10-17", "2008-10-17"];
+
+/// Keys allowed at the top level of a policy document
+const DOCUMENT_KEYS: [&str; 3] = ["Version", "Id", "Statement"];
+
+/// Keys allowed within a policy statement
+const STATEMENT_KEYS: [&str; 9] = [
+    "Sid",
+    "Effect",
+    "Principal",
+    "NotPrincipal",
+    "Action",
+    "NotAction",
+    "Resource",
+    "NotResource",
+    "Condition",
+];
+
+/// Keys allowed within a principal block
+const PRINCIPAL_KEYS: [&str; 4] = ["AWS", "Service", "Federated", "CanonicalUser"];
+
+/// Condition operators supported when evaluating policies, all except "Null" can
+/// also be used with the "IfExists" suffix
+const CONDITION_OPERATORS: [&str; 12] = [
+    "StringEquals",
+    "StringNotEquals",
+    "StringEqualsIgnoreCase",
+    "StringNotEqualsIgnoreCase",
+    "StringLike",
+    "StringNotLike",
+    "ArnEquals",
+    "ArnNotEquals",
+    "ArnLike",
+    "ArnNotLike",
+    "Bool",
+    "Null",
+];
+
+/// Resource based policy document attached to a secret
+///
+/// https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_grammar.html
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PolicyDocument {
+    #[serde(rename = "Version")]
+    pub version: Option<String>,
+    #[serde(rename = "Id")]
+    pub id: Option<String>,
+    #[serde(rename = "Statement")]
+    pub statement: OneOrMany<PolicyStatement>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PolicyStatement {
+    #[serde(rename = "Sid")]
+    pub sid: Option<String>,
+    #[serde(rename = "Effect")]
+    pub effect: PolicyEffect,
+    #[serde(rename = "Principal")]
+    pub principal: Option<Principal>,
+    #[serde(rename = "NotPrincipal")]
+    pub not_principal: Option<Principal>,
+    #[serde(rename = "Action")]
+    pub action: Option<OneOrMany<String>>,
+    #[serde(rename = "NotAction")]
+    pub not_action: Option<OneOrMany<String>>,
+    #[serde(rename = "Resource")]
+    pub resource: Option<OneOrMany<String>>,
+    #[serde(rename = "NotResource")]
+    pub not_resource: Option<OneOrMany<String>>,
+    /// Condition operator -> condition key -> condition values
+    #[serde(rename = "Condition")]
+    pub condition: Option<HashMap<String, HashMap<String, OneOrMany<Value>>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PolicyEffect {
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Principal {
+    /// Wildcard "*" principal
+    Any(String),
+    /// Principal type (i.e "AWS") to the principal identifiers
+    Typed(HashMap<String, OneOrMany<String>>),
+}
+
+impl Principal {
+    /// Whether the principal grants access to anyone
+    pub fn is_wildcard(&self) -> bool {
+        match self {
+            Principal::Any(value) => value == "*",
+            Principal::Typed(principals) => principals
+                .values()
+                .any(|values| values.iter().any(|value| value == "*")),
+        }
+    }
+}
+
+/// Policy values that can be a single value or a list of values
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        match self {
+            OneOrMany::One(value) => std::slice::from_ref(value).iter(),
+            OneOrMany::Many(values) => values.iter(),
+        }
+    }
+}
+
+/// Kind of policy document, determines which elements are required
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyKind {
+    /// Policy attached to a secret, statements must specify a Principal
+    Resource,
+    /// Policy attached to an identity, statements must not specify a Principal
+    Identity,
+}
+
+/// Problem found when validating a policy document
+#[derive(Debug)]
+pub struct PolicyValidationError {
+    /// Name of the check that failed
+    pub check_name: &'static str,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl PolicyValidationError {
+    fn new(check_name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            check_name,
+            message: message.into(),
+        }
+    }
+}
+
+impl PolicyDocument {
+    /// Parse and validate a policy document of the provided `kind` from its JSON `value`
+    pub fn parse(
+        value: &str,
+        kind: PolicyKind,
+    ) -> Result<PolicyDocument, Vec<PolicyValidationError>> {
+        let value: Value = serde_json::from_str(value).map_err(|error| {
+            vec![PolicyValidationError::new(
+                "JSON_SYNTAX",
+                format!("Policy is not valid JSON: {error}"),
+            )]
+        })?;
+
+        let errors = validate_document(&value, kind);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        serde_json::from_value(value).map_err(|error| {
+            vec![PolicyValidationError::new(
+                "POLICY_SYNTAX",
+                format!("Policy does not match the policy grammar: {error}"),
+            )]
+        })
+    }
+
+    /// Whether the policy grants access to any principal
+    pub fn is_public(&self) -> bool {
+        self.statement.iter().any(|statement| {
+            statement.effect == PolicyEffect::Allow
+                && statement.condition.is_none()
+                && statement
+                    .principal
+                    .as_ref()
+                    .is_some_and(Principal::is_wildcard)
+        })
+    }
+}
+
+/// Perform structural checks against a policy document
+fn validate_document(value: &Value, kind: PolicyKind) -> Vec<PolicyValidationError> {
+    let mut errors = Vec::new();
+
+    let document = match value.as_object() {
+        Some(value) => value,
+        None => {
+            errors.push(PolicyValidationError::new(
+                "POLICY_SYNTAX",
+                "Policy must be a JSON object",
+            ));
+            return errors;
+        }
+    };
+
+    check_unknown_keys(document, &DOCUMENT_KEYS, "policy", &mut errors);
+
+    match document.get("Version") {
+        Some(Value::String(version)) if POLICY_VERSIONS.contains(&version.as_str()) => {}
+        Some(_) => errors.push(PolicyValidationError::new(
+            "VERSION",
+            format!("Version must be one of: {}", POLICY_VERSIONS.join(", ")),
+        )),
+        None => {}
+    }
+
+    if document.get("Id").is_some_and(|id| !id.is_string()) {
+        errors.push(PolicyValidationError::new(
+            "POLICY_SYNTAX",
+            "Id must be a string",
+        ));
+    }
+
+    let statements: Vec<&Value> = match document.get("Statement") {
+        Some(Value::Array(statements)) if !statements.is_empty() => statements.iter().collect(),
+        Some(statement @ Value::Object(_)) => vec![statement],
+        Some(_) => {
+            errors.push(PolicyValidationError::new(
+                "STATEMENT",
+                "Statement must be an object or a non-empty list of objects",
+            ));
+            return errors;
+        }
+        None => {
+            errors.push(PolicyValidationError::new(
+                "STATEMENT",
+                "Policy is missing the required Statement",
+            ));
+            return errors;
+        }
+    };
+
+    for (index, statement) in statements.into_iter().enumerate() {
+        match statement.as_object() {
+            Some(statement) => validate_statement(index, statement, kind, &mut errors),
+            None => errors.push(PolicyValidationError::new(
+                "STATEMENT",
+                format!("Statement {index} must be an object"),
+            )),
+        }
+    }
+
+    errors
+}
+
+/// Perform structural checks against a single policy statement
+fn validate_statement(
+    index: usize,
+    statement: &Map<String, Value>,
+    kind: PolicyKind,
+    errors: &mut Vec<PolicyValidationError>,
+) {
+    let location = format!("statement {index}");
+
+    check_unknown_keys(statement, &STATEMENT_KEYS, &location, errors);
+
+    if statement.get("Sid").is_some_and(|sid| !sid.is_string()) {
+        errors.push(PolicyValidationError::new(
+            "STATEMENT",
+            format!("Sid in {location} must be a string"),
+        ));
+    }
+
+    match statement.get("Effect") {
+        Some(Value::String(effect)) if effect == "Allow" || effect == "Deny" => {}
+        Some(_) => errors.push(PolicyValidationError::new(
+            "EFFECT",
+            format!("Effect in {location} must be either Allow or Deny"),
+        )),
+        None => errors.push(PolicyValidationError::new(
+            "EFFECT",
+            format!("Missing Effect in {location}"),
+        )),
+    }
+
+    match exclusive_key(statement, "Principal", "NotPrincipal") {
+        Ok(Some(_)) if kind == PolicyKind::Identity => errors.push(PolicyValidationError::new(
+            "PRINCIPAL",
+            format!("Principal in {location} is not allowed in identity policies"),
+        )),
+        Ok(None) if kind == PolicyKind::Identity => {}
+        Ok(Some(principal)) => {
+            if !is_valid_principal(principal) {
+                errors.push(PolicyValidationError::new(
+                    "PRINCIPAL",
+                    format!(
+                        "Principal in {location} must be \"*\" or an object with keys: {}",
+                        PRINCIPAL_KEYS.join(", ")
+                    ),
+                ));
+            }
+        }
+        Ok(None) => errors.push(PolicyValidationError::new(
+            "PRINCIPAL",
+            format!("Missing Principal in {location}, resource policies must specify a Principal"),
+        )),
+        Err(message) => errors.push(PolicyValidationError::new(
+            "PRINCIPAL",
+            format!("{message} in {location}"),
+        )),
+    }
+
+    match exclusive_key(statement, "Action", "NotAction") {
+        Ok(Some(action)) => {
+            if !is_string_or_string_list(action) {
+                errors.push(PolicyValidationError::new(
+                    "ACTION",
+                    format!("Action in {location} must be a string or list of strings"),
+                ));
+            } else if !action_values(action).all(is_valid_action) {
+                errors.push(PolicyValidationError::new(
+                    "ACTION",
+                    format!("Action in {location} must be \"*\" or in the format service:action"),
+                ));
+            }
+        }
+        Ok(None) => errors.push(PolicyValidationError::new(
+            "ACTION",
+            format!("Missing Action in {location}"),
+        )),
+        Err(message) => errors.push(PolicyValidationError::new(
+            "ACTION",
+            format!("{message} in {location}"),
+        )),
+    }
+
+    match exclusive_key(statement, "Resource", "NotResource") {
+        Ok(Some(resource)) => {
+            if !is_string_or_string_list(resource) {
+                errors.push(PolicyValidationError::new(
+                    "RESOURCE",
+                    format!("Resource in {location} must be a string or list of strings"),
+                ));
+            }
+        }
+        Ok(None) => errors.push(PolicyValidationError::new(
+            "RESOURCE",
+            format!("Missing Resource in {location}"),
+        )),
+        Err(message) => errors.push(PolicyValidationError::new(
+            "RESOURCE",
+            format!("{message} in {location}"),
+        )),
+    }
+
+    if let Some(condition) = statement.get("Condition") {
+        if !is_valid_condition(condition) {
+            errors.push(PolicyValidationError::new(
+                "CONDITION",
+                format!(
+                    "Condition in {location} must be an object of condition operators to objects \
+                    of condition keys and values"
+                ),
+            ));
+        }
+
+        // Unsupported operators can't be evaluated, accepting them would silently
+        // ignore the statement they are in (including denies)
+        for operator in condition.as_object().into_iter().flat_map(Map::keys) {
+            if !is_supported_condition_operator(operator) {
+                errors.push(PolicyValidationError::new(
+                    "CONDITION",
+                    format!(
+                        "Condition operator \"{operator}\" in {location} is not supported, \
+                        supported operators are: {}",
+                        CONDITION_OPERATORS.join(", ")
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Report any keys on the `object` that aren't in the `allowed` keys
+fn check_unknown_keys(
+    object: &Map<String, Value>,
+    allowed: &[&str],
+    location: &str,
+    errors: &mut Vec<PolicyValidationError>,
+) {
+    for key in object.keys() {
+        if !allowed.contains(&key.as_str()) {
+            errors.push(PolicyValidationError::new(
+                "POLICY_SYNTAX",
+                format!("Unknown key \"{key}\" in {location}"),
+            ));
+        }
+    }
+}
+
+/// Get the value for either the `key` or the `not_key` (i.e "Action" or "NotAction")
+/// erroring if both are specified
+fn exclusive_key<'a>(
+    object: &'a Map<String, Value>,
+    key: &str,
+    not_key: &str,
+) -> Result<Option<&'a Value>, String> {
+    match (object.get(key), object.get(not_key)) {
+        (Some(_), Some(_)) => Err(format!("Cannot specify both {key} and {not_key}")),
+        (Some(value), None) | (None, Some(value)) => Ok(Some(value)),
+        (None, None) => Ok(None),
+    }
+}
+
+fn is_string_or_string_list(value: &Value) -> bool {
+    match value {
+        Value::String(_) => true,
+        Value::Array(values) => !values.is_empty() && values.iter().all(Value::is_string),
+        _ => false,
+    }
+}
+
+/// Iterate the string values of a string or string list value
+fn action_values(value: &Value) -> impl Iterator<Item = &str> {
+    let values: Vec<&str> = match value {
+        Value::String(value) => vec![value.as_str()],
+        Value::Array(values) => values.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    values.into_iter()
+}
+
+fn is_valid_action(value: &str) -> bool {
+    if value == "*" {
+        return true;
+    }
+
+    value
+        .split_once(':')
+        .is_some_and(|(service, action)| !service.is_empty() && !action.is_empty())
+}
+
+fn is_valid_principal(value: &Value) -> bool {
+    match value {
+        Value::String(value) => value == "*",
+        Value::Object(principals) => {
+            !principals.is_empty()
+                && principals.iter().all(|(key, value)| {
+                    PRINCIPAL_KEYS.contains(&key.as_str()) && is_string_or_string_list(value)
+                })
+        }
+        _ => false,
+    }
+}
+
+fn is_supported_condition_operator(operator: &str) -> bool {
+    if operator == "Null" {
+        return true;
+    }
+
+    let operator = operator.strip_suffix("IfExists").unwrap_or(operator);
+    operator != "Null" && CONDITION_OPERATORS.contains(&operator)
+}
+
+fn is_valid_condition(value: &Value) -> bool {
+    let operators = match value.as_object() {
+        Some(value) => value,
+        None => return false,
+    };
+
+    operators.values().all(|keys| match keys.as_object() {
+        Some(keys) => keys.values().all(|value| match value {
+            Value::String(_) | Value::Bool(_) | Value::Number(_) => true,
+            Value::Array(values) => values
+                .iter()
+                .all(|value| value.is_string() || value.is_boolean() || value.is_number()),
+            _ => false,
+        }),
+        None => false,
+    })
+}
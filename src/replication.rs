@@ -0,0 +1,77 @@
+//! Replication of secrets into the regions of their replicas, replicas are kept up
+//! to date by copying the primary secret into them whenever it changes
+
+use crate::{
+    database::secrets::{
+        CreateReplicaSecretVersion, copy_secret_replica, copy_secret_replica_state,
+        create_replica_secret_version, get_secret_replica_targets,
+        get_secret_versions_missing_from_replica,
+    },
+    handlers::error::AwsError,
+    kms::{decrypt_value, encrypt_secret_value},
+};
+use tokio_rusqlite::rusqlite::Connection;
+
+/// Copy the current state of the secret with the `secret_arn` into its replica with the
+/// `replica_arn` in `region`, creating the replica if it doesn't already exist
+///
+/// Versions copied to the replica are encrypted using the `kms_key_id` (ARN) of the
+/// replica rather than the key of the primary secret, replicas without a KMS key use
+/// the default key of their region. Tags and the resource policy of an existing replica
+/// are replaced with a copy of the ones from the primary secret
+pub fn sync_secret_replica(
+    db: &Connection,
+    secret_arn: &str,
+    replica_arn: &str,
+    region: &str,
+    kms_key_id: Option<&str>,
+) -> Result<(), AwsError> {
+    copy_secret_replica(db, secret_arn, replica_arn, region, kms_key_id)?;
+
+    for version in get_secret_versions_missing_from_replica(db, secret_arn, replica_arn)? {
+        let (secret_string, secret_binary) = match &version.encrypted_value {
+            Some(encrypted) => decrypt_value(db, secret_arn, &version.version_id, encrypted)?,
+            None => (version.secret_string, version.secret_binary),
+        };
+
+        let encrypted_value = encrypt_secret_value(
+            db,
+            kms_key_id,
+            replica_arn,
+            &version.version_id,
+            &secret_string,
+            &secret_binary,
+        )?;
+
+        create_replica_secret_version(
+            db,
+            CreateReplicaSecretVersion {
+                replica_arn: replica_arn.to_string(),
+                version_id: version.version_id,
+                created_at: version.created_at,
+                secret_string,
+                secret_binary,
+                encrypted_value,
+            },
+        )?;
+    }
+
+    copy_secret_replica_state(db, secret_arn, replica_arn)?;
+
+    Ok(())
+}
+
+/// Copy the current state of the secret with the `secret_arn` into all of its replicas
+pub fn sync_secret_replicas(db: &Connection, secret_arn: &str) -> Result<(), AwsError> {
+    for replica in get_secret_replica_targets(db, secret_arn)? {
+        sync_secret_replica(
+            db,
+            secret_arn,
+            &replica.arn,
+            &replica.region,
+            replica.kms_key_id.as_deref(),
+        )?;
+    }
+
+    Ok(())
+}
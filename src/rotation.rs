@@ -0,0 +1,486 @@
+use crate::{
+    arn::ArnConfig,
+    database::{
+        DbErr, DbHandle,
+        secrets::{
+            CreateRotationStep, CreateSecretVersion, add_secret_version_stage,
+            create_rotation_step, create_secret_version, get_secret_by_version_id,
+            get_secret_by_version_stage, get_secret_latest_version, get_secrets_due_rotation,
+            remove_secret_version_stage, remove_secret_version_stage_any, set_secret_next_rotation,
+            set_secret_rotated,
+        },
+        transaction,
+    },
+    handlers::{error::AwsError, models::RotationRules},
+    kms::{decrypt_secret_value, encrypt_secret_value},
+    replication::sync_secret_replicas,
+    utils::schedule::ScheduleExpression,
+};
+use chrono::{DateTime, Days, Utc};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use thiserror::Error;
+use tokio_rusqlite::rusqlite::Connection;
+use uuid::Uuid;
+
+/// Maximum time to wait for a rotation webhook to respond to a step
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Steps performed during a secret rotation, these match the steps
+/// that AWS sends to a rotation function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationStep {
+    /// Create a new version of the secret with the AWSPENDING stage
+    Create,
+    /// Apply the pending secret to the resource the secret protects
+    Set,
+    /// Test the pending secret against the resource
+    Test,
+    /// Move the AWSCURRENT stage to the pending secret version
+    Finish,
+}
+
+impl RotationStep {
+    /// All the rotation steps in the order they are performed
+    pub const ALL: [RotationStep; 4] = [
+        RotationStep::Create,
+        RotationStep::Set,
+        RotationStep::Test,
+        RotationStep::Finish,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RotationStep::Create => "createSecret",
+            RotationStep::Set => "setSecret",
+            RotationStep::Test => "testSecret",
+            RotationStep::Finish => "finishSecret",
+        }
+    }
+}
+
+impl Display for RotationStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Compute the date of the next rotation for the provided `rules` based on
+/// the rotation having last happened at `from`
+pub fn next_rotation_date(rules: &RotationRules, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Some(days) = rules.automatically_after_days {
+        return from.checked_add_days(Days::new(u64::try_from(days).ok()?));
+    }
+
+    let expression: ScheduleExpression = rules.schedule_expression.as_ref()?.parse().ok()?;
+    expression.next_after(from)
+}
+
+#[derive(Debug, Error)]
+pub enum RotationError {
+    /// Error from the database while rotating
+    #[error(transparent)]
+    Database(#[from] tokio_rusqlite::Error<DbErr>),
+
+    /// Error while updating the rotated secret and its replicas
+    #[error(transparent)]
+    Update(#[from] tokio_rusqlite::Error<AwsError>),
+
+    /// A rotation step failed, the pending version is left in place
+    #[error("rotation step {0} failed")]
+    StepFailed(RotationStep),
+}
+
+/// Event payload sent to a rotation webhook for each step, matches
+/// the event AWS sends to a rotation lambda
+#[derive(Serialize)]
+struct RotationEvent<'a> {
+    #[serde(rename = "Step")]
+    step: &'static str,
+    #[serde(rename = "SecretId")]
+    secret_id: &'a str,
+    #[serde(rename = "ClientRequestToken")]
+    client_request_token: &'a str,
+}
+
+/// Marks a secret as having a rotation in progress, the secret can be rotated
+/// again once the guard is dropped
+pub struct RotationGuard {
+    rotating: Arc<Mutex<HashSet<String>>>,
+    secret_arn: String,
+}
+
+impl Drop for RotationGuard {
+    fn drop(&mut self) {
+        self.rotating
+            .lock()
+            .expect("rotation lock poisoned")
+            .remove(&self.secret_arn);
+    }
+}
+
+/// Runner for performing secret rotations
+///
+/// Secrets with a rotation lambda ARN that has a configured webhook have each
+/// step sent to the webhook, secrets without a rotation lambda ARN are rotated
+/// locally
+#[derive(Clone, Default)]
+pub struct RotationRunner {
+    /// Mapping from rotation lambda ARN to the webhook URL to invoke
+    lambdas: Arc<HashMap<String, String>>,
+    /// HTTP client for invoking webhooks
+    client: reqwest::Client,
+    /// ARNs of the secrets with a rotation in progress
+    rotating: Arc<Mutex<HashSet<String>>>,
+}
+
+impl RotationRunner {
+    pub fn new(lambdas: HashMap<String, String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .expect("failed to create rotation http client");
+
+        Self {
+            lambdas: Arc::new(lambdas),
+            client,
+            rotating: Default::default(),
+        }
+    }
+
+    /// Check if the `rotation_lambda_arn` has a configured webhook
+    pub fn has_lambda(&self, rotation_lambda_arn: &str) -> bool {
+        self.lambdas.contains_key(rotation_lambda_arn)
+    }
+
+    /// Mark the secret with the `secret_arn` as being rotated, provides [None] when
+    /// a rotation of the secret is already in progress
+    pub fn begin_rotation(&self, secret_arn: &str) -> Option<RotationGuard> {
+        let mut rotating = self.rotating.lock().expect("rotation lock poisoned");
+        if !rotating.insert(secret_arn.to_string()) {
+            return None;
+        }
+
+        Some(RotationGuard {
+            rotating: self.rotating.clone(),
+            secret_arn: secret_arn.to_string(),
+        })
+    }
+
+    /// Spawn a task performing the rotation of the secret marked as being rotated
+    /// by the `guard`, like AWS the rotation steps are performed after the request
+    /// starting the rotation has been responded to
+    pub fn spawn_rotation(
+        &self,
+        guard: RotationGuard,
+        db: DbHandle,
+        version_id: String,
+        rotation_lambda_arn: Option<String>,
+        rotation_rules: Option<RotationRules>,
+    ) {
+        let runner = self.clone();
+
+        tokio::spawn(async move {
+            let secret_arn = guard.secret_arn.clone();
+
+            match runner
+                .rotate_secret(
+                    &db,
+                    secret_arn,
+                    version_id,
+                    rotation_lambda_arn,
+                    rotation_rules,
+                )
+                .await
+            {
+                Ok(()) => {}
+                // The pending version is left in place for the rotation to be reattempted
+                Err(RotationError::StepFailed(step)) => {
+                    tracing::warn!(%step, "secret rotation did not complete");
+                }
+                Err(error) => {
+                    tracing::error!(?error, "failed to rotate secret");
+                }
+            }
+
+            drop(guard);
+        });
+    }
+
+    /// Rotate the secret with the `secret_arn` performing each of the rotation steps
+    /// against the pending version `version_id`
+    ///
+    /// The outcome of each step is recorded, when a step fails the rotation is
+    /// stopped leaving the AWSPENDING version in place to be reattempted at the
+    /// next scheduled rotation.
+    ///
+    /// When complete the secret is marked as rotated and the next rotation date is
+    /// computed from the `rotation_rules`
+    pub async fn rotate_secret(
+        &self,
+        db: &DbHandle,
+        secret_arn: String,
+        version_id: String,
+        rotation_lambda_arn: Option<String>,
+        rotation_rules: Option<RotationRules>,
+    ) -> Result<(), RotationError> {
+        let webhook_url = match &rotation_lambda_arn {
+            Some(rotation_lambda_arn) => {
+                let webhook_url = self.lambdas.get(rotation_lambda_arn);
+                if webhook_url.is_none() {
+                    // Lambda was removed from the configuration after rotation was enabled
+                    tracing::warn!(
+                        %secret_arn,
+                        %rotation_lambda_arn,
+                        "rotation lambda has no configured webhook, rotating locally"
+                    );
+                }
+
+                webhook_url
+            }
+            None => None,
+        };
+
+        for step in RotationStep::ALL {
+            tracing::debug!(%secret_arn, %version_id, %step, "performing rotation step");
+
+            let outcome = match webhook_url {
+                Some(webhook_url) => {
+                    self.perform_webhook_step(webhook_url, step, &secret_arn, &version_id)
+                        .await
+                }
+                None => {
+                    let secret_arn = secret_arn.clone();
+                    let version_id = version_id.clone();
+
+                    db.call(move |db| {
+                        transaction(db, move |db| {
+                            perform_local_step(db, step, &secret_arn, &version_id)
+                        })
+                    })
+                    .await
+                    .map_err(|error| {
+                        tracing::error!(?error, %step, "failed to perform rotation step");
+                        error.to_string()
+                    })
+                }
+            };
+
+            let success = outcome.is_ok();
+
+            db.call({
+                let create = CreateRotationStep {
+                    secret_arn: secret_arn.clone(),
+                    version_id: version_id.clone(),
+                    step: step.to_string(),
+                    success,
+                    message: outcome.err(),
+                };
+
+                move |db| create_rotation_step(db, create)
+            })
+            .await
+            .inspect_err(|error| tracing::error!(?error, "failed to record rotation step"))?;
+
+            if !success {
+                let next_rotation_at = rotation_rules
+                    .as_ref()
+                    .and_then(|rules| next_rotation_date(rules, Utc::now()));
+
+                db.call(move |db| {
+                    set_secret_next_rotation(db, &secret_arn, next_rotation_at)?;
+                    sync_secret_replicas(db, &secret_arn)
+                })
+                .await
+                .inspect_err(|error| tracing::error!(?error, "failed to schedule next rotation"))?;
+
+                return Err(RotationError::StepFailed(step));
+            }
+        }
+
+        let rotated_at = Utc::now();
+        let next_rotation_at = rotation_rules
+            .as_ref()
+            .and_then(|rules| next_rotation_date(rules, rotated_at));
+
+        db.call(move |db| {
+            set_secret_rotated(db, &secret_arn, rotated_at, next_rotation_at)?;
+            sync_secret_replicas(db, &secret_arn)
+        })
+        .await
+        .inspect_err(|error| tracing::error!(?error, "failed to mark secret as rotated"))?;
+
+        Ok(())
+    }
+
+    /// Start the rotation of all the secrets that have reached their next rotation
+    /// date, each rotation is performed in its own task so a slow rotation webhook
+    /// doesn't hold up the other rotations or background tasks
+    pub async fn rotate_due_secrets(
+        &self,
+        db: &DbHandle,
+    ) -> Result<(), tokio_rusqlite::Error<DbErr>> {
+        let now = Utc::now();
+        let secret_arns = db.call(move |db| get_secrets_due_rotation(db, now)).await?;
+
+        for secret_arn in secret_arns {
+            let (secret, pending) = db
+                .call(move |db| {
+                    let region = ArnConfig::secret_arn_region(&secret_arn);
+                    let secret = get_secret_latest_version(db, region, &secret_arn)?;
+                    let pending =
+                        get_secret_by_version_stage(db, region, &secret_arn, "AWSPENDING")?;
+                    Ok((secret, pending))
+                })
+                .await?;
+
+            let secret = match secret {
+                Some(value) => value,
+                None => continue,
+            };
+
+            // Secrets already being rotated are left to that rotation
+            let guard = match self.begin_rotation(&secret.arn) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            // Reattempt an incomplete rotation or start a new one
+            let version_id = pending
+                .map(|pending| pending.version_id)
+                .filter(|version_id| version_id != &secret.version_id)
+                .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+            tracing::debug!(secret_arn = %secret.arn, %version_id, "rotating due secret");
+
+            self.spawn_rotation(
+                guard,
+                db.clone(),
+                version_id,
+                secret.rotation_lambda_arn,
+                secret.rotation_rules,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Performs a rotation step by sending the step event to the webhook, any
+    /// non success status code is treated as a failure of the step
+    async fn perform_webhook_step(
+        &self,
+        webhook_url: &str,
+        step: RotationStep,
+        secret_arn: &str,
+        version_id: &str,
+    ) -> Result<(), String> {
+        let event = RotationEvent {
+            step: step.as_str(),
+            secret_id: secret_arn,
+            client_request_token: version_id,
+        };
+
+        let response = self
+            .client
+            .post(webhook_url)
+            .json(&event)
+            .send()
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, %step, "failed to invoke rotation webhook");
+                error.to_string()
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            tracing::warn!(%status, %step, %body, "rotation webhook step failed");
+            return Err(format!("rotation webhook responded with {status}: {body}"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Performs a rotation step locally, the local runner creates the pending version
+/// from a copy of the current secret value and promotes it once finished
+fn perform_local_step(
+    db: &Connection,
+    step: RotationStep,
+    secret_arn: &str,
+    version_id: &str,
+) -> Result<(), AwsError> {
+    let region = ArnConfig::secret_arn_region(secret_arn);
+
+    match step {
+        RotationStep::Create => {
+            // Pending version was already created by a previous attempt
+            if get_secret_by_version_id(db, region, secret_arn, version_id)?.is_some() {
+                return Ok(());
+            }
+
+            let mut current = match get_secret_latest_version(db, region, secret_arn)? {
+                Some(value) => value,
+                None => return Err(DbErr::QueryReturnedNoRows.into()),
+            };
+
+            // Encrypted values are bound to their version so the copy is encrypted again
+            decrypt_secret_value(db, &mut current)?;
+            let encrypted_value = encrypt_secret_value(
+                db,
+                current.kms_key_id.as_deref(),
+                secret_arn,
+                version_id,
+                &current.secret_string,
+                &current.secret_binary,
+            )?;
+
+            create_secret_version(
+                db,
+                CreateSecretVersion {
+                    secret_arn: secret_arn.to_string(),
+                    version_id: version_id.to_string(),
+                    secret_string: current.secret_string,
+                    secret_binary: current.secret_binary,
+                    encrypted_value,
+                },
+            )?;
+
+            remove_secret_version_stage_any(db, secret_arn, "AWSPENDING")?;
+            add_secret_version_stage(db, secret_arn, version_id, "AWSPENDING")?;
+        }
+
+        // Local secrets have no external resource to update or test
+        RotationStep::Set | RotationStep::Test => {}
+
+        RotationStep::Finish => {
+            let current = match get_secret_latest_version(db, region, secret_arn)? {
+                Some(value) => value,
+                None => return Err(DbErr::QueryReturnedNoRows.into()),
+            };
+
+            // Promote the pending version unless it is already the current version
+            if current.version_id != version_id {
+                // Ensure nobody else has the AWSPREVIOUS stage
+                remove_secret_version_stage_any(db, secret_arn, "AWSPREVIOUS")?;
+
+                // Move the old current version to AWSPREVIOUS
+                add_secret_version_stage(db, secret_arn, &current.version_id, "AWSPREVIOUS")?;
+                remove_secret_version_stage(db, secret_arn, &current.version_id, "AWSCURRENT")?;
+
+                // Promote the pending version to AWSCURRENT
+                add_secret_version_stage(db, secret_arn, version_id, "AWSCURRENT")?;
+            }
+
+            remove_secret_version_stage(db, secret_arn, version_id, "AWSPENDING")?;
+        }
+    }
+
+    // Keep any replicas up to date with the rotated versions
+    sync_secret_replicas(db, secret_arn)
+}
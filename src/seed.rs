@@ -0,0 +1,236 @@
+//! Seeding of secrets from a declarative fixtures file applied on startup
+
+use crate::{
+    arn::ArnConfig,
+    database::{
+        DbErr,
+        secrets::{delete_all_secrets, delete_secret, get_secret_arn},
+    },
+    dump::{
+        DumpError, DumpSecret, DumpSecretVersion, DumpTag, ImportSummary, SecretsDump,
+        import_secrets,
+    },
+    handlers::create_secret_arn,
+    kms::resolve_kms_key_id,
+};
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::Path};
+use thiserror::Error;
+use tokio_rusqlite::rusqlite::Connection;
+use uuid::Uuid;
+
+/// How the secrets in a seed file are applied to the database
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedMode {
+    /// Only create secrets that don't already exist
+    #[default]
+    CreateIfMissing,
+    /// Replace existing secrets with the secrets from the seed file
+    Overwrite,
+    /// Remove all existing secrets before creating the secrets from the seed file
+    Reset,
+}
+
+impl SeedMode {
+    /// Parse a seed mode from its name (i.e create_if_missing)
+    pub fn parse(value: &str) -> Option<SeedMode> {
+        match value {
+            "create_if_missing" => Some(SeedMode::CreateIfMissing),
+            "overwrite" => Some(SeedMode::Overwrite),
+            "reset" => Some(SeedMode::Reset),
+            _ => None,
+        }
+    }
+}
+
+/// Seed file containing the secrets to create
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedFile {
+    #[serde(default)]
+    pub secrets: Vec<SeedSecret>,
+}
+
+/// Secret within a [SeedFile]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedSecret {
+    pub name: String,
+    /// Region to create the secret in, defaults to the configured region
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Key ID or ARN of the KMS key to encrypt the secret with
+    #[serde(default)]
+    pub kms_key_id: Option<String>,
+    /// Value of the AWSCURRENT version
+    #[serde(default)]
+    pub secret_string: Option<String>,
+    /// Base64 encoded value of the AWSCURRENT version
+    #[serde(default)]
+    pub secret_binary: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    /// Additional versions created before the AWSCURRENT version
+    #[serde(default)]
+    pub versions: Vec<SeedSecretVersion>,
+}
+
+/// Additional version of a [SeedSecret]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedSecretVersion {
+    /// ID of the version, a random ID is used when not specified
+    #[serde(default)]
+    pub version_id: Option<String>,
+    #[serde(default)]
+    pub secret_string: Option<String>,
+    #[serde(default)]
+    pub secret_binary: Option<String>,
+    #[serde(default)]
+    pub version_stages: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum SeedError {
+    #[error("failed to read seed file: {0}")]
+    Read(std::io::Error),
+
+    #[error("seed file is not valid: {0}")]
+    Invalid(String),
+
+    #[error("each version of secret {0} must have exactly one of secret_string or secret_binary")]
+    InvalidValue(String),
+
+    #[error("secret {0} must have an AWSCURRENT version")]
+    MissingCurrentVersion(String),
+
+    #[error("KMS key of secret {0} does not exist or is disabled")]
+    KmsKey(String),
+
+    #[error(transparent)]
+    Db(#[from] DbErr),
+
+    #[error(transparent)]
+    Dump(#[from] DumpError),
+}
+
+/// Load the seed file at `path`, files with the ".json" extension are loaded as
+/// JSON, files with the ".yaml" or ".yml" extension are loaded as YAML and files
+/// with the ".toml" extension are loaded as TOML
+pub fn load_seed_file(path: &str) -> Result<SeedFile, SeedError> {
+    let value = std::fs::read_to_string(path).map_err(SeedError::Read)?;
+
+    let extension = Path::new(path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+
+    match extension.as_deref() {
+        Some("json") => {
+            serde_json::from_str(&value).map_err(|error| SeedError::Invalid(error.to_string()))
+        }
+        Some("yaml" | "yml") => {
+            serde_yaml_ng::from_str(&value).map_err(|error| SeedError::Invalid(error.to_string()))
+        }
+        Some("toml") => {
+            toml::from_str(&value).map_err(|error| SeedError::Invalid(error.to_string()))
+        }
+        _ => Err(SeedError::Invalid(
+            "seed file must have a .json, .yaml, .yml or .toml extension".to_string(),
+        )),
+    }
+}
+
+/// Apply the secrets from the `seed` file to the database using the `mode`
+///
+/// Should be performed within a transaction so a failed seed doesn't leave the
+/// database partially seeded
+pub fn apply_seed(
+    db: &Connection,
+    arn: &ArnConfig,
+    mode: SeedMode,
+    seed: SeedFile,
+) -> Result<ImportSummary, SeedError> {
+    if mode == SeedMode::Reset {
+        delete_all_secrets(db)?;
+    }
+
+    let mut dump = SecretsDump::default();
+
+    for secret in seed.secrets {
+        let region = secret.region.unwrap_or_else(|| arn.region.clone());
+
+        if let Some(existing_arn) = get_secret_arn(db, &region, &secret.name)? {
+            match mode {
+                SeedMode::CreateIfMissing => continue,
+                SeedMode::Overwrite | SeedMode::Reset => {
+                    delete_secret(db, &existing_arn)?;
+                }
+            }
+        }
+
+        let kms_key_id = match &secret.kms_key_id {
+            Some(kms_key_id) => resolve_kms_key_id(db, &region, kms_key_id)
+                .map_err(|_| SeedError::KmsKey(secret.name.clone()))?,
+            None => None,
+        };
+
+        let mut versions: Vec<DumpSecretVersion> = secret
+            .versions
+            .into_iter()
+            .map(|version| DumpSecretVersion {
+                version_id: version
+                    .version_id
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+                secret_string: version.secret_string,
+                secret_binary: version.secret_binary,
+                version_stages: version.version_stages,
+            })
+            .collect();
+
+        if secret.secret_string.is_some() || secret.secret_binary.is_some() {
+            versions.push(DumpSecretVersion {
+                version_id: Uuid::new_v4().to_string(),
+                secret_string: secret.secret_string,
+                secret_binary: secret.secret_binary,
+                version_stages: vec!["AWSCURRENT".to_string()],
+            });
+        }
+
+        // Versions must have either a string or binary value
+        if versions
+            .iter()
+            .any(|version| version.secret_string.is_some() == version.secret_binary.is_some())
+        {
+            return Err(SeedError::InvalidValue(secret.name));
+        }
+
+        if !versions.iter().any(|version| {
+            version
+                .version_stages
+                .iter()
+                .any(|version_stage| version_stage == "AWSCURRENT")
+        }) {
+            return Err(SeedError::MissingCurrentVersion(secret.name));
+        }
+
+        dump.secrets.push(DumpSecret {
+            arn: create_secret_arn(&arn.secret_arn_prefix(&region), &secret.name),
+            region,
+            name: secret.name,
+            description: secret.description,
+            kms_key_id,
+            tags: secret
+                .tags
+                .into_iter()
+                .map(|(key, value)| DumpTag { key, value })
+                .collect(),
+            versions,
+        });
+    }
+
+    let summary = import_secrets(db, dump)?;
+    Ok(summary)
+}
@@ -0,0 +1,163 @@
+use super::{StsContext, StsError, StsParams, principal_account_id, write_element};
+use crate::{
+    handlers::access::authorize_identity, middleware::aws_sig_v4::AccessKey,
+    utils::date::chrono_to_system_time,
+};
+use aws_credential_types::Credentials;
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use rand::{RngExt, distr::Alphanumeric};
+
+/// Default lifetime of the issued credentials in seconds
+const DEFAULT_DURATION_SECONDS: i64 = 60 * 60;
+
+/// Minimum lifetime of the issued credentials in seconds
+const MIN_DURATION_SECONDS: i64 = 15 * 60;
+
+/// Maximum lifetime of the issued credentials in seconds
+const MAX_DURATION_SECONDS: i64 = 12 * 60 * 60;
+
+// https://docs.aws.amazon.com/STS/latest/APIReference/API_AssumeRole.html
+pub fn assume_role(ctx: &StsContext, params: &StsParams) -> Result<String, StsError> {
+    let role_arn = params
+        .get("RoleArn")
+        .ok_or_else(|| StsError::ValidationError("RoleArn is required".to_string()))?;
+
+    let role_session_name = params
+        .get("RoleSessionName")
+        .ok_or_else(|| StsError::ValidationError("RoleSessionName is required".to_string()))?;
+
+    let duration_seconds = match params.get("DurationSeconds") {
+        Some(value) => value.parse::<i64>().map_err(|_| {
+            StsError::ValidationError("DurationSeconds must be a number".to_string())
+        })?,
+        None => DEFAULT_DURATION_SECONDS,
+    };
+
+    if !(MIN_DURATION_SECONDS..=MAX_DURATION_SECONDS).contains(&duration_seconds) {
+        return Err(StsError::ValidationError(format!(
+            "DurationSeconds must be between {MIN_DURATION_SECONDS} and {MAX_DURATION_SECONDS}"
+        )));
+    }
+
+    if !is_valid_role_session_name(role_session_name) {
+        return Err(StsError::ValidationError(
+            "RoleSessionName must be 2-64 characters of letters, numbers or +=,.@-_".to_string(),
+        ));
+    }
+
+    let role_name = match role_name(role_arn) {
+        Some(value) => value,
+        None => {
+            return Err(StsError::ValidationError(format!(
+                "{role_arn} is not a valid role ARN"
+            )));
+        }
+    };
+
+    let identity = &ctx.identity;
+
+    authorize_identity(identity, "sts:AssumeRole", role_arn).map_err(|_| {
+        StsError::AccessDenied(identity.principal_arn.clone(), role_arn.to_string())
+    })?;
+
+    // Credentials can't outlive the temporary credentials used to request them
+    let expiration = Utc::now() + TimeDelta::seconds(duration_seconds);
+    let expiration = match identity.expiry {
+        Some(caller_expiry) => expiration.min(DateTime::<Utc>::from(caller_expiry)),
+        None => expiration,
+    };
+    let expiry = chrono_to_system_time(expiration).ok_or(StsError::InternalFailure)?;
+
+    let access_key_id = format!("ASIA{}", random_id(16));
+    let secret_access_key = random_string(40);
+    let session_token = random_string(256);
+
+    let partition = &ctx.arn.partition;
+    let account_id = principal_account_id(role_arn, &ctx.arn);
+    let assumed_role_id = format!("AROA{}:{role_session_name}", random_id(17));
+    let assumed_role_arn =
+        format!("arn:{partition}:sts::{account_id}:assumed-role/{role_name}/{role_session_name}");
+
+    // Temporary credentials inherit the identity policy of the caller
+    ctx.access_keys.insert(AccessKey {
+        credentials: Credentials::new(
+            access_key_id.clone(),
+            secret_access_key.clone(),
+            Some(session_token.clone()),
+            Some(expiry),
+            "sm-sts",
+        ),
+        principal_arn: assumed_role_arn.clone(),
+        policy: identity.policy.clone(),
+    });
+
+    tracing::debug!(%assumed_role_arn, %access_key_id, "issued temporary credentials");
+
+    let mut credentials = String::new();
+    write_element(&mut credentials, "AccessKeyId", &access_key_id);
+    write_element(&mut credentials, "SecretAccessKey", &secret_access_key);
+    write_element(&mut credentials, "SessionToken", &session_token);
+    write_element(
+        &mut credentials,
+        "Expiration",
+        &expiration.to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+
+    let mut assumed_role_user = String::new();
+    write_element(&mut assumed_role_user, "AssumedRoleId", &assumed_role_id);
+    write_element(&mut assumed_role_user, "Arn", &assumed_role_arn);
+
+    Ok(format!(
+        "<Credentials>{credentials}</Credentials>\
+        <AssumedRoleUser>{assumed_role_user}</AssumedRoleUser>"
+    ))
+}
+
+/// Get the name of the role from a role ARN in the format
+/// arn:aws:iam::<account-id>:role/<path><role-name>
+fn role_name(role_arn: &str) -> Option<&str> {
+    let mut parts = role_arn.splitn(6, ':');
+    let (arn, _partition, service, _region, _account_id, resource) = (
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+    );
+
+    if arn != "arn" || service != "iam" {
+        return None;
+    }
+
+    let path = resource.strip_prefix("role/")?;
+    let role_name = path.rsplit('/').next()?;
+
+    if role_name.is_empty() {
+        return None;
+    }
+
+    Some(role_name)
+}
+
+/// Check the session name is 2-64 characters of `[\w+=,.@-]`
+fn is_valid_role_session_name(value: &str) -> bool {
+    (2..=64).contains(&value.len())
+        && value
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || "_+=,.@-".contains(char))
+}
+
+/// Generate a random uppercase alphanumeric identifier of `length`
+fn random_id(length: usize) -> String {
+    random_string(length).to_ascii_uppercase()
+}
+
+/// Generate a random alphanumeric string of `length`
+fn random_string(length: usize) -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
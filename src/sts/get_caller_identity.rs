@@ -0,0 +1,17 @@
+use super::{StsContext, StsError, principal_account_id, write_element};
+
+// https://docs.aws.amazon.com/STS/latest/APIReference/API_GetCallerIdentity.html
+pub fn get_caller_identity(ctx: &StsContext) -> Result<String, StsError> {
+    let identity = &ctx.identity;
+
+    let mut result = String::new();
+    write_element(&mut result, "Arn", &identity.principal_arn);
+    write_element(&mut result, "UserId", &identity.access_key_id);
+    write_element(
+        &mut result,
+        "Account",
+        principal_account_id(&identity.principal_arn, &ctx.arn),
+    );
+
+    Ok(result)
+}
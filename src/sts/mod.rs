@@ -0,0 +1,273 @@
+//! Local stand-in for the AWS Security Token Service (STS) query protocol API,
+//! allowing clients to obtain temporary credentials accepted by the server
+
+use crate::{
+    arn::ArnConfig,
+    handlers::error::ErrorType,
+    middleware::aws_sig_v4::{AccessKeyStore, AuthenticatedIdentity},
+};
+use axum::{
+    body::Body,
+    http::{HeaderValue, Method, Request, StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use futures::future::BoxFuture;
+use http_body_util::BodyExt;
+use std::{collections::HashMap, fmt::Write, mem::swap};
+use thiserror::Error;
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+mod assume_role;
+mod get_caller_identity;
+
+/// XML namespace used by STS responses
+const STS_NAMESPACE: &str = "https://sts.amazonaws.com/doc/2011-06-15/";
+
+/// Content type used by query protocol requests
+const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+
+/// Parameters of a query protocol request
+type StsParams = HashMap<String, String>;
+
+/// State available to STS actions while handling a request
+pub struct StsContext {
+    /// Store to add issued temporary credentials to
+    pub access_keys: AccessKeyStore,
+    /// Identity that made the request
+    pub identity: AuthenticatedIdentity,
+    /// Partition and account used when generating ARNs
+    pub arn: ArnConfig,
+}
+
+#[derive(Debug, Error)]
+pub enum StsError {
+    #[error("Could not find operation {0} for version 2011-06-15")]
+    InvalidAction(String),
+
+    #[error("{0}")]
+    ValidationError(String),
+
+    #[error("User: {0} is not authorized to perform: sts:AssumeRole on resource: {1}")]
+    AccessDenied(String, String),
+
+    #[error("An error occurred on the server side.")]
+    InternalFailure,
+}
+
+impl StsError {
+    fn code(&self) -> &'static str {
+        match self {
+            StsError::InvalidAction(_) => "InvalidAction",
+            StsError::ValidationError(_) => "ValidationError",
+            StsError::AccessDenied(_, _) => "AccessDenied",
+            StsError::InternalFailure => "InternalFailure",
+        }
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            StsError::AccessDenied(_, _) => StatusCode::FORBIDDEN,
+            StsError::InternalFailure => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_type(&self) -> &'static str {
+        match self {
+            StsError::InternalFailure => "Receiver",
+            _ => "Sender",
+        }
+    }
+}
+
+impl IntoResponse for StsError {
+    fn into_response(self) -> Response {
+        let body = format!(
+            "<ErrorResponse xmlns=\"{STS_NAMESPACE}\">\
+                <Error>\
+                    <Type>{}</Type>\
+                    <Code>{}</Code>\
+                    <Message>{}</Message>\
+                </Error>\
+                <RequestId>{}</RequestId>\
+            </ErrorResponse>",
+            self.error_type(),
+            self.code(),
+            escape_xml(&self.to_string()),
+            Uuid::new_v4()
+        );
+
+        let mut response = xml_response(self.status_code(), body);
+        response.extensions_mut().insert(ErrorType(self.code()));
+        response
+    }
+}
+
+/// Layer handling STS query protocol requests, requests that aren't form encoded
+/// POST requests to the root path are passed on to the inner service
+#[derive(Clone, Default)]
+pub struct StsLayer;
+
+impl<S> Layer<S> for StsLayer {
+    type Service = StsMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        StsMiddleware { inner }
+    }
+}
+
+/// Middleware structure
+#[derive(Clone)]
+pub struct StsMiddleware<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for StsMiddleware<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let mut inner = self.inner.clone();
+
+        // Swap to ensure we get the service that was ready and not the cloned one
+        swap(&mut inner, &mut self.inner);
+
+        // Secrets Manager requests use the JSON protocol, only query protocol requests
+        // are form encoded. Other routes (i.e the admin API) are never STS requests
+        let is_query_request = req.method() == Method::POST
+            && req.uri().path() == "/"
+            && req
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with(FORM_CONTENT_TYPE));
+
+        if !is_query_request {
+            return Box::pin(inner.call(req));
+        }
+
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+
+            let access_keys = parts
+                .extensions
+                .get::<AccessKeyStore>()
+                .expect("sts service missing access key store");
+
+            let identity = parts
+                .extensions
+                .get::<AuthenticatedIdentity>()
+                .expect("sts service missing authenticated identity");
+
+            let arn = parts
+                .extensions
+                .get::<ArnConfig>()
+                .expect("sts service missing arn config");
+
+            let ctx = StsContext {
+                access_keys: access_keys.clone(),
+                identity: identity.clone(),
+                arn: arn.clone(),
+            };
+
+            let body = match body.collect().await {
+                Ok(value) => value.to_bytes(),
+                Err(error) => {
+                    tracing::error!(?error, "failed to collect bytes");
+                    return Ok(StsError::InternalFailure.into_response());
+                }
+            };
+
+            let params: StsParams = match serde_urlencoded::from_bytes(&body) {
+                Ok(value) => value,
+                Err(error) => {
+                    tracing::error!(?error, "failed to parse sts request");
+                    return Ok(StsError::ValidationError(
+                        "The request parameters are not valid".to_string(),
+                    )
+                    .into_response());
+                }
+            };
+
+            let action = params.get("Action").map(String::as_str).unwrap_or_default();
+
+            let result = match action {
+                "AssumeRole" => assume_role::assume_role(&ctx, &params),
+                "GetCallerIdentity" => get_caller_identity::get_caller_identity(&ctx),
+                _ => Err(StsError::InvalidAction(action.to_string())),
+            };
+
+            Ok(match result {
+                Ok(result) => action_response(action, &result),
+                Err(error) => error.into_response(),
+            })
+        })
+    }
+}
+
+/// Create the response for a successful `action` wrapping the `result` XML
+fn action_response(action: &str, result: &str) -> Response {
+    let body = format!(
+        "<{action}Response xmlns=\"{STS_NAMESPACE}\">\
+            <{action}Result>{result}</{action}Result>\
+            <ResponseMetadata>\
+                <RequestId>{}</RequestId>\
+            </ResponseMetadata>\
+        </{action}Response>",
+        Uuid::new_v4()
+    );
+
+    xml_response(StatusCode::OK, body)
+}
+
+fn xml_response(status_code: StatusCode, body: String) -> Response {
+    let mut response = (status_code, body).into_response();
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/xml"));
+    response
+}
+
+/// Get the account ID from the `principal_arn`, uses the configured account
+/// when the ARN doesn't specify an account
+fn principal_account_id<'a>(principal_arn: &'a str, arn: &'a ArnConfig) -> &'a str {
+    principal_arn
+        .split(':')
+        .nth(4)
+        .filter(|account_id| !account_id.is_empty())
+        .unwrap_or(&arn.account_id)
+}
+
+/// Write an XML element containing the escaped `value` to `output`
+fn write_element(output: &mut String, name: &str, value: &str) {
+    _ = write!(output, "<{name}>{}</{name}>", escape_xml(value));
+}
+
+/// Escape the XML special characters within `value`
+fn escape_xml(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&apos;"),
+            char => output.push(char),
+        }
+    }
+    output
+}
@@ -0,0 +1,269 @@
+//! Optional request throttling mimicking the per-API request quotas of AWS, requests
+//! are rate limited per access key and target using a token bucket for each pair
+
+use crate::{
+    handlers::error::{IntoErrorResponse, ThrottlingException},
+    middleware::aws_sig_v4::AuthenticatedIdentity,
+};
+use axum::{body::Body, http::Request, response::Response};
+use futures::future::BoxFuture;
+use std::{
+    collections::HashMap,
+    mem::swap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+/// Default requests per second allowed for each target, based on the published
+/// Secrets Manager and KMS request quotas
+const DEFAULT_THROTTLE_LIMITS: &[(&str, u32)] = &[
+    ("secretsmanager.GetSecretValue", 10_000),
+    ("secretsmanager.DescribeSecret", 10_000),
+    ("secretsmanager.BatchGetSecretValue", 100),
+    ("secretsmanager.ListSecrets", 100),
+    ("secretsmanager.ListSecretVersionIds", 50),
+    ("secretsmanager.GetRandomPassword", 50),
+    ("secretsmanager.GetResourcePolicy", 50),
+    ("secretsmanager.CreateSecret", 50),
+    ("secretsmanager.PutSecretValue", 50),
+    ("secretsmanager.UpdateSecret", 50),
+    ("secretsmanager.UpdateSecretVersionStage", 50),
+    ("secretsmanager.DeleteSecret", 50),
+    ("secretsmanager.RestoreSecret", 50),
+    ("secretsmanager.TagResource", 50),
+    ("secretsmanager.UntagResource", 50),
+    ("secretsmanager.RotateSecret", 50),
+    ("secretsmanager.CancelRotateSecret", 50),
+    ("secretsmanager.PutResourcePolicy", 50),
+    ("secretsmanager.DeleteResourcePolicy", 50),
+    ("secretsmanager.ValidateResourcePolicy", 50),
+    ("secretsmanager.ReplicateSecretToRegions", 50),
+    ("secretsmanager.RemoveRegionsFromReplication", 50),
+    ("secretsmanager.StopReplicationToReplica", 50),
+    ("TrentService.CreateKey", 5),
+    ("TrentService.DescribeKey", 2_000),
+    ("TrentService.ListKeys", 100),
+    ("TrentService.EnableKey", 5),
+    ("TrentService.DisableKey", 5),
+];
+
+/// Time taken for a bucket to refill, buckets unused for this long are full and
+/// are forgotten as a new bucket would be identical
+const REFILL_WINDOW: Duration = Duration::from_secs(1);
+
+/// Get the default requests per second allowed for each target
+pub fn default_throttle_limits() -> HashMap<String, u32> {
+    DEFAULT_THROTTLE_LIMITS
+        .iter()
+        .map(|(target, limit)| (target.to_string(), *limit))
+        .collect()
+}
+
+/// Token bucket allowing up to `limit` requests per second, the bucket holds at most
+/// one second worth of tokens
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: u32, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit),
+            updated_at: now,
+        }
+    }
+
+    /// Take a token from the bucket, provides false when the bucket is empty
+    fn try_take(&mut self, limit: u32, now: Instant) -> bool {
+        let limit = f64::from(limit);
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * limit).min(limit);
+        self.updated_at = now;
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+/// Token buckets of the access keys and targets that have recently made requests
+struct TokenBuckets {
+    /// Token buckets keyed by access key ID and target
+    buckets: HashMap<(String, String), TokenBucket>,
+    /// Last time the full buckets were forgotten
+    evicted_at: Instant,
+}
+
+/// Rate limiter tracking the requests made by each access key to each target
+#[derive(Clone)]
+struct Throttler {
+    /// Requests per second allowed for each target, targets without a
+    /// limit are not throttled
+    limits: Arc<HashMap<String, u32>>,
+    buckets: Arc<Mutex<TokenBuckets>>,
+}
+
+impl Throttler {
+    fn new(limits: HashMap<String, u32>) -> Self {
+        Self {
+            limits: Arc::new(limits),
+            buckets: Arc::new(Mutex::new(TokenBuckets {
+                buckets: HashMap::new(),
+                evicted_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Check if a request to the `target` by the `access_key_id` is allowed
+    fn is_allowed(&self, access_key_id: &str, target: &str) -> bool {
+        self.is_allowed_at(access_key_id, target, Instant::now())
+    }
+
+    /// Check if a request to the `target` by the `access_key_id` is allowed at `now`
+    fn is_allowed_at(&self, access_key_id: &str, target: &str, now: Instant) -> bool {
+        let limit = match self.limits.get(target) {
+            Some(value) => *value,
+            None => return true,
+        };
+
+        let mut buckets = self.buckets.lock().expect("throttle lock poisoned");
+
+        // Forget the buckets that have refilled so access keys that stop making
+        // requests (i.e expired temporary credentials) don't accumulate
+        if now.saturating_duration_since(buckets.evicted_at) >= REFILL_WINDOW {
+            buckets.buckets.retain(|_, bucket| {
+                now.saturating_duration_since(bucket.updated_at) < REFILL_WINDOW
+            });
+            buckets.evicted_at = now;
+        }
+
+        buckets
+            .buckets
+            .entry((access_key_id.to_string(), target.to_string()))
+            .or_insert_with(|| TokenBucket::new(limit, now))
+            .try_take(limit, now)
+    }
+}
+
+/// Layer throttling requests that exceed the request limit of their target
+#[derive(Clone)]
+pub struct ThrottleLayer {
+    throttler: Option<Throttler>,
+}
+
+impl ThrottleLayer {
+    /// Create a throttle layer allowing the requests per second in `limits` for each
+    /// target, throttling is disabled when no `limits` are provided
+    pub fn new(limits: Option<HashMap<String, u32>>) -> Self {
+        Self {
+            throttler: limits.map(Throttler::new),
+        }
+    }
+}
+
+impl<S> Layer<S> for ThrottleLayer {
+    type Service = ThrottleMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ThrottleMiddleware {
+            inner,
+            throttler: self.throttler.clone(),
+        }
+    }
+}
+
+/// Middleware structure
+#[derive(Clone)]
+pub struct ThrottleMiddleware<S> {
+    inner: S,
+    throttler: Option<Throttler>,
+}
+
+impl<S> Service<Request<Body>> for ThrottleMiddleware<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let mut inner = self.inner.clone();
+
+        // Swap to ensure we get the service that was ready and not the cloned one
+        swap(&mut inner, &mut self.inner);
+
+        if let Some(throttler) = &self.throttler {
+            let identity = req
+                .extensions()
+                .get::<AuthenticatedIdentity>()
+                .expect("throttle service missing authenticated identity");
+
+            let target = req
+                .headers()
+                .get("x-amz-target")
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+
+            if !throttler.is_allowed(&identity.access_key_id, target) {
+                tracing::debug!(access_key_id = %identity.access_key_id, %target, "request throttled");
+                return Box::pin(async { Ok(ThrottlingException.into_error_response()) });
+            }
+        }
+
+        Box::pin(inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{REFILL_WINDOW, Throttler};
+    use std::{collections::HashMap, time::Instant};
+
+    /// Tests that requests beyond the limit are throttled until the bucket refills
+    #[test]
+    fn test_throttled_until_refilled() {
+        let throttler = Throttler::new(HashMap::from([("target".to_string(), 1)]));
+        let now = Instant::now();
+
+        assert!(throttler.is_allowed_at("a", "target", now));
+        assert!(!throttler.is_allowed_at("a", "target", now));
+        assert!(throttler.is_allowed_at("b", "target", now));
+        assert!(throttler.is_allowed_at("a", "target", now + REFILL_WINDOW));
+    }
+
+    /// Tests that the buckets of access keys that stop making requests are forgotten
+    #[test]
+    fn test_idle_buckets_evicted() {
+        let throttler = Throttler::new(HashMap::from([("target".to_string(), 1)]));
+        let now = Instant::now();
+
+        assert!(throttler.is_allowed_at("a", "target", now));
+        assert!(throttler.is_allowed_at("b", "target", now));
+
+        let later = now + REFILL_WINDOW * 2;
+        assert!(throttler.is_allowed_at("c", "target", later));
+
+        let buckets = throttler.buckets.lock().unwrap();
+        assert_eq!(buckets.buckets.len(), 1);
+        assert!(
+            buckets
+                .buckets
+                .contains_key(&("c".to_string(), "target".to_string()))
+        );
+    }
+}
@@ -1,7 +1,10 @@
+use aws_lc_rs::digest::{SHA256, digest};
+use axum::http::HeaderMap;
 use thiserror::Error;
 
 /// Parsed AWS SigV4 header
 pub struct AwsSigV4Auth<'a> {
+    pub algorithm: SigningAlgorithm,
     pub signing_scope: SigningScope<'a>,
     pub signed_headers: Vec<&'a str>,
     pub signature: &'a str,
@@ -9,41 +12,163 @@ pub struct AwsSigV4Auth<'a> {
 
 #[derive(Debug, Error)]
 pub enum AuthHeaderError {
-    #[error("invalid header parts")]
+    #[error(
+        "Authorization header requires the signing algorithm followed by the 'Credential', \
+        'SignedHeaders' and 'Signature' parameters."
+    )]
     InvalidHeader,
 
-    #[error("unsupported algorithm, this implementation only supports AWS4-HMAC-SHA256")]
-    UnsupportedAlgorithm,
+    #[error(
+        "Unsupported AWS 'algorithm': '{0}', only 'AWS4-HMAC-SHA256' and \
+        'AWS4-ECDSA-P256-SHA256' are supported."
+    )]
+    UnsupportedAlgorithm(String),
 
-    #[error("invalid key value pair")]
-    InvalidKeyValue,
+    #[error("Authorization header parameter '{0}' must be in the format <key>=<value>.")]
+    InvalidKeyValue(String),
 
-    #[error("missing Credential")]
+    #[error("Authorization header requires 'Credential' parameter.")]
     MissingCredential,
 
-    #[error("missing SignedHeaders")]
+    #[error("Authorization header requires 'SignedHeaders' parameter.")]
     MissingSignedHeaders,
 
-    #[error("missing Signature")]
+    #[error("Authorization header requires 'Signature' parameter.")]
     MissingSignature,
 
-    #[error("invalid scope")]
-    InvalidScope,
+    #[error("Credential '{0}' is missing components of the credential scope.")]
+    InvalidScope(String),
+
+    #[error(
+        "AWS query-string parameters must include '{0}'. Re-examine the query-string parameters."
+    )]
+    MissingQueryParameter(&'static str),
+}
+
+/// Signed header with a value that isn't visible ASCII
+#[derive(Debug, Error)]
+#[error("Signed header '{0}' contains characters that are not visible ASCII.")]
+pub struct InvalidSignedHeader(pub String);
+
+/// Algorithm used to sign a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigningAlgorithm {
+    /// AWS4-HMAC-SHA256 (SigV4)
+    HmacSha256,
+    /// AWS4-ECDSA-P256-SHA256 (SigV4A)
+    EcdsaP256Sha256,
+}
+
+impl SigningAlgorithm {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "AWS4-HMAC-SHA256" => Some(Self::HmacSha256),
+            "AWS4-ECDSA-P256-SHA256" => Some(Self::EcdsaP256Sha256),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HmacSha256 => "AWS4-HMAC-SHA256",
+            Self::EcdsaP256Sha256 => "AWS4-ECDSA-P256-SHA256",
+        }
+    }
+}
+
+/// Parsed AWS SigV4 query string authentication used by presigned URLs
+pub struct AwsSigV4QueryAuth<'a> {
+    pub auth: AwsSigV4Auth<'a>,
+    pub amz_date: &'a str,
+    pub expires: &'a str,
+    pub security_token: Option<&'a str>,
+    /// Regions a SigV4A signature is valid for
+    pub region_set: Option<&'a str>,
+}
+
+/// Query parameters used for AWS SigV4 query string authentication, these are
+/// excluded from the query string when calculating the signature
+const AUTH_QUERY_PARAMS: &[&str] = &[
+    "X-Amz-Algorithm",
+    "X-Amz-Credential",
+    "X-Amz-Date",
+    "X-Amz-Expires",
+    "X-Amz-SignedHeaders",
+    "X-Amz-Signature",
+    "X-Amz-Security-Token",
+    "X-Amz-Region-Set",
+];
+
+/// Check if the decoded query `params` contain AWS SigV4 query string authentication
+pub fn has_auth_query(params: &[(String, String)]) -> bool {
+    params.iter().any(|(key, _)| key == "X-Amz-Algorithm")
+}
+
+/// Parse the decoded query `params` to extract the AWS SigV4 data
+pub fn parse_auth_query(
+    params: &[(String, String)],
+) -> Result<AwsSigV4QueryAuth<'_>, AuthHeaderError> {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let required_param =
+        |name: &'static str| param(name).ok_or(AuthHeaderError::MissingQueryParameter(name));
+
+    let algorithm = required_param("X-Amz-Algorithm")?;
+    let algorithm = SigningAlgorithm::parse(algorithm)
+        .ok_or_else(|| AuthHeaderError::UnsupportedAlgorithm(algorithm.to_string()))?;
+
+    let credential = required_param("X-Amz-Credential")?;
+    let signed_headers = required_param("X-Amz-SignedHeaders")?;
+    let signature = required_param("X-Amz-Signature")?;
+    let amz_date = required_param("X-Amz-Date")?;
+    let expires = required_param("X-Amz-Expires")?;
+    let security_token = param("X-Amz-Security-Token");
+    let region_set = param("X-Amz-Region-Set");
+
+    let signed_headers: Vec<&str> = signed_headers.split(';').collect();
+
+    let signing_scope = parse_algorithm_signing_scope(credential, algorithm)
+        .ok_or_else(|| AuthHeaderError::InvalidScope(credential.to_string()))?;
+
+    Ok(AwsSigV4QueryAuth {
+        auth: AwsSigV4Auth {
+            algorithm,
+            signing_scope,
+            signed_headers,
+            signature,
+        },
+        amz_date,
+        expires,
+        security_token,
+        region_set,
+    })
+}
+
+/// Remove the AWS SigV4 authentication parameters from the raw `query` string
+pub fn strip_auth_query(query: &str) -> String {
+    query
+        .split('&')
+        .filter(|pair| {
+            let key = pair.split_once('=').map_or(*pair, |(key, _)| key);
+            !pair.is_empty() && !AUTH_QUERY_PARAMS.contains(&key)
+        })
+        .collect::<Vec<_>>()
+        .join("&")
 }
 
 /// Parse the Authorization header value to extract the AWS SigV4 data
 pub fn parse_auth_header<'a>(header: &'a str) -> Result<AwsSigV4Auth<'a>, AuthHeaderError> {
     let mut parts = header.splitn(2, ' ');
 
-    // AWS4-HMAC-SHA256
-    let algorithm = parts
-        .next()
-        .ok_or(AuthHeaderError::InvalidHeader)?
-        .to_string();
-
-    if algorithm != "AWS4-HMAC-SHA256" {
-        return Err(AuthHeaderError::UnsupportedAlgorithm);
-    }
+    // AWS4-HMAC-SHA256 or AWS4-ECDSA-P256-SHA256
+    let algorithm = parts.next().ok_or(AuthHeaderError::InvalidHeader)?;
+    let algorithm = SigningAlgorithm::parse(algorithm)
+        .ok_or_else(|| AuthHeaderError::UnsupportedAlgorithm(algorithm.to_string()))?;
 
     let kv_string = parts.next().ok_or(AuthHeaderError::InvalidHeader)?;
 
@@ -52,9 +177,9 @@ pub fn parse_auth_header<'a>(header: &'a str) -> Result<AwsSigV4Auth<'a>, AuthHe
     let mut signature: Option<&str> = None;
 
     for kv in kv_string.split(", ") {
-        let mut split = kv.splitn(2, '=');
-        let key = split.next().ok_or(AuthHeaderError::InvalidKeyValue)?;
-        let value = split.next().ok_or(AuthHeaderError::InvalidKeyValue)?;
+        let (key, value) = kv
+            .split_once('=')
+            .ok_or_else(|| AuthHeaderError::InvalidKeyValue(kv.to_string()))?;
         match key {
             "Credential" => {
                 credential = Some(value);
@@ -76,9 +201,11 @@ pub fn parse_auth_header<'a>(header: &'a str) -> Result<AwsSigV4Auth<'a>, AuthHe
 
     let signed_headers: Vec<&str> = signed_headers.split(';').collect();
 
-    let signing_scope = parse_signing_scope(credential).ok_or(AuthHeaderError::InvalidScope)?;
+    let signing_scope = parse_algorithm_signing_scope(credential, algorithm)
+        .ok_or_else(|| AuthHeaderError::InvalidScope(credential.to_string()))?;
 
     Ok(AwsSigV4Auth {
+        algorithm,
         signing_scope,
         signed_headers,
         signature,
@@ -89,6 +216,8 @@ pub struct SigningScope<'a> {
     pub access_key_id: &'a str,
     #[allow(unused)]
     pub date_yyyymmdd: &'a str,
+    /// Region the request was signed for, SigV4A scopes don't include a region
+    /// so this is empty for SigV4A signatures
     pub region: &'a str,
     pub service: &'a str,
     pub aws4_request: &'a str,
@@ -110,3 +239,198 @@ pub fn parse_signing_scope(value: &str) -> Option<SigningScope<'_>> {
         aws4_request,
     })
 }
+
+/// Parse a SigV4A credential scope, these don't include the region as the
+/// regions are provided by the X-Amz-Region-Set instead
+pub fn parse_signing_scope_v4a(value: &str) -> Option<SigningScope<'_>> {
+    let mut parts = value.split('/');
+    let access_key_id = parts.next()?;
+    let date_yyyymmdd = parts.next()?;
+    let service = parts.next()?;
+    let aws4_request = parts.next()?;
+
+    Some(SigningScope {
+        access_key_id,
+        date_yyyymmdd,
+        region: "",
+        service,
+        aws4_request,
+    })
+}
+
+/// Parse the credential scope in the format used by the signing `algorithm`
+fn parse_algorithm_signing_scope(
+    value: &str,
+    algorithm: SigningAlgorithm,
+) -> Option<SigningScope<'_>> {
+    match algorithm {
+        SigningAlgorithm::HmacSha256 => parse_signing_scope(value),
+        SigningAlgorithm::EcdsaP256Sha256 => parse_signing_scope_v4a(value),
+    }
+}
+
+/// Create the canonical request used when signing a request, the `query` is
+/// the raw query string of the request (excluding the X-Amz-Signature parameter)
+/// and the `payload_hash` is the hex encoded SHA256 hash of the body or
+/// UNSIGNED-PAYLOAD
+pub fn canonical_request(
+    method: &str,
+    path: &str,
+    query: Option<&str>,
+    headers: &HeaderMap,
+    signed_headers: &[&str],
+    payload_hash: &str,
+) -> Result<String, InvalidSignedHeader> {
+    let path = uri_encode(&normalize_path(path), true);
+
+    let mut query: Vec<(String, String)> = query
+        .and_then(|query| serde_urlencoded::from_str::<Vec<(String, String)>>(query).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(key, _)| key != "X-Amz-Signature")
+        .map(|(key, value)| (uri_encode(&key, false), uri_encode(&value, false)))
+        .collect();
+    query.sort();
+
+    let query = query
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut canonical_headers = String::new();
+    for name in signed_headers {
+        let values = headers
+            .get_all(*name)
+            .iter()
+            .map(|value| {
+                value
+                    .to_str()
+                    .map(|value| value.split_whitespace().collect::<Vec<_>>().join(" "))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| InvalidSignedHeader(name.to_string()))?;
+
+        canonical_headers.push_str(name);
+        canonical_headers.push(':');
+        canonical_headers.push_str(&values.join(","));
+        canonical_headers.push('\n');
+    }
+
+    let signed_headers = signed_headers.join(";");
+
+    Ok(format!(
+        "{method}\n{path}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}"
+    ))
+}
+
+/// Create the string to sign for a `canonical_request` signed using the `algorithm`
+/// at the `amz_date` (YYYYMMDDTHHMMSSZ) within the credential `scope`
+pub fn string_to_sign(
+    algorithm: SigningAlgorithm,
+    amz_date: &str,
+    scope: &str,
+    canonical_request: &str,
+) -> String {
+    let canonical_request_hash = hex_encode(digest(&SHA256, canonical_request.as_bytes()).as_ref());
+    format!(
+        "{}\n{amz_date}\n{scope}\n{canonical_request_hash}",
+        algorithm.as_str()
+    )
+}
+
+/// Hex encode the `bytes` using lowercase characters
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Remove the dot segments from a URI `path`, empty paths are normalized to "/"
+fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+
+    for segment in path.split('/').skip(1) {
+        match segment {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    format!("/{}", segments.join("/"))
+}
+
+/// URI encode a `value` as required by SigV4, only unreserved characters are left
+/// unencoded (and forward slashes when encoding a `path`)
+fn uri_encode(value: &str, path: bool) -> String {
+    let mut output = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || matches!(byte, b'-' | b'_' | b'.' | b'~')
+            || (path && byte == b'/')
+        {
+            output.push(byte as char);
+        } else {
+            output.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::canonical_request;
+    use axum::http::{HeaderMap, HeaderValue};
+
+    /// Tests the canonical request matches the get-vanilla-query-order-key-case
+    /// example from the AWS SigV4 test suite
+    #[test]
+    fn test_canonical_request() {
+        let mut headers = HeaderMap::new();
+        headers.insert("host", HeaderValue::from_static("example.amazonaws.com"));
+        headers.insert("x-amz-date", HeaderValue::from_static("20150830T123600Z"));
+
+        let canonical_request = canonical_request(
+            "GET",
+            "/",
+            Some("Param2=value2&Param1=value1"),
+            &headers,
+            &["host", "x-amz-date"],
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        )
+        .unwrap();
+
+        assert_eq!(
+            canonical_request,
+            concat!(
+                "GET\n",
+                "/\n",
+                "Param1=value1&Param2=value2\n",
+                "host:example.amazonaws.com\n",
+                "x-amz-date:20150830T123600Z\n",
+                "\n",
+                "host;x-amz-date\n",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            )
+        );
+    }
+
+    /// Tests that dot segments are removed and reserved characters are encoded
+    #[test]
+    fn test_canonical_request_path() {
+        let canonical_request = canonical_request(
+            "GET",
+            "/a/./b/../c d",
+            None,
+            &HeaderMap::new(),
+            &[],
+            "UNSIGNED-PAYLOAD",
+        )
+        .unwrap();
+
+        assert!(canonical_request.starts_with("GET\n/a/c%20d\n\n"));
+    }
+}
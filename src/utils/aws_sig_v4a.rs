@@ -0,0 +1,184 @@
+//! Verification of AWS SigV4A (AWS4-ECDSA-P256-SHA256) signatures, the ECDSA
+//! key of an access key is derived from its secret access key in the same
+//! way as the AWS SDKs
+
+use aws_lc_rs::{
+    hmac,
+    signature::{
+        ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_ASN1_SIGNING, EcdsaKeyPair, KeyPair,
+        UnparsedPublicKey,
+    },
+};
+use thiserror::Error;
+
+/// Name of the SigV4A signing algorithm, used as the KDF label
+const ALGORITHM: &str = "AWS4-ECDSA-P256-SHA256";
+
+/// Order of the P-256 curve minus two, the derived private key must be
+/// within the range 1 to N-1
+const P256_ORDER_MINUS_TWO: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x4f,
+];
+
+/// DER encoded prefix of an RFC 5915 ECPrivateKey for a P-256 private key,
+/// followed by the 32 byte private key and [EC_PRIVATE_KEY_DER_SUFFIX]
+const EC_PRIVATE_KEY_DER_PREFIX: [u8; 7] = [0x30, 0x31, 0x02, 0x01, 0x01, 0x04, 0x20];
+
+/// DER encoded named curve parameters (prime256v1) of an RFC 5915 ECPrivateKey
+const EC_PRIVATE_KEY_DER_SUFFIX: [u8; 12] = [
+    0xa0, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07,
+];
+
+#[derive(Debug, Error)]
+pub enum SigV4AError {
+    #[error("failed to derive signing key")]
+    KeyDerivation,
+
+    #[error("signature is not valid hex")]
+    InvalidSignatureEncoding,
+}
+
+/// Derive the ECDSA P-256 key pair for an access key from its `access_key_id`
+/// and `secret_access_key`.
+///
+/// The private key is derived using the NIST SP 800-108 HMAC-SHA256 counter mode
+/// KDF keyed with "AWS4A" followed by the secret, incrementing the counter until
+/// the derived value is within the range of valid private keys
+pub fn derive_key_pair(
+    access_key_id: &str,
+    secret_access_key: &str,
+) -> Result<EcdsaKeyPair, SigV4AError> {
+    let input_key = format!("AWS4A{secret_access_key}");
+    let key = hmac::Key::new(hmac::HMAC_SHA256, input_key.as_bytes());
+
+    for counter in 1..=u8::MAX {
+        // Fixed input: i || Label || 0x00 || Context (access key ID || counter) || L
+        let mut input = Vec::with_capacity(ALGORITHM.len() + access_key_id.len() + 10);
+        input.extend_from_slice(&1u32.to_be_bytes());
+        input.extend_from_slice(ALGORITHM.as_bytes());
+        input.push(0);
+        input.extend_from_slice(access_key_id.as_bytes());
+        input.push(counter);
+        input.extend_from_slice(&256u32.to_be_bytes());
+
+        let tag = hmac::sign(&key, &input);
+        let mut candidate: [u8; 32] = tag.as_ref()[..32]
+            .try_into()
+            .map_err(|_| SigV4AError::KeyDerivation)?;
+
+        // Big endian byte arrays of the same length compare the same as the values
+        if candidate > P256_ORDER_MINUS_TWO {
+            continue;
+        }
+
+        // Private key is the candidate plus one
+        for byte in candidate.iter_mut().rev() {
+            let (value, overflow) = byte.overflowing_add(1);
+            *byte = value;
+            if !overflow {
+                break;
+            }
+        }
+
+        let mut private_key_der = Vec::with_capacity(
+            EC_PRIVATE_KEY_DER_PREFIX.len() + candidate.len() + EC_PRIVATE_KEY_DER_SUFFIX.len(),
+        );
+        private_key_der.extend_from_slice(&EC_PRIVATE_KEY_DER_PREFIX);
+        private_key_der.extend_from_slice(&candidate);
+        private_key_der.extend_from_slice(&EC_PRIVATE_KEY_DER_SUFFIX);
+
+        return EcdsaKeyPair::from_private_key_der(
+            &ECDSA_P256_SHA256_ASN1_SIGNING,
+            &private_key_der,
+        )
+        .map_err(|_| SigV4AError::KeyDerivation);
+    }
+
+    Err(SigV4AError::KeyDerivation)
+}
+
+/// Verify the hex encoded DER `signature` of the `string_to_sign` was created
+/// using the private key of the `key_pair`
+pub fn verify_signature(
+    key_pair: &EcdsaKeyPair,
+    string_to_sign: &str,
+    signature: &str,
+) -> Result<bool, SigV4AError> {
+    let signature = hex_decode(signature).ok_or(SigV4AError::InvalidSignatureEncoding)?;
+    let public_key = UnparsedPublicKey::new(
+        &ECDSA_P256_SHA256_ASN1,
+        key_pair.public_key().as_ref().to_vec(),
+    );
+
+    Ok(public_key
+        .verify(string_to_sign.as_bytes(), &signature)
+        .is_ok())
+}
+
+/// Decode a hex encoded `value`
+fn hex_decode(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(value.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{derive_key_pair, hex_decode, verify_signature};
+    use crate::utils::aws_sig_v4::{SigningAlgorithm, hex_encode, string_to_sign};
+    use aws_lc_rs::{
+        rand::SystemRandom,
+        signature::{ECDSA_P256_SHA256_ASN1_SIGNING, EcdsaKeyPair, KeyPair},
+    };
+
+    /// Tests that the derived key pair matches the public key from the AWS SigV4A test suite
+    #[test]
+    fn test_derive_key_pair() {
+        let key_pair =
+            derive_key_pair("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY").unwrap();
+
+        let expected = concat!(
+            "04",
+            "b6618f6a65740a99e650b33b6b4b5bd0d43b176d721a3edfea7e7d2d56d936b1",
+            "865ed22a7eadc9c5cb9d2cbaca1b3699139fedc5043dc6661864218330c8e518"
+        );
+        assert_eq!(hex_encode(key_pair.public_key().as_ref()), expected);
+    }
+
+    /// Tests that a signature created by the derived key is verified while
+    /// signatures from other keys are not
+    #[test]
+    fn test_verify_signature() {
+        let key_pair = derive_key_pair("AKIDEXAMPLE", "secret").unwrap();
+        let string_to_sign = string_to_sign(
+            SigningAlgorithm::EcdsaP256Sha256,
+            "20250101T000000Z",
+            "20250101/secretsmanager/aws4_request",
+            "canonical",
+        );
+
+        let rng = SystemRandom::new();
+        let signature = key_pair.sign(&rng, string_to_sign.as_bytes()).unwrap();
+        let signature = hex_encode(signature.as_ref());
+        assert!(verify_signature(&key_pair, &string_to_sign, &signature).unwrap());
+
+        let other = EcdsaKeyPair::generate(&ECDSA_P256_SHA256_ASN1_SIGNING).unwrap();
+        let signature = other.sign(&rng, string_to_sign.as_bytes()).unwrap();
+        let signature = hex_encode(signature.as_ref());
+        assert!(!verify_signature(&key_pair, &string_to_sign, &signature).unwrap());
+    }
+
+    /// Tests hex values are decoded
+    #[test]
+    fn test_hex_decode() {
+        assert_eq!(hex_decode("00ff10"), Some(vec![0x00, 0xff, 0x10]));
+        assert_eq!(hex_decode("0"), None);
+        assert_eq!(hex_decode("zz"), None);
+    }
+}
@@ -29,9 +29,15 @@ pub fn parse_amz_date(value: &str) -> Result<DateTime<Utc>, AmzDateError> {
     Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
 }
 
-/// Convert a chrono Utc date time to a SystemTime
+/// Formats the date in the X-Amz-Date header format (YYYYMMDDTHHMMSSZ)
+pub fn format_amz_date(value: DateTime<Utc>) -> String {
+    value.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Convert a chrono Utc date time to a SystemTime, dates before the unix epoch
+/// are not supported
 pub fn chrono_to_system_time(value: DateTime<Utc>) -> Option<SystemTime> {
-    let duration_since_epoch = value.timestamp() as u64;
+    let duration_since_epoch = u64::try_from(value.timestamp()).ok()?;
     let nanos = value.timestamp_subsec_nanos();
     let duration = Duration::new(duration_since_epoch, nanos);
     SystemTime::UNIX_EPOCH.checked_add(duration)
@@ -115,4 +121,12 @@ mod tests {
         let expected = dt.timestamp() as f64 + 0.987;
         assert!((result - expected).abs() < 1e-9);
     }
+
+    #[test]
+    fn test_format_amz_date() {
+        let dt = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let result = format_amz_date(dt);
+        assert_eq!(result, "20150830T123600Z");
+        assert_eq!(parse_amz_date(&result).unwrap(), dt);
+    }
 }
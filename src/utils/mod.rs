@@ -1,4 +1,6 @@
 pub mod aws_sig_v4;
+pub mod aws_sig_v4a;
 pub mod date;
 pub mod filter;
+pub mod schedule;
 pub mod string;
@@ -0,0 +1,466 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeDelta, Timelike, Utc};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Minimum number of hours between rotations for a rate expression
+const MIN_RATE_HOURS: i64 = 4;
+
+/// Maximum number of days between rotations for a rate expression
+const MAX_RATE_DAYS: i64 = 1000;
+
+/// Maximum number of hours for a rotation window
+const MAX_WINDOW_HOURS: i64 = 24;
+
+/// Range of years supported by cron expressions
+const CRON_YEARS: (u32, u32) = (1970, 2199);
+
+/// Number of days to search ahead for the next cron match before giving up
+const CRON_SEARCH_DAYS: u64 = 366 * 10;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ScheduleExpressionError {
+    #[error("schedule expression must be either rate() or cron()")]
+    UnknownExpression,
+
+    #[error("invalid rate expression")]
+    InvalidRate,
+
+    #[error("rate expression is outside the allowed range")]
+    RateOutOfRange,
+
+    #[error("cron expression must have 6 fields")]
+    InvalidFieldCount,
+
+    #[error("invalid cron field \"{0}\"")]
+    InvalidField(String),
+
+    #[error("cron expression must use '?' for either the day-of-month or day-of-week")]
+    InvalidDayFields,
+}
+
+/// Rotation schedule expression, either a `rate()` or `cron()` expression
+///
+/// https://docs.aws.amazon.com/secretsmanager/latest/userguide/rotate-secrets_schedule.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleExpression {
+    /// Rotate at a fixed rate after the last rotation
+    Rate(TimeDelta),
+    /// Rotate at the times matching the cron expression
+    Cron(CronExpression),
+}
+
+impl ScheduleExpression {
+    /// Get the next scheduled time after `from`
+    pub fn next_after(&self, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            ScheduleExpression::Rate(delta) => from.checked_add_signed(*delta),
+            ScheduleExpression::Cron(cron) => cron.next_after(from),
+        }
+    }
+}
+
+impl FromStr for ScheduleExpression {
+    type Err = ScheduleExpressionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Some(value) = s.strip_prefix("rate(").and_then(|s| s.strip_suffix(')')) {
+            return parse_rate(value).map(ScheduleExpression::Rate);
+        }
+
+        if let Some(value) = s.strip_prefix("cron(").and_then(|s| s.strip_suffix(')')) {
+            return value.parse().map(ScheduleExpression::Cron);
+        }
+
+        Err(ScheduleExpressionError::UnknownExpression)
+    }
+}
+
+/// Parse the value of a `rate(N hours)` or `rate(N days)` expression
+fn parse_rate(value: &str) -> Result<TimeDelta, ScheduleExpressionError> {
+    let (amount, unit) = value
+        .trim()
+        .split_once(' ')
+        .ok_or(ScheduleExpressionError::InvalidRate)?;
+
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| ScheduleExpressionError::InvalidRate)?;
+
+    match unit.trim() {
+        "hour" | "hours" => {
+            if !(MIN_RATE_HOURS..24).contains(&amount) {
+                return Err(ScheduleExpressionError::RateOutOfRange);
+            }
+
+            Ok(TimeDelta::hours(amount))
+        }
+        "day" | "days" => {
+            if !(1..=MAX_RATE_DAYS).contains(&amount) {
+                return Err(ScheduleExpressionError::RateOutOfRange);
+            }
+
+            Ok(TimeDelta::days(amount))
+        }
+        _ => Err(ScheduleExpressionError::InvalidRate),
+    }
+}
+
+/// Parse the `Duration` of a rotation window (e.g "3h") into a [TimeDelta]
+pub fn parse_rotation_window(value: &str) -> Option<TimeDelta> {
+    let hours: i64 = value.strip_suffix('h')?.parse().ok()?;
+
+    if !(1..=MAX_WINDOW_HOURS).contains(&hours) {
+        return None;
+    }
+
+    Some(TimeDelta::hours(hours))
+}
+
+/// AWS style cron expression with six fields:
+///
+/// `Minutes Hours Day-of-month Month Day-of-week Year`
+///
+/// Supports `*`, `?`, values, ranges (`1-5`), lists (`1,15`), increments (`0/15`)
+/// and month / day names. Day of week values are 1-7 where 1 is Sunday
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpression {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    /// Allowed days of the month, [None] when the field is '?'
+    days_of_month: Option<Vec<u32>>,
+    months: Vec<u32>,
+    /// Allowed days of the week (1 = Sunday), [None] when the field is '?'
+    days_of_week: Option<Vec<u32>>,
+    years: Vec<u32>,
+}
+
+impl CronExpression {
+    /// Get the next time matching the expression strictly after `from`
+    pub fn next_after(&self, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = from
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(TimeDelta::minutes(1))?;
+        let start_date = start.date_naive();
+
+        for offset in 0..CRON_SEARCH_DAYS {
+            let date = start_date.checked_add_days(Days::new(offset))?;
+
+            if !self.matches_date(date) {
+                continue;
+            }
+
+            for &hour in &self.hours {
+                for &minute in &self.minutes {
+                    let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+                    let value = date.and_time(time).and_utc();
+
+                    if value >= start {
+                        return Some(value);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Check if the provided date matches the date fields of the expression
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let year = match u32::try_from(date.year()) {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+
+        if !self.years.contains(&year) || !self.months.contains(&date.month()) {
+            return false;
+        }
+
+        if let Some(days_of_month) = &self.days_of_month
+            && !days_of_month.contains(&date.day())
+        {
+            return false;
+        }
+
+        if let Some(days_of_week) = &self.days_of_week
+            && !days_of_week.contains(&date.weekday().number_from_sunday())
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+impl FromStr for CronExpression {
+    type Err = ScheduleExpressionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week, years] = fields.as_slice() else {
+            return Err(ScheduleExpressionError::InvalidFieldCount);
+        };
+
+        // Exactly one of the day fields must be '?'
+        let (days_of_month, days_of_week) = match (*days_of_month, *days_of_week) {
+            ("?", "?") => return Err(ScheduleExpressionError::InvalidDayFields),
+            ("?", days_of_week) => (None, Some(parse_field(days_of_week, 1, 7, &DAY_NAMES)?)),
+            (days_of_month, "?") => (Some(parse_field(days_of_month, 1, 31, &[])?), None),
+            _ => return Err(ScheduleExpressionError::InvalidDayFields),
+        };
+
+        Ok(CronExpression {
+            minutes: parse_field(minutes, 0, 59, &[])?,
+            hours: parse_field(hours, 0, 23, &[])?,
+            days_of_month,
+            months: parse_field(months, 1, 12, &MONTH_NAMES)?,
+            days_of_week,
+            years: parse_field(years, CRON_YEARS.0, CRON_YEARS.1, &[])?,
+        })
+    }
+}
+
+/// Parse a cron field into the sorted list of values it allows within `min..=max`,
+/// `names` are the optional names for the values starting at `min`
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<Vec<u32>, ScheduleExpressionError> {
+    let invalid = || ScheduleExpressionError::InvalidField(field.to_string());
+
+    let parse_value = |value: &str| -> Result<u32, ScheduleExpressionError> {
+        let value = match names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(value))
+        {
+            Some(index) => min + index as u32,
+            None => value.parse().map_err(|_| invalid())?,
+        };
+
+        if !(min..=max).contains(&value) {
+            return Err(invalid());
+        }
+
+        Ok(value)
+    };
+
+    let mut values = Vec::new();
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| invalid())?;
+                if step == 0 {
+                    return Err(invalid());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (parse_value(start)?, parse_value(end)?),
+                // Increments from a single value continue until the maximum
+                None if step > 1 => (parse_value(range)?, max),
+                None => {
+                    let value = parse_value(range)?;
+                    (value, value)
+                }
+            },
+        };
+
+        if start > end {
+            return Err(invalid());
+        }
+
+        values.extend((start..=end).step_by(step as usize));
+    }
+
+    values.sort_unstable();
+    values.dedup();
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_rate_days() {
+        let expression: ScheduleExpression = "rate(10 days)".parse().unwrap();
+        assert_eq!(expression, ScheduleExpression::Rate(TimeDelta::days(10)));
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 11, 12, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_rate_hours() {
+        let expression: ScheduleExpression = "rate(4 hours)".parse().unwrap();
+        assert_eq!(expression, ScheduleExpression::Rate(TimeDelta::hours(4)));
+    }
+
+    #[test]
+    fn test_rate_singular() {
+        let expression: ScheduleExpression = "rate(1 day)".parse().unwrap();
+        assert_eq!(expression, ScheduleExpression::Rate(TimeDelta::days(1)));
+    }
+
+    #[test]
+    fn test_rate_out_of_range() {
+        assert_eq!(
+            "rate(1 hours)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::RateOutOfRange)
+        );
+        assert_eq!(
+            "rate(1001 days)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::RateOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_rate_invalid() {
+        assert_eq!(
+            "rate(10 weeks)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::InvalidRate)
+        );
+        assert_eq!(
+            "rate(days)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::InvalidRate)
+        );
+    }
+
+    #[test]
+    fn test_unknown_expression() {
+        assert_eq!(
+            "every(10 days)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::UnknownExpression)
+        );
+    }
+
+    #[test]
+    fn test_cron_daily() {
+        let expression: ScheduleExpression = "cron(0 16 * * ? *)".parse().unwrap();
+
+        // Before the time on the same day
+        let from = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 1, 16, 0, 0).unwrap());
+
+        // Exactly at the time should move to the next day
+        let from = Utc.with_ymd_and_hms(2025, 1, 1, 16, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 2, 16, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_cron_days_of_month() {
+        let expression: ScheduleExpression = "cron(0 8 1,15 * ? *)".parse().unwrap();
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 15, 8, 0, 0).unwrap());
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 20, 0, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 2, 1, 8, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_cron_days_of_week() {
+        // Every monday at 10:30 (2025-01-01 is a wednesday)
+        let expression: ScheduleExpression = "cron(30 10 ? * MON *)".parse().unwrap();
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 6, 10, 30, 0).unwrap());
+
+        // Numeric day of week where 2 is monday
+        let expression: ScheduleExpression = "cron(30 10 ? * 2 *)".parse().unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 6, 10, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_cron_increments() {
+        let expression: ScheduleExpression = "cron(0 0/6 * * ? *)".parse().unwrap();
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 1, 7, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap());
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 1, 19, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_cron_month_names() {
+        let expression: ScheduleExpression = "cron(0 0 1 JUN-AUG ? *)".parse().unwrap();
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_cron_year() {
+        let expression: ScheduleExpression = "cron(0 0 1 1 ? 2030)".parse().unwrap();
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let next = expression.next_after(from).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap());
+
+        // No more matches after the year
+        let from = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(expression.next_after(from), None);
+    }
+
+    #[test]
+    fn test_cron_invalid() {
+        assert_eq!(
+            "cron(0 0 * *)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::InvalidFieldCount)
+        );
+        assert_eq!(
+            "cron(0 0 * * * *)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::InvalidDayFields)
+        );
+        assert_eq!(
+            "cron(0 0 ? * ? *)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::InvalidDayFields)
+        );
+        assert_eq!(
+            "cron(60 0 * * ? *)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::InvalidField("60".to_string()))
+        );
+        assert_eq!(
+            "cron(0 0 L * ? *)".parse::<ScheduleExpression>(),
+            Err(ScheduleExpressionError::InvalidField("L".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_rotation_window() {
+        assert_eq!(parse_rotation_window("3h"), Some(TimeDelta::hours(3)));
+        assert_eq!(parse_rotation_window("24h"), Some(TimeDelta::hours(24)));
+        assert_eq!(parse_rotation_window("0h"), None);
+        assert_eq!(parse_rotation_window("25h"), None);
+        assert_eq!(parse_rotation_window("3d"), None);
+    }
+}
@@ -1,16 +1,32 @@
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::SystemTime};
 
-use axum::{Extension, Router, routing::post_service};
+use axum::{
+    Extension, Router,
+    routing::{get, post_service},
+};
 use loker::{
+    admin::{SnapshotStore, admin_router},
+    arn::ArnConfig,
     database::{DbHandle, initialize_database},
+    fault::{FaultLayer, FaultStore},
     handlers::{self},
-    middleware::aws_sig_v4::AwsSigV4AuthLayer,
+    metrics::{Metrics, MetricsLayer, metrics_handler},
+    middleware::aws_sig_v4::{AccessKey, AwsSigV4AuthLayer},
+    policy::{PolicyDocument, PolicyKind},
+    rotation::RotationRunner,
+    sts::StsLayer,
+    throttle::ThrottleLayer,
 };
 
 use aws_config::{BehaviorVersion, Region, SdkConfig};
 use aws_sdk_secretsmanager::config::{Credentials, SharedCredentialsProvider};
+use aws_sigv4::{
+    http_request::{SignableBody, SignableRequest, SigningSettings, sign},
+    sign::v4::SigningParams,
+};
 use tokio::task::AbortHandle;
 use tokio_rusqlite::Connection;
+use tower::Layer;
 
 /// Create an AWS sdk config for use in tests
 #[allow(dead_code)]
@@ -25,7 +41,7 @@ pub fn test_sdk_config(endpoint_url: &str, credentials: Credentials) -> SdkConfi
 
 #[allow(dead_code)]
 pub struct TestServer {
-    pub db: Connection,
+    pub db: DbHandle,
     pub abort_handle: AbortHandle,
 }
 
@@ -43,24 +59,100 @@ pub async fn test_memory_database() -> DbHandle {
         initialize_database(db).unwrap();
     })
     .await;
-    db
+    DbHandle::from(db)
 }
 
 #[allow(dead_code)]
 pub async fn start_test_server(
-    db: Connection,
+    db: DbHandle,
+    credentials: Credentials,
+) -> (SocketAddr, AbortHandle) {
+    start_test_server_with_rotation(db, credentials, RotationRunner::default()).await
+}
+
+#[allow(dead_code)]
+pub async fn start_test_server_with_rotation(
+    db: DbHandle,
+    credentials: Credentials,
+    rotation: RotationRunner,
+) -> (SocketAddr, AbortHandle) {
+    start_test_server_with_layer(
+        db,
+        AwsSigV4AuthLayer::new(credentials, &ArnConfig::default()),
+        rotation,
+    )
+    .await
+}
+
+#[allow(dead_code)]
+pub async fn start_test_server_with_layer(
+    db: DbHandle,
+    auth_layer: AwsSigV4AuthLayer,
+    rotation: RotationRunner,
+) -> (SocketAddr, AbortHandle) {
+    start_test_server_with_arn_config(db, auth_layer, rotation, ArnConfig::default()).await
+}
+
+#[allow(dead_code)]
+pub async fn start_test_server_with_arn_config(
+    db: DbHandle,
+    auth_layer: AwsSigV4AuthLayer,
+    rotation: RotationRunner,
+    arn: ArnConfig,
+) -> (SocketAddr, AbortHandle) {
+    start_test_server_with_options(db, auth_layer, rotation, arn, ThrottleLayer::new(None)).await
+}
+
+#[allow(dead_code)]
+pub async fn start_test_server_with_throttle(
+    db: DbHandle,
     credentials: Credentials,
+    throttle_limits: HashMap<String, u32>,
+) -> (SocketAddr, AbortHandle) {
+    start_test_server_with_options(
+        db,
+        AwsSigV4AuthLayer::new(credentials, &ArnConfig::default()),
+        RotationRunner::default(),
+        ArnConfig::default(),
+        ThrottleLayer::new(Some(throttle_limits)),
+    )
+    .await
+}
+
+async fn start_test_server_with_options(
+    db: DbHandle,
+    auth_layer: AwsSigV4AuthLayer,
+    rotation: RotationRunner,
+    arn: ArnConfig,
+    throttle_layer: ThrottleLayer,
 ) -> (SocketAddr, AbortHandle) {
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
     let server_address = listener.local_addr().unwrap();
 
+    let snapshots = SnapshotStore::create(&db, None).await.unwrap();
+    let faults = FaultStore::default();
+    let metrics = Metrics::default();
+
     let abort_handle = tokio::spawn(async move {
         let handlers = handlers::create_handlers();
-        let handlers_service = handlers.into_service();
+        let metrics_layer = MetricsLayer::new(metrics.clone(), handlers.targets());
+        let handlers_service = FaultLayer::new(faults.clone()).layer(handlers.into_service());
+        let handlers_service = throttle_layer.layer(handlers_service);
+        let access_keys = auth_layer.access_keys();
         let app = Router::new()
             .route_service("/", post_service(handlers_service))
-            .layer(AwsSigV4AuthLayer::new(credentials))
-            .layer(Extension(db));
+            .merge(admin_router())
+            .layer(StsLayer)
+            .layer(auth_layer.with_metrics(metrics.clone()))
+            .layer(metrics_layer)
+            .route("/metrics", get(metrics_handler))
+            .layer(Extension(access_keys))
+            .layer(Extension(arn))
+            .layer(Extension(db))
+            .layer(Extension(rotation))
+            .layer(Extension(snapshots))
+            .layer(Extension(faults))
+            .layer(Extension(metrics));
 
         axum::serve(listener, app).await.unwrap();
     })
@@ -81,3 +173,93 @@ pub async fn test_server() -> (aws_sdk_secretsmanager::Client, TestServer) {
 
     (client, TestServer { abort_handle, db })
 }
+
+/// Create a test server where requests are made by an identity with the
+/// provided identity `policy`
+#[allow(dead_code)]
+pub async fn test_server_with_identity_policy(
+    policy: &str,
+) -> (aws_sdk_secretsmanager::Client, TestServer) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let policy = PolicyDocument::parse(policy, PolicyKind::Identity).unwrap();
+    let auth_layer = AwsSigV4AuthLayer::from_access_keys([AccessKey {
+        policy: Some(Arc::new(policy)),
+        ..AccessKey::new(credentials.clone(), &ArnConfig::default())
+    }]);
+
+    let (server_address, abort_handle) =
+        start_test_server_with_layer(db.clone(), auth_layer, RotationRunner::default()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    (client, TestServer { abort_handle, db })
+}
+
+/// Make a request to the admin route at `path` signed using the `credentials`
+#[allow(dead_code)]
+pub async fn admin_request(
+    server_address: SocketAddr,
+    credentials: &Credentials,
+    method: reqwest::Method,
+    path: &str,
+    body: &[u8],
+) -> reqwest::Response {
+    let client = reqwest::Client::new();
+    let request = signed_admin_request(&client, server_address, credentials, method, path, body);
+    client.execute(request).await.unwrap()
+}
+
+/// Create a request to the admin route at `path` signed using the `credentials`
+#[allow(dead_code)]
+pub fn signed_admin_request(
+    client: &reqwest::Client,
+    server_address: SocketAddr,
+    credentials: &Credentials,
+    method: reqwest::Method,
+    path: &str,
+    body: &[u8],
+) -> reqwest::Request {
+    let url = format!("http://{server_address}{path}");
+
+    let identity = credentials.clone().into();
+    let signing_params = SigningParams::builder()
+        .identity(&identity)
+        .region("us-east-1")
+        .name("secretsmanager")
+        .time(SystemTime::now())
+        .settings(SigningSettings::default())
+        .build()
+        .unwrap()
+        .into();
+
+    let signable_request = SignableRequest::new(
+        method.as_str(),
+        &url,
+        std::iter::empty(),
+        SignableBody::Bytes(body),
+    )
+    .unwrap();
+
+    let (signing_instructions, _signature) = sign(signable_request, &signing_params)
+        .unwrap()
+        .into_parts();
+
+    let mut request = client.request(method, &url).body(body.to_vec());
+
+    for (name, value) in signing_instructions.headers() {
+        request = request.header(name, value);
+    }
+
+    request.build().unwrap()
+}
+
+/// Get the error type of an error response
+#[allow(dead_code)]
+pub fn error_type(response: &reqwest::Response) -> Option<&str> {
+    response
+        .headers()
+        .get("x-amzn-errortype")
+        .and_then(|value| value.to_str().ok())
+}
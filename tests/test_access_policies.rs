@@ -0,0 +1,314 @@
+use aws_sdk_secretsmanager::{
+    error::{ProvideErrorMetadata, SdkError},
+    types::Tag,
+};
+
+use loker::database::secrets::put_secret_resource_policy;
+
+use crate::common::{test_server, test_server_with_identity_policy};
+
+mod common;
+
+/// Identity policy that can create, describe and batch get any secret but can only get the
+/// value of secrets with a "team" tag of "alpha"
+const TAG_CONDITION_POLICY: &str = r#"{
+    "Version": "2012-10-17",
+    "Statement": [
+        {
+            "Effect": "Allow",
+            "Action": [
+                "secretsmanager:CreateSecret",
+                "secretsmanager:Describe*",
+                "secretsmanager:BatchGetSecretValue"
+            ],
+            "Resource": "*"
+        },
+        {
+            "Effect": "Allow",
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*",
+            "Condition": {
+                "StringEquals": { "secretsmanager:ResourceTag/team": "alpha" }
+            }
+        }
+    ]
+}"#;
+
+/// Identity policy that can do anything to secrets prefixed with "app/" except
+/// get the value of secrets prefixed with "app/restricted"
+const RESOURCE_WILDCARD_POLICY: &str = r#"{
+    "Version": "2012-10-17",
+    "Statement": [
+        {
+            "Effect": "Allow",
+            "Action": "secretsmanager:*",
+            "Resource": "arn:aws:secretsmanager:*:*:secret:app/*"
+        },
+        {
+            "Effect": "Deny",
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "arn:aws:secretsmanager:*:*:secret:app/restricted*"
+        }
+    ]
+}"#;
+
+/// Resource policy that denies everyone from getting the secret value
+const DENY_RESOURCE_POLICY: &str = r#"{
+    "Version": "2012-10-17",
+    "Statement": [
+        {
+            "Effect": "Deny",
+            "Principal": { "AWS": "*" },
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*"
+        }
+    ]
+}"#;
+
+/// Assert that the error is a service error with the AccessDeniedException code
+fn assert_access_denied<E: ProvideErrorMetadata + std::fmt::Debug, R: std::fmt::Debug>(
+    error: SdkError<E, R>,
+) {
+    let error = match error {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    assert_eq!(error.err().code(), Some("AccessDeniedException"));
+}
+
+/// Tests that an explicit deny in a resource policy prevents the action
+#[tokio::test]
+async fn test_resource_policy_explicit_deny() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(DENY_RESOURCE_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    let get_err = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert_access_denied(get_err);
+
+    // Actions not covered by the policy are still allowed
+    client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that a stored resource policy which can't be evaluated fails the request
+/// rather than being ignored
+#[tokio::test]
+async fn test_invalid_stored_resource_policy_error() {
+    let (client, server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Policies using unsupported operators can't be put through the API
+    let arn = create_response.arn().unwrap().to_string();
+    let policy = DENY_RESOURCE_POLICY.replace(
+        r#""Resource": "*""#,
+        r#""Resource": "*", "Condition": { "NumericLessThan": { "aws:MultiFactorAuthAge": "3600" } }"#,
+    );
+    server
+        .db
+        .call(move |db| put_secret_resource_policy(db, &arn, &policy))
+        .await
+        .unwrap();
+
+    let get_err = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let get_err = match get_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    assert_eq!(get_err.err().code(), Some("InternalServiceError"));
+}
+
+/// Tests that actions not allowed by the identity policy are implicitly denied
+#[tokio::test]
+async fn test_identity_policy_implicit_deny() {
+    let (client, _server) = test_server_with_identity_policy(TAG_CONDITION_POLICY).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let delete_err = client
+        .delete_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert_access_denied(delete_err);
+}
+
+/// Tests that resource tag conditions in the identity policy are evaluated
+/// against the tags of the secret
+#[tokio::test]
+async fn test_identity_policy_resource_tag_condition() {
+    let (client, _server) = test_server_with_identity_policy(TAG_CONDITION_POLICY).await;
+
+    client
+        .create_secret()
+        .name("alpha")
+        .secret_string("test")
+        .tags(Tag::builder().key("team").value("alpha").build())
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .create_secret()
+        .name("beta")
+        .secret_string("test")
+        .tags(Tag::builder().key("team").value("beta").build())
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("alpha")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+
+    let get_err = client
+        .get_secret_value()
+        .secret_id("beta")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert_access_denied(get_err);
+}
+
+/// Tests that action and resource wildcards are matched and that explicit denies
+/// take priority over allows
+#[tokio::test]
+async fn test_identity_policy_resource_wildcard() {
+    let (client, _server) = test_server_with_identity_policy(RESOURCE_WILDCARD_POLICY).await;
+
+    client
+        .create_secret()
+        .name("app/test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .create_secret()
+        .name("app/restricted")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .get_secret_value()
+        .secret_id("app/test")
+        .send()
+        .await
+        .unwrap();
+
+    let get_err = client
+        .get_secret_value()
+        .secret_id("app/restricted")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert_access_denied(get_err);
+
+    let create_err = client
+        .create_secret()
+        .name("other")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert_access_denied(create_err);
+}
+
+/// Tests that secrets denied to the caller are reported as errors by
+/// BatchGetSecretValue rather than failing the whole request
+#[tokio::test]
+async fn test_batch_get_secret_value_access_denied() {
+    let (client, _server) = test_server_with_identity_policy(TAG_CONDITION_POLICY).await;
+
+    client
+        .create_secret()
+        .name("alpha")
+        .secret_string("test")
+        .tags(Tag::builder().key("team").value("alpha").build())
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .create_secret()
+        .name("beta")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let batch_response = client
+        .batch_get_secret_value()
+        .secret_id_list("alpha")
+        .secret_id_list("beta")
+        .send()
+        .await
+        .unwrap();
+
+    let values = batch_response.secret_values();
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].name(), Some("alpha"));
+
+    let errors = batch_response.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].secret_id(), Some("beta"));
+    assert_eq!(errors[0].error_code(), Some("AccessDeniedException"));
+}
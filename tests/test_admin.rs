@@ -0,0 +1,289 @@
+use crate::common::{
+    TestServer, admin_request, error_type, start_test_server, start_test_server_with_layer,
+    test_memory_database, test_sdk_config,
+};
+use aws_credential_types::Credentials;
+use loker::{
+    admin::SnapshotStore,
+    arn::ArnConfig,
+    database::{DbHandle, create_database},
+    middleware::aws_sig_v4::{AccessKey, AwsSigV4AuthLayer},
+    policy::{PolicyDocument, PolicyKind},
+    rotation::RotationRunner,
+};
+use reqwest::Method;
+use std::{net::SocketAddr, path::Path, sync::Arc};
+
+mod common;
+
+/// Test server along with the details needed to make admin requests
+struct AdminTestServer {
+    client: aws_sdk_secretsmanager::Client,
+    server_address: SocketAddr,
+    credentials: Credentials,
+    _server: TestServer,
+}
+
+async fn test_admin_server() -> AdminTestServer {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials.clone());
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    AdminTestServer {
+        client,
+        server_address,
+        credentials,
+        _server: TestServer { abort_handle, db },
+    }
+}
+
+/// Tests that restoring a snapshot returns the database to the state it was captured in
+#[tokio::test]
+async fn test_snapshot_restore() {
+    let server = test_admin_server().await;
+
+    server
+        .client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::POST,
+        "/_loker/snapshot/before",
+        &[],
+    )
+    .await;
+    assert_eq!(response.status(), 204);
+
+    server
+        .client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("changed")
+        .send()
+        .await
+        .unwrap();
+
+    server
+        .client
+        .create_secret()
+        .name("other")
+        .secret_string("other")
+        .send()
+        .await
+        .unwrap();
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::POST,
+        "/_loker/restore/before",
+        &[],
+    )
+    .await;
+    assert_eq!(response.status(), 204);
+
+    let get_response = server
+        .client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+
+    let err = server
+        .client
+        .get_secret_value()
+        .secret_id("other")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ResourceNotFoundException"))
+    );
+}
+
+/// Tests that resetting returns the database to the state it was in when the server started
+#[tokio::test]
+async fn test_reset() {
+    let server = test_admin_server().await;
+
+    server
+        .client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::POST,
+        "/_loker/reset",
+        &[],
+    )
+    .await;
+    assert_eq!(response.status(), 204);
+
+    let list_response = server.client.list_secrets().send().await.unwrap();
+    assert!(list_response.secret_list().is_empty());
+}
+
+/// Tests that restoring a snapshot that doesn't exist fails
+#[tokio::test]
+async fn test_restore_unknown_snapshot() {
+    let server = test_admin_server().await;
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::POST,
+        "/_loker/restore/unknown",
+        &[],
+    )
+    .await;
+
+    assert_eq!(response.status(), 400);
+    assert_eq!(error_type(&response), Some("ResourceNotFoundException"));
+}
+
+/// Tests that snapshot names with unsupported characters are rejected
+#[tokio::test]
+async fn test_snapshot_invalid_name() {
+    let server = test_admin_server().await;
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::POST,
+        "/_loker/snapshot/not.valid",
+        &[],
+    )
+    .await;
+
+    assert_eq!(response.status(), 400);
+    assert_eq!(error_type(&response), Some("InvalidParameterException"));
+}
+
+/// Tests that admin requests must be authenticated
+#[tokio::test]
+async fn test_admin_unauthenticated() {
+    let server = test_admin_server().await;
+
+    let response = reqwest::Client::new()
+        .post(format!("http://{}/_loker/reset", server.server_address))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(error_type(&response), Some("MissingAuthenticationToken"));
+}
+
+/// Tests that the identity policy must allow the admin actions
+#[tokio::test]
+async fn test_admin_identity_policy() {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let policy = PolicyDocument::parse(
+        r#"{
+            "Version": "2012-10-17",
+            "Statement": [
+                { "Effect": "Allow", "Action": "loker:CreateSnapshot", "Resource": "*" }
+            ]
+        }"#,
+        PolicyKind::Identity,
+    )
+    .unwrap();
+    let auth_layer = AwsSigV4AuthLayer::from_access_keys([AccessKey {
+        policy: Some(Arc::new(policy)),
+        ..AccessKey::new(credentials.clone(), &ArnConfig::default())
+    }]);
+
+    let (server_address, abort_handle) =
+        start_test_server_with_layer(db.clone(), auth_layer, RotationRunner::default()).await;
+    let _server = TestServer { abort_handle, db };
+
+    let response = admin_request(
+        server_address,
+        &credentials,
+        Method::POST,
+        "/_loker/snapshot/test",
+        &[],
+    )
+    .await;
+    assert_eq!(response.status(), 204);
+
+    let response = admin_request(
+        server_address,
+        &credentials,
+        Method::POST,
+        "/_loker/reset",
+        &[],
+    )
+    .await;
+    assert_eq!(error_type(&response), Some("AccessDeniedException"));
+}
+
+/// Get the snapshot files within the `directory`
+fn snapshot_files(directory: &Path) -> Vec<std::fs::DirEntry> {
+    std::fs::read_dir(directory)
+        .unwrap()
+        .map(Result::unwrap)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("loker-snapshot-")
+        })
+        .collect()
+}
+
+/// Tests that snapshots of encrypted databases are stored in files next to the
+/// database only readable by the owner, which are removed with the snapshot
+#[tokio::test]
+async fn test_encrypted_snapshot_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let raw_path = directory
+        .path()
+        .join("loker.db")
+        .to_string_lossy()
+        .to_string();
+
+    let db = DbHandle::from(
+        create_database("key".to_string(), None, raw_path)
+            .await
+            .unwrap(),
+    );
+    let snapshots = SnapshotStore::create(&db, Some("key".to_string()))
+        .await
+        .unwrap();
+
+    let files = snapshot_files(directory.path());
+    assert_eq!(files.len(), 1);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = files[0].metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    drop(snapshots);
+    assert!(snapshot_files(directory.path()).is_empty());
+}
@@ -0,0 +1,153 @@
+use crate::common::{
+    TestServer, start_test_server, start_test_server_with_arn_config, test_memory_database,
+    test_sdk_config, test_server,
+};
+use aws_config::Region;
+use aws_credential_types::Credentials;
+use loker::{arn::ArnConfig, middleware::aws_sig_v4::AwsSigV4AuthLayer, rotation::RotationRunner};
+
+mod common;
+
+/// Tests that the default ARN config produces ARNs in the region of the client
+/// and the default account
+#[tokio::test]
+async fn test_default_secret_arn() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let arn = create_response.arn().unwrap();
+    assert!(arn.starts_with("arn:aws:secretsmanager:us-east-1:000000000000:secret:test-"));
+    assert_eq!(
+        arn.len(),
+        "arn:aws:secretsmanager:us-east-1:000000000000:secret:test-".len() + 6
+    );
+}
+
+/// Tests that a custom partition and account ID are used in the generated ARNs
+#[tokio::test]
+async fn test_custom_secret_arn() {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+
+    let arn = ArnConfig {
+        partition: "aws-cn".to_string(),
+        region: "cn-north-1".to_string(),
+        account_id: "123456789012".to_string(),
+    };
+
+    let (server_address, abort_handle) = start_test_server_with_arn_config(
+        db.clone(),
+        AwsSigV4AuthLayer::new(credentials.clone(), &arn),
+        RotationRunner::default(),
+        arn,
+    )
+    .await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    let _server = TestServer { abort_handle, db };
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Region should come from the client rather than the configured fallback
+    assert!(
+        create_response
+            .arn()
+            .unwrap()
+            .starts_with("arn:aws-cn:secretsmanager:us-east-1:123456789012:secret:test-")
+    );
+
+    // Secret should be accessible using a partial ARN in the custom account
+    let get_response = client
+        .get_secret_value()
+        .secret_id("arn:aws-cn:secretsmanager:us-east-1:123456789012:secret:test-??????")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.arn(), create_response.arn());
+}
+
+/// Tests that a secret created from a client in another region uses that
+/// region in its ARN
+#[tokio::test]
+async fn test_secret_arn_request_region() {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let client = aws_sdk_secretsmanager::Client::from_conf(
+        aws_sdk_secretsmanager::config::Builder::from(&sdk_config)
+            .region(Region::from_static("eu-west-1"))
+            .build(),
+    );
+
+    let _server = TestServer { abort_handle, db };
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let arn = create_response.arn().unwrap();
+    assert!(arn.starts_with("arn:aws:secretsmanager:eu-west-1:000000000000:secret:test-"));
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id(arn)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.arn(), Some(arn));
+    assert_eq!(get_response.secret_string(), Some("test"));
+}
+
+/// Tests that requests are made as the root principal of the configured account
+#[tokio::test]
+async fn test_custom_account_principal() {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+
+    let arn = ArnConfig {
+        account_id: "123456789012".to_string(),
+        ..ArnConfig::default()
+    };
+
+    let (server_address, abort_handle) = start_test_server_with_arn_config(
+        db.clone(),
+        AwsSigV4AuthLayer::new(credentials.clone(), &arn),
+        RotationRunner::default(),
+        arn,
+    )
+    .await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let sts_client = aws_sdk_sts::Client::new(&sdk_config);
+
+    let _server = TestServer { abort_handle, db };
+
+    let response = sts_client.get_caller_identity().send().await.unwrap();
+
+    assert_eq!(response.arn(), Some("arn:aws:iam::123456789012:root"));
+    assert_eq!(response.account(), Some("123456789012"));
+}
@@ -0,0 +1,217 @@
+use crate::common::{
+    TestServer, error_type, start_test_server, start_test_server_with_layer, test_memory_database,
+};
+use aws_credential_types::Credentials;
+use aws_sigv4::{
+    http_request::{SignableBody, SignableRequest, SigningSettings, sign},
+    sign::v4::SigningParams,
+};
+use loker::{arn::ArnConfig, middleware::aws_sig_v4::AwsSigV4AuthLayer, rotation::RotationRunner};
+use std::{
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+mod common;
+
+/// Body of the ListSecrets requests made by the tests
+const LIST_SECRETS_BODY: &[u8] = b"{}";
+
+/// Target of the ListSecrets requests made by the tests
+const LIST_SECRETS_TARGET: &str = "secretsmanager.ListSecrets";
+
+/// Make a ListSecrets request signed at `time` using the `credentials`
+async fn signed_list_secrets(
+    server_address: SocketAddr,
+    credentials: &Credentials,
+    time: SystemTime,
+) -> reqwest::Response {
+    let url = format!("http://{server_address}/");
+
+    let identity = credentials.clone().into();
+    let signing_params = SigningParams::builder()
+        .identity(&identity)
+        .region("us-east-1")
+        .name("secretsmanager")
+        .time(time)
+        .settings(SigningSettings::default())
+        .build()
+        .unwrap()
+        .into();
+
+    let signable_request = SignableRequest::new(
+        "POST",
+        &url,
+        [("x-amz-target", LIST_SECRETS_TARGET)].into_iter(),
+        SignableBody::Bytes(LIST_SECRETS_BODY),
+    )
+    .unwrap();
+
+    let (signing_instructions, _signature) = sign(signable_request, &signing_params)
+        .unwrap()
+        .into_parts();
+
+    let mut request = reqwest::Client::new()
+        .post(&url)
+        .header("x-amz-target", LIST_SECRETS_TARGET)
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(LIST_SECRETS_BODY);
+
+    for (name, value) in signing_instructions.headers() {
+        request = request.header(name, value);
+    }
+
+    request.send().await.unwrap()
+}
+
+/// Make a ListSecrets request with the provided `headers` and no signature
+async fn unsigned_list_secrets(
+    server_address: SocketAddr,
+    headers: &[(&str, &str)],
+) -> reqwest::Response {
+    let mut request = reqwest::Client::new()
+        .post(format!("http://{server_address}/"))
+        .header("x-amz-target", LIST_SECRETS_TARGET)
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(LIST_SECRETS_BODY);
+
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+
+    request.send().await.unwrap()
+}
+
+/// Get the error type and message of an error response
+async fn error_details(response: reqwest::Response) -> (String, String) {
+    let error_type = error_type(&response).unwrap_or_default().to_string();
+    let body: serde_json::Value = response.json().await.unwrap();
+    let message = body["message"].as_str().unwrap_or_default().to_string();
+
+    (error_type, message)
+}
+
+/// Start a test server accepting the test credentials
+async fn test_auth_server() -> (SocketAddr, Credentials, TestServer) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    (server_address, credentials, TestServer { abort_handle, db })
+}
+
+/// Tests that a request signed too far in the past is rejected as expired
+/// with the server time in the message
+#[tokio::test]
+async fn test_signature_expired_failure() {
+    let (server_address, credentials, _server) = test_auth_server().await;
+
+    let response = signed_list_secrets(
+        server_address,
+        &credentials,
+        SystemTime::now() - Duration::from_secs(60 * 10),
+    )
+    .await;
+
+    let (error_type, message) = error_details(response).await;
+    assert_eq!(error_type, "InvalidSignatureException");
+    assert!(message.starts_with("Signature expired: "));
+    assert!(message.ends_with(" - 5 min.)"));
+}
+
+/// Tests that a request signed too far in the future is rejected as not yet current
+#[tokio::test]
+async fn test_signature_not_yet_current_failure() {
+    let (server_address, credentials, _server) = test_auth_server().await;
+
+    let response = signed_list_secrets(
+        server_address,
+        &credentials,
+        SystemTime::now() + Duration::from_secs(60 * 10),
+    )
+    .await;
+
+    let (error_type, message) = error_details(response).await;
+    assert_eq!(error_type, "InvalidSignatureException");
+    assert!(message.starts_with("Signature not yet current: "));
+}
+
+/// Tests that a request without a date names the missing date headers
+#[tokio::test]
+async fn test_missing_date_failure() {
+    let (server_address, _credentials, _server) = test_auth_server().await;
+
+    let response = unsigned_list_secrets(
+        server_address,
+        &[(
+            "authorization",
+            "AWS4-HMAC-SHA256 Credential=ANOTREAL/20250101/us-east-1/secretsmanager/aws4_request, \
+            SignedHeaders=host, Signature=0000",
+        )],
+    )
+    .await;
+
+    let (error_type, message) = error_details(response).await;
+    assert_eq!(error_type, "IncompleteSignature");
+    assert!(message.contains("'X-Amz-Date' or a 'Date' header"));
+}
+
+/// Tests that an Authorization header missing the signature names the
+/// missing component
+#[tokio::test]
+async fn test_missing_signature_failure() {
+    let (server_address, _credentials, _server) = test_auth_server().await;
+
+    let response = unsigned_list_secrets(
+        server_address,
+        &[
+            (
+                "authorization",
+                "AWS4-HMAC-SHA256 Credential=ANOTREAL/20250101/us-east-1/secretsmanager/aws4_request, \
+                SignedHeaders=host",
+            ),
+            ("x-amz-date", "20250101T000000Z"),
+        ],
+    )
+    .await;
+
+    let (error_type, message) = error_details(response).await;
+    assert_eq!(error_type, "IncompleteSignature");
+    assert!(message.starts_with("Authorization header requires 'Signature' parameter."));
+}
+
+/// Tests that a signature that doesn't match only includes the expected canonical
+/// request and string to sign when signature debugging is enabled
+#[tokio::test]
+async fn test_signature_mismatch_debug() {
+    let credentials = Credentials::for_tests();
+    let wrong_credentials = Credentials::new(
+        credentials.access_key_id(),
+        "test_not_matching",
+        None,
+        None,
+        "test",
+    );
+
+    let (server_address, _credentials, _server) = test_auth_server().await;
+    let response = signed_list_secrets(server_address, &wrong_credentials, SystemTime::now()).await;
+
+    let (error_type, message) = error_details(response).await;
+    assert_eq!(error_type, "InvalidSignatureException");
+    assert!(message.starts_with("The request signature we calculated does not match"));
+    assert!(!message.contains("The Canonical String for this request should have been"));
+
+    let db = test_memory_database().await;
+    let auth_layer =
+        AwsSigV4AuthLayer::new(credentials, &ArnConfig::default()).with_debug_signatures(true);
+    let (server_address, abort_handle) =
+        start_test_server_with_layer(db.clone(), auth_layer, RotationRunner::default()).await;
+    let _server = TestServer { abort_handle, db };
+
+    let response = signed_list_secrets(server_address, &wrong_credentials, SystemTime::now()).await;
+
+    let (error_type, message) = error_details(response).await;
+    assert_eq!(error_type, "InvalidSignatureException");
+    assert!(message.contains("The Canonical String for this request should have been\n'POST\n/\n"));
+    assert!(message.contains("The String-to-Sign should have been\n'AWS4-HMAC-SHA256\n"));
+}
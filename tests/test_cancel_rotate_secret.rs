@@ -0,0 +1,127 @@
+use aws_sdk_secretsmanager::{
+    error::SdkError,
+    operation::cancel_rotate_secret::CancelRotateSecretError,
+    types::{RotationRulesType, error::ResourceNotFoundException},
+};
+
+use crate::common::test_server;
+
+mod common;
+
+/// Tests that cancelling rotation disables rotation for the secret
+#[tokio::test]
+async fn test_cancel_rotate_secret_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotate_immediately(false)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(7)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let cancel_response = client
+        .cancel_rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(cancel_response.arn(), create_response.arn());
+    assert_eq!(cancel_response.name(), create_response.name());
+
+    // No rotation was in progress
+    assert_eq!(cancel_response.version_id(), None);
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(false));
+    assert_eq!(describe_response.next_rotation_date(), None);
+}
+
+/// Tests that cancelling an in progress rotation reports the pending version
+/// and leaves the AWSPENDING stage in place
+#[tokio::test]
+async fn test_cancel_rotate_secret_pending_version_success() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let pending_response = client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-pending")
+        .version_stages("AWSPENDING")
+        .send()
+        .await
+        .unwrap();
+
+    let cancel_response = client
+        .cancel_rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(cancel_response.version_id(), pending_response.version_id());
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_stage("AWSPENDING")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), pending_response.version_id());
+}
+
+/// Tests that trying to cancel rotation for an unknown secret will fail
+#[tokio::test]
+async fn test_cancel_rotate_secret_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let cancel_err = client
+        .cancel_rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let cancel_err = match cancel_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match cancel_err.into_err() {
+        CancelRotateSecretError::ResourceNotFoundException(error) => error,
+        error => {
+            panic!("expected CancelRotateSecretError::ResourceNotFoundException got {error:?}")
+        }
+    };
+}
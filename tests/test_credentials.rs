@@ -1,7 +1,14 @@
 use crate::common::{
-    TestServer, start_test_server, test_memory_database, test_sdk_config, test_server,
+    TestServer, start_test_server, start_test_server_with_layer, test_memory_database,
+    test_sdk_config, test_server,
 };
 use aws_credential_types::Credentials;
+use loker::{
+    arn::ArnConfig,
+    middleware::aws_sig_v4::{AccessKey, AwsSigV4AuthLayer},
+    rotation::RotationRunner,
+};
+use std::time::{Duration, SystemTime};
 
 mod common;
 
@@ -87,6 +94,212 @@ async fn test_invalid_access_key_secret_credentials_failure() {
 
     assert!(
         err.as_service_error()
-            .is_some_and(|value| value.meta().code() == Some("SignatureDoesNotMatch"))
+            .is_some_and(|value| value.meta().code() == Some("InvalidSignatureException"))
+    );
+}
+
+/// Tests that requests signed by any of the configured access keys will succeed
+/// and are made as the principal of the key used
+#[tokio::test]
+async fn test_multiple_access_keys_success() {
+    let db = test_memory_database().await;
+
+    let key_a = Credentials::new("TEST_A", "test_a", None, None, "test");
+    let key_b = Credentials::new("TEST_B", "test_b", None, None, "test");
+
+    let auth_layer = AwsSigV4AuthLayer::from_access_keys([
+        AccessKey::new(key_a.clone(), &ArnConfig::default()),
+        AccessKey {
+            principal_arn: "arn:aws:iam::1:user/service-b".to_string(),
+            ..AccessKey::new(key_b.clone(), &ArnConfig::default())
+        },
+    ]);
+
+    let (server_address, abort_handle) =
+        start_test_server_with_layer(db.clone(), auth_layer, RotationRunner::default()).await;
+
+    let _server = TestServer { abort_handle, db };
+
+    let client_a = aws_sdk_secretsmanager::Client::new(&test_sdk_config(
+        &format!("http://{server_address}/"),
+        key_a,
+    ));
+    let client_b = aws_sdk_secretsmanager::Client::new(&test_sdk_config(
+        &format!("http://{server_address}/"),
+        key_b,
+    ));
+
+    client_a
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Only allow the principal of the second key to get the secret value
+    client_a
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [
+                    {
+                        "Effect": "Deny",
+                        "NotPrincipal": { "AWS": "arn:aws:iam::1:user/service-b" },
+                        "Action": "secretsmanager:GetSecretValue",
+                        "Resource": "*"
+                    }
+                ]
+            }"#,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    client_b
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let err = client_a
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("AccessDeniedException"))
+    );
+}
+
+/// Create a test server accepting the provided `server_credentials` and a client
+/// signing requests with the `client_credentials`
+async fn test_server_with_credentials(
+    server_credentials: Credentials,
+    client_credentials: Credentials,
+) -> (aws_sdk_secretsmanager::Client, TestServer) {
+    let db = test_memory_database().await;
+
+    let (server_address, abort_handle) = start_test_server(db.clone(), server_credentials).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), client_credentials);
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    (client, TestServer { abort_handle, db })
+}
+
+/// Tests that temporary credentials with a matching session token will succeed
+#[tokio::test]
+async fn test_session_token_success() {
+    let expiry = SystemTime::now() + Duration::from_secs(60 * 60);
+    let credentials = Credentials::new("TEST", "test", Some("token".to_string()), None, "test");
+
+    let (client, _server) = test_server_with_credentials(
+        Credentials::new(
+            "TEST",
+            "test",
+            Some("token".to_string()),
+            Some(expiry),
+            "test",
+        ),
+        credentials,
+    )
+    .await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that temporary credentials with a session token that doesn't match will fail
+#[tokio::test]
+async fn test_session_token_mismatch_failure() {
+    let (client, _server) = test_server_with_credentials(
+        Credentials::new("TEST", "test", Some("token".to_string()), None, "test"),
+        Credentials::new(
+            "TEST",
+            "test",
+            Some("token_not_matching".to_string()),
+            None,
+            "test",
+        ),
+    )
+    .await;
+
+    let err = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("UnrecognizedClientException"))
+    );
+}
+
+/// Tests that temporary credentials used without their session token will fail
+#[tokio::test]
+async fn test_session_token_missing_failure() {
+    let (client, _server) = test_server_with_credentials(
+        Credentials::new("TEST", "test", Some("token".to_string()), None, "test"),
+        Credentials::new("TEST", "test", None, None, "test"),
+    )
+    .await;
+
+    let err = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("UnrecognizedClientException"))
+    );
+}
+
+/// Tests that expired temporary credentials will fail
+#[tokio::test]
+async fn test_session_token_expired_failure() {
+    let expiry = SystemTime::now() - Duration::from_secs(60);
+
+    let (client, _server) = test_server_with_credentials(
+        Credentials::new(
+            "TEST",
+            "test",
+            Some("token".to_string()),
+            Some(expiry),
+            "test",
+        ),
+        Credentials::new("TEST", "test", Some("token".to_string()), None, "test"),
+    )
+    .await;
+
+    let err = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ExpiredTokenException"))
     );
 }
@@ -0,0 +1,164 @@
+use chrono::Utc;
+use loker::database::{
+    CreateDatabaseError, create_database, create_memory_database, initialize_database,
+    migrations::{MIGRATIONS, apply_migration, setup_migrations},
+    secrets::{get_secret_counts, get_secret_regions},
+};
+use std::path::PathBuf;
+use tokio_rusqlite::{params, rusqlite::Connection};
+use uuid::Uuid;
+
+/// Get a path to a database file that doesn't exist yet
+fn test_database_path() -> PathBuf {
+    std::env::temp_dir().join(format!("loker-test-{}.db", Uuid::new_v4()))
+}
+
+/// Tests that opening a database with the wrong encryption key fails
+#[tokio::test]
+async fn test_wrong_encryption_key() {
+    let path = test_database_path();
+    let raw_path = path.to_string_lossy().to_string();
+
+    let db = create_database("key".to_string(), None, raw_path.clone())
+        .await
+        .unwrap();
+    db.close().await.unwrap();
+
+    let err = create_database("wrong".to_string(), None, raw_path)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, CreateDatabaseError::InvalidEncryptionKey));
+
+    _ = std::fs::remove_file(path);
+}
+
+/// Tests that the database can be rekeyed to a new encryption key
+#[tokio::test]
+async fn test_rekey_database() {
+    let path = test_database_path();
+    let raw_path = path.to_string_lossy().to_string();
+
+    let db = create_database("key".to_string(), None, raw_path.clone())
+        .await
+        .unwrap();
+    db.close().await.unwrap();
+
+    let db = create_database(
+        "key".to_string(),
+        Some("new-key".to_string()),
+        raw_path.clone(),
+    )
+    .await
+    .unwrap();
+    db.close().await.unwrap();
+
+    // Old key is no longer valid
+    let err = create_database("key".to_string(), None, raw_path.clone())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, CreateDatabaseError::InvalidEncryptionKey));
+
+    // Starting again with both keys should use the already applied new key
+    let db = create_database(
+        "key".to_string(),
+        Some("new-key".to_string()),
+        raw_path.clone(),
+    )
+    .await
+    .unwrap();
+    db.close().await.unwrap();
+
+    let db = create_database("new-key".to_string(), None, raw_path)
+        .await
+        .unwrap();
+    db.close().await.unwrap();
+
+    _ = std::fs::remove_file(path);
+}
+
+/// Tests that an in-memory database is created with all migrations applied
+#[tokio::test]
+async fn test_memory_database() {
+    let db = create_memory_database().await.unwrap();
+
+    let regions = db.call(|db| get_secret_regions(db)).await.unwrap();
+
+    assert!(regions.is_empty());
+}
+
+/// Create a database with only the migrations from before secret regions were tracked
+fn database_before_regions() -> Connection {
+    let db = Connection::open_in_memory().unwrap();
+    setup_migrations(&db).unwrap();
+
+    for (name, migration) in MIGRATIONS
+        .iter()
+        .take_while(|(name, _)| *name != "m5_add_secrets_region")
+    {
+        apply_migration(&db, name, migration).unwrap();
+        db.execute(
+            r#"INSERT INTO "migrations" ("name", "applied_at") VALUES (?1, ?2)"#,
+            params![name, Utc::now()],
+        )
+        .unwrap();
+    }
+
+    db
+}
+
+/// Tests that secrets created before regions were tracked are migrated into the
+/// region of their ARN and keep their versions
+#[test]
+fn test_migrate_secrets_region() {
+    let mut db = database_before_regions();
+
+    let arn = "arn:aws:secretsmanager:eu-west-2:123456789012:secret:test-AbCdEf";
+    db.execute(
+        r#"INSERT INTO "secrets" ("arn", "name", "created_at") VALUES (?1, 'test', ?2)"#,
+        params![arn, Utc::now()],
+    )
+    .unwrap();
+    db.execute(
+        r#"INSERT INTO "secrets_versions" ("secret_arn", "version_id", "secret_string", "created_at")
+        VALUES (?1, 'version', 'value', ?2)"#,
+        params![arn, Utc::now()],
+    )
+    .unwrap();
+
+    initialize_database(&mut db).unwrap();
+
+    assert_eq!(
+        get_secret_regions(&db).unwrap(),
+        vec!["eu-west-2".to_string()]
+    );
+    assert_eq!(get_secret_counts(&db).unwrap(), (1, 1));
+}
+
+/// Tests that migrations leaving rows that violate their foreign keys are not applied
+#[test]
+fn test_migrate_foreign_key_violation() {
+    let mut db = database_before_regions();
+
+    // Version of a secret that doesn't exist
+    db.pragma_update(None, "foreign_keys", false).unwrap();
+    db.execute(
+        r#"INSERT INTO "secrets_versions" ("secret_arn", "version_id", "secret_string", "created_at")
+        VALUES ('arn:aws:secretsmanager:us-east-1:1:secret:missing', 'version', 'value', ?1)"#,
+        params![Utc::now()],
+    )
+    .unwrap();
+
+    initialize_database(&mut db).unwrap_err();
+
+    // Migration should have been rolled back
+    let applied: bool = db
+        .query_row(
+            r#"SELECT EXISTS (SELECT 1 FROM "migrations" WHERE "name" = 'm5_add_secrets_region')"#,
+            params![],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(!applied);
+}
@@ -0,0 +1,151 @@
+use crate::common::{TestServer, start_test_server, test_memory_database, test_sdk_config};
+use aws_credential_types::Credentials;
+use aws_sdk_secretsmanager::types::Tag;
+use loker::{
+    database::{DbHandle, transaction},
+    dump::{DumpError, ImportSummary, SecretsDump, export_secrets, import_secrets},
+};
+
+mod common;
+
+/// Create a test server using the `db` along with a client for the server
+async fn test_client(db: DbHandle) -> (aws_sdk_secretsmanager::Client, TestServer) {
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    (client, TestServer { abort_handle, db })
+}
+
+async fn export(db: &DbHandle) -> SecretsDump {
+    db.call(|db| export_secrets(db)).await.unwrap()
+}
+
+async fn import(db: &DbHandle, dump: SecretsDump) -> Result<ImportSummary, DumpError> {
+    db.call(move |db| transaction(db, move |t| import_secrets(t, dump)))
+        .await
+        .map_err(|error| match error {
+            tokio_rusqlite::Error::Error(error) => error,
+            error => panic!("unexpected error {error}"),
+        })
+}
+
+/// Tests that secrets exported from one database can be imported into another
+#[tokio::test]
+async fn test_export_import() {
+    let source_db = test_memory_database().await;
+    let (source_client, _source_server) = test_client(source_db.clone()).await;
+
+    let create_response = source_client
+        .create_secret()
+        .name("test")
+        .description("test description")
+        .secret_string("test")
+        .tags(Tag::builder().key("tag").value("value").build())
+        .send()
+        .await
+        .unwrap();
+
+    let put_response = source_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-2")
+        .send()
+        .await
+        .unwrap();
+
+    let dump = export(&source_db).await;
+    assert_eq!(dump.secrets.len(), 1);
+    assert_eq!(dump.secrets[0].versions.len(), 2);
+
+    let target_db = test_memory_database().await;
+    let summary = import(&target_db, dump).await.unwrap();
+    assert_eq!(summary.secrets_created, 1);
+    assert_eq!(summary.versions_created, 2);
+
+    let (target_client, _target_server) = test_client(target_db).await;
+
+    let describe_response = target_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.arn(), create_response.arn());
+    assert_eq!(describe_response.description(), Some("test description"));
+    assert_eq!(describe_response.tags().len(), 1);
+
+    let get_response = target_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test-2"));
+    assert_eq!(get_response.version_id(), put_response.version_id());
+
+    let get_response = target_client
+        .get_secret_value()
+        .secret_id("test")
+        .version_stage("AWSPREVIOUS")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+    assert_eq!(get_response.version_id(), create_response.version_id());
+}
+
+/// Tests that importing the same dump again doesn't create anything
+#[tokio::test]
+async fn test_import_idempotent() {
+    let db = test_memory_database().await;
+    let (client, _server) = test_client(db.clone()).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let dump = export(&db).await;
+    let summary = import(&db, dump).await.unwrap();
+    assert_eq!(summary.secrets_created, 0);
+    assert_eq!(summary.versions_created, 0);
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+}
+
+/// Tests that importing a version with a different value than the existing version fails
+#[tokio::test]
+async fn test_import_version_conflict() {
+    let db = test_memory_database().await;
+    let (client, _server) = test_client(db.clone()).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let mut dump = export(&db).await;
+    dump.secrets[0].versions[0].secret_string = Some("changed".to_string());
+
+    let err = import(&db, dump).await.unwrap_err();
+    assert!(matches!(err, DumpError::VersionConflict(_, _)));
+}
@@ -0,0 +1,293 @@
+use crate::common::{
+    TestServer, admin_request, error_type, start_test_server, test_memory_database, test_sdk_config,
+};
+use aws_config::retry::RetryConfig;
+use aws_credential_types::Credentials;
+use reqwest::Method;
+use serde_json::json;
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+mod common;
+
+/// Test server along with the details needed to manage faults
+struct FaultTestServer {
+    client: aws_sdk_secretsmanager::Client,
+    server_address: SocketAddr,
+    credentials: Credentials,
+    _server: TestServer,
+}
+
+impl FaultTestServer {
+    /// Add a fault `rule` providing its ID
+    async fn add_fault(&self, rule: serde_json::Value) -> String {
+        let response = admin_request(
+            self.server_address,
+            &self.credentials,
+            Method::POST,
+            "/_loker/faults",
+            rule.to_string().as_bytes(),
+        )
+        .await;
+
+        assert_eq!(response.status(), 200);
+
+        let body: serde_json::Value = response.json().await.unwrap();
+        body["id"].as_str().unwrap().to_string()
+    }
+}
+
+/// Create a test server with a client that doesn't retry failed requests
+async fn test_fault_server() -> FaultTestServer {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials.clone());
+    let config = aws_sdk_secretsmanager::config::Builder::from(&sdk_config)
+        .retry_config(RetryConfig::disabled())
+        .build();
+    let client = aws_sdk_secretsmanager::Client::from_conf(config);
+
+    FaultTestServer {
+        client,
+        server_address,
+        credentials,
+        _server: TestServer { abort_handle, db },
+    }
+}
+
+/// Tests that an error fault is only applied to the target it was added for
+/// and stops being applied once cleared
+#[tokio::test]
+async fn test_error_fault_target() {
+    let server = test_fault_server().await;
+
+    server
+        .client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    server
+        .add_fault(json!({
+            "target": "secretsmanager.GetSecretValue",
+            "fault": { "type": "error", "error": "ThrottlingException" }
+        }))
+        .await;
+
+    let err = server
+        .client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ThrottlingException"))
+    );
+
+    // Other targets are not affected
+    server
+        .client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::DELETE,
+        "/_loker/faults",
+        &[],
+    )
+    .await;
+    assert_eq!(response.status(), 204);
+
+    server
+        .client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that a fault for a secret name applies to requests using its name or ARN
+#[tokio::test]
+async fn test_error_fault_secret_name() {
+    let server = test_fault_server().await;
+
+    let create_response = server
+        .client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    server
+        .client
+        .create_secret()
+        .name("other")
+        .secret_string("other")
+        .send()
+        .await
+        .unwrap();
+
+    server
+        .add_fault(json!({
+            "secret_name": "test",
+            "fault": { "type": "error", "error": "LimitExceededException" }
+        }))
+        .await;
+
+    for secret_id in ["test", create_response.arn().unwrap()] {
+        let err = server
+            .client
+            .get_secret_value()
+            .secret_id(secret_id)
+            .send()
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.as_service_error()
+                .is_some_and(|value| value.meta().code() == Some("LimitExceededException"))
+        );
+    }
+
+    server
+        .client
+        .get_secret_value()
+        .secret_id("other")
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that a fault with a probability of zero is never applied
+#[tokio::test]
+async fn test_fault_probability_zero() {
+    let server = test_fault_server().await;
+
+    server
+        .add_fault(json!({
+            "probability": 0.0,
+            "fault": { "type": "error", "error": "InternalServiceError" }
+        }))
+        .await;
+
+    server.client.list_secrets().send().await.unwrap();
+}
+
+/// Tests that a latency fault delays the request
+#[tokio::test]
+async fn test_latency_fault() {
+    let server = test_fault_server().await;
+
+    server
+        .add_fault(json!({
+            "target": "secretsmanager.ListSecrets",
+            "fault": { "type": "latency", "delay_ms": 250 }
+        }))
+        .await;
+
+    let start = Instant::now();
+    server.client.list_secrets().send().await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(250));
+}
+
+/// Tests that a dropped connection fault doesn't provide a response
+#[tokio::test]
+async fn test_drop_connection_fault() {
+    let server = test_fault_server().await;
+
+    let id = server
+        .add_fault(json!({ "fault": { "type": "drop_connection" } }))
+        .await;
+
+    let err = server.client.list_secrets().send().await.unwrap_err();
+    assert!(err.as_service_error().is_none());
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::DELETE,
+        &format!("/_loker/faults/{id}"),
+        &[],
+    )
+    .await;
+    assert_eq!(response.status(), 204);
+
+    server.client.list_secrets().send().await.unwrap();
+}
+
+/// Tests that the faults can be listed and unknown faults can't be deleted
+#[tokio::test]
+async fn test_list_and_delete_faults() {
+    let server = test_fault_server().await;
+
+    let id = server
+        .add_fault(json!({
+            "target": "secretsmanager.ListSecrets",
+            "fault": { "type": "latency", "delay_ms": 10 }
+        }))
+        .await;
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::GET,
+        "/_loker/faults",
+        &[],
+    )
+    .await;
+    assert_eq!(response.status(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["faults"][0]["id"], id);
+    assert_eq!(body["faults"][0]["fault"]["type"], "latency");
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::DELETE,
+        "/_loker/faults/unknown",
+        &[],
+    )
+    .await;
+    assert_eq!(error_type(&response), Some("ResourceNotFoundException"));
+}
+
+/// Tests that faults with a probability outside of 0 to 1 are rejected
+#[tokio::test]
+async fn test_invalid_fault_probability() {
+    let server = test_fault_server().await;
+
+    let response = admin_request(
+        server.server_address,
+        &server.credentials,
+        Method::POST,
+        "/_loker/faults",
+        json!({
+            "probability": 2.0,
+            "fault": { "type": "drop_connection" }
+        })
+        .to_string()
+        .as_bytes(),
+    )
+    .await;
+
+    assert_eq!(error_type(&response), Some("InvalidParameterException"));
+}
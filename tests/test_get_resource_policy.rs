@@ -0,0 +1,56 @@
+use aws_sdk_secretsmanager::{
+    error::SdkError, operation::get_resource_policy::GetResourcePolicyError,
+    types::error::ResourceNotFoundException,
+};
+
+use crate::common::test_server;
+
+mod common;
+
+/// Tests that getting the policy of a secret without one succeeds
+#[tokio::test]
+async fn test_get_resource_policy_none_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.arn(), create_response.arn());
+    assert_eq!(get_response.name(), create_response.name());
+    assert_eq!(get_response.resource_policy(), None);
+}
+
+/// Tests that getting the policy of an unknown secret will fail
+#[tokio::test]
+async fn test_get_resource_policy_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let get_err = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let get_err = match get_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match get_err.into_err() {
+        GetResourcePolicyError::ResourceNotFoundException(error) => error,
+        error => panic!("expected GetResourcePolicyError::ResourceNotFoundException got {error:?}"),
+    };
+}
@@ -173,7 +173,7 @@ async fn test_get_secret_value_by_partial_arn_string_success() {
 
     let get_response = client
         .get_secret_value()
-        .secret_id("arn:aws:secretsmanager:us-east-1:1:secret:*")
+        .secret_id("arn:aws:secretsmanager:us-east-1:000000000000:secret:*")
         .send()
         .await
         .unwrap();
@@ -198,7 +198,7 @@ async fn test_get_secret_value_by_partial_arn_string_success() {
 
     let get_response = client
         .get_secret_value()
-        .secret_id("arn:aws:secretsmanager:*:1:secret:*")
+        .secret_id("arn:aws:secretsmanager:*:000000000000:secret:*")
         .send()
         .await
         .unwrap();
@@ -223,7 +223,7 @@ async fn test_get_secret_value_by_partial_arn_string_success() {
 
     let get_response = client
         .get_secret_value()
-        .secret_id("arn:aws:secretsmanager:us-east-1:1:secret:test-??????")
+        .secret_id("arn:aws:secretsmanager:us-east-1:000000000000:secret:test-??????")
         .send()
         .await
         .unwrap();
@@ -0,0 +1,343 @@
+use crate::common::{TestServer, start_test_server, test_memory_database, test_sdk_config};
+use aws_credential_types::Credentials;
+use aws_sdk_kms::types::KeyState;
+use tokio_rusqlite::params;
+
+mod common;
+
+/// Create a test server along with a Secrets Manager and KMS client
+async fn test_clients() -> (
+    aws_sdk_secretsmanager::Client,
+    aws_sdk_kms::Client,
+    TestServer,
+) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+    let kms_client = aws_sdk_kms::Client::new(&sdk_config);
+
+    (client, kms_client, TestServer { abort_handle, db })
+}
+
+/// Create a KMS key returning its ARN
+async fn create_key(kms_client: &aws_sdk_kms::Client) -> String {
+    kms_client
+        .create_key()
+        .send()
+        .await
+        .unwrap()
+        .key_metadata()
+        .unwrap()
+        .arn()
+        .unwrap()
+        .to_string()
+}
+
+/// Tests that a created key can be described by its key ID and ARN
+#[tokio::test]
+async fn test_create_and_describe_key() {
+    let (_client, kms_client, _server) = test_clients().await;
+
+    let create_response = kms_client
+        .create_key()
+        .description("test key")
+        .send()
+        .await
+        .unwrap();
+
+    let key_metadata = create_response.key_metadata().unwrap();
+    assert_eq!(key_metadata.description(), Some("test key"));
+    assert_eq!(key_metadata.key_state(), Some(&KeyState::Enabled));
+    assert!(key_metadata.enabled());
+
+    let describe_response = kms_client
+        .describe_key()
+        .key_id(key_metadata.key_id())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        describe_response.key_metadata().unwrap().arn(),
+        key_metadata.arn()
+    );
+
+    let describe_response = kms_client
+        .describe_key()
+        .key_id(key_metadata.arn().unwrap())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        describe_response.key_metadata().unwrap().key_id(),
+        key_metadata.key_id()
+    );
+
+    let list_response = kms_client.list_keys().send().await.unwrap();
+    assert_eq!(list_response.keys().len(), 1);
+    assert_eq!(list_response.keys()[0].key_arn(), key_metadata.arn());
+}
+
+/// Tests that describing an unknown key fails
+#[tokio::test]
+async fn test_describe_unknown_key() {
+    let (_client, kms_client, _server) = test_clients().await;
+
+    let err = kms_client
+        .describe_key()
+        .key_id("4a0d2b5e-0f4a-4b9f-9a3c-8b2a3e4d5f60")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("NotFoundException"))
+    );
+}
+
+/// Tests that a secret encrypted with a KMS key can be read back
+#[tokio::test]
+async fn test_create_secret_with_kms_key() {
+    let (client, kms_client, _server) = test_clients().await;
+
+    let key_arn = create_key(&kms_client).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .kms_key_id(&key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.kms_key_id(), Some(key_arn.as_str()));
+
+    // New versions are encrypted with the same key
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-2")
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test-2"));
+}
+
+/// Tests that creating a secret with an unknown KMS key fails
+#[tokio::test]
+async fn test_create_secret_unknown_kms_key() {
+    let (client, _kms_client, _server) = test_clients().await;
+
+    let err = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .kms_key_id("4a0d2b5e-0f4a-4b9f-9a3c-8b2a3e4d5f60")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("EncryptionFailure"))
+    );
+}
+
+/// Tests that a secret can't be read while its KMS key is disabled
+#[tokio::test]
+async fn test_get_secret_value_disabled_kms_key() {
+    let (client, kms_client, _server) = test_clients().await;
+
+    let key_arn = create_key(&kms_client).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .kms_key_id(&key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    kms_client
+        .disable_key()
+        .key_id(&key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    let err = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("KMSInvalidStateException"))
+    );
+
+    kms_client
+        .enable_key()
+        .key_id(&key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+}
+
+/// Tests that changing the KMS key of a secret keeps existing versions readable
+#[tokio::test]
+async fn test_update_secret_kms_key() {
+    let (client, kms_client, _server) = test_clients().await;
+
+    let first_key_arn = create_key(&kms_client).await;
+    let second_key_arn = create_key(&kms_client).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .kms_key_id(&first_key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .update_secret()
+        .secret_id("test")
+        .secret_string("test-2")
+        .kms_key_id(&second_key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        describe_response.kms_key_id(),
+        Some(second_key_arn.as_str())
+    );
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test-2"));
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_id(create_response.version_id().unwrap())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+}
+
+/// Tests that an encrypted value moved onto another version can't be decrypted
+#[tokio::test]
+async fn test_get_secret_value_moved_encrypted_value() {
+    let (client, kms_client, server) = test_clients().await;
+
+    let key_arn = create_key(&kms_client).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .kms_key_id(&key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    let put_response = client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-2")
+        .send()
+        .await
+        .unwrap();
+
+    let first_version_id = create_response.version_id().unwrap().to_string();
+    let second_version_id = put_response.version_id().unwrap().to_string();
+
+    // Replace the value of the current version with the value of the first version
+    server
+        .db
+        .call_unwrap(move |db| {
+            db.execute(
+                r#"
+                UPDATE "secrets_versions"
+                SET "encrypted_data_key" = "first"."encrypted_data_key",
+                    "encrypted_value" = "first"."encrypted_value"
+                FROM "secrets_versions" AS "first"
+                WHERE "secrets_versions"."version_id" = ?1 AND "first"."version_id" = ?2
+                "#,
+                params![second_version_id, first_version_id],
+            )
+        })
+        .await
+        .unwrap();
+
+    let err = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("DecryptionFailure"))
+    );
+}
@@ -0,0 +1,93 @@
+use crate::common::{TestServer, start_test_server, test_memory_database, test_sdk_config};
+use aws_credential_types::Credentials;
+use std::net::SocketAddr;
+
+mod common;
+
+/// Start a test server accepting the test credentials
+async fn test_metrics_server() -> (SocketAddr, TestServer) {
+    let db = test_memory_database().await;
+    let (server_address, abort_handle) =
+        start_test_server(db.clone(), Credentials::for_tests()).await;
+
+    (server_address, TestServer { abort_handle, db })
+}
+
+/// Get the metrics from the server without authenticating
+async fn get_metrics(server_address: SocketAddr) -> String {
+    let response = reqwest::get(format!("http://{server_address}/metrics"))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert!(
+        response
+            .headers()
+            .get("content-type")
+            .is_some_and(|value| value.to_str().unwrap().starts_with("text/plain"))
+    );
+
+    response.text().await.unwrap()
+}
+
+/// Tests that handled requests and the stored secrets are included in the metrics
+#[tokio::test]
+async fn test_metrics_requests() {
+    let (server_address, _server) = test_metrics_server().await;
+
+    let sdk_config = test_sdk_config(
+        &format!("http://{server_address}/"),
+        Credentials::for_tests(),
+    );
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let metrics = get_metrics(server_address).await;
+
+    assert!(metrics.contains(
+        "loker_requests_total{target=\"secretsmanager.CreateSecret\",error_type=\"\"} 1\n"
+    ));
+    assert!(metrics.contains(
+        "loker_requests_total{target=\"secretsmanager.CreateSecret\",\
+        error_type=\"ResourceExistsException\"} 1\n"
+    ));
+    assert!(metrics.contains("loker_secrets 1\n"));
+    assert!(metrics.contains("loker_secret_versions 1\n"));
+}
+
+/// Tests that requests failing authentication are counted by reason
+#[tokio::test]
+async fn test_metrics_auth_failures() {
+    let (server_address, _server) = test_metrics_server().await;
+
+    let sdk_config = test_sdk_config(
+        &format!("http://{server_address}/"),
+        Credentials::new("TEST_THAT_DOES_NOT_MATCH", "test", None, None, "test"),
+    );
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    client.list_secrets().send().await.unwrap_err();
+
+    let metrics = get_metrics(server_address).await;
+
+    assert!(metrics.contains("loker_auth_failures_total{reason=\"unknown_access_key\"} 1\n"));
+    assert!(metrics.contains(
+        "loker_requests_total{target=\"secretsmanager.ListSecrets\",\
+        error_type=\"InvalidClientTokenId\"} 1\n"
+    ));
+}
@@ -0,0 +1,164 @@
+use crate::common::{TestServer, error_type, start_test_server, test_memory_database};
+use aws_credential_types::Credentials;
+use aws_sigv4::{
+    http_request::{SignableBody, SignableRequest, SignatureLocation, SigningSettings, sign},
+    sign::v4::SigningParams,
+};
+use std::{
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+mod common;
+
+/// Body of the ListSecrets requests made by the tests
+const LIST_SECRETS_BODY: &[u8] = b"{}";
+
+/// Make a ListSecrets request presigned at `time` using the `credentials`, the
+/// signature is valid for `expires_in` after the `time`
+async fn presigned_list_secrets(
+    server_address: SocketAddr,
+    credentials: &Credentials,
+    time: SystemTime,
+    expires_in: Duration,
+    body: SignableBody<'_>,
+) -> reqwest::Response {
+    let url = format!("http://{server_address}/");
+    let target = "secretsmanager.ListSecrets";
+
+    let mut signing_settings = SigningSettings::default();
+    signing_settings.signature_location = SignatureLocation::QueryParams;
+    signing_settings.expires_in = Some(expires_in);
+
+    let identity = credentials.clone().into();
+    let signing_params = SigningParams::builder()
+        .identity(&identity)
+        .region("us-east-1")
+        .name("secretsmanager")
+        .time(time)
+        .settings(signing_settings)
+        .build()
+        .unwrap()
+        .into();
+
+    let signable_request =
+        SignableRequest::new("POST", &url, [("x-amz-target", target)].into_iter(), body).unwrap();
+
+    let (signing_instructions, _signature) = sign(signable_request, &signing_params)
+        .unwrap()
+        .into_parts();
+
+    let query = serde_urlencoded::to_string(signing_instructions.params()).unwrap();
+
+    reqwest::Client::new()
+        .post(format!("{url}?{query}"))
+        .header("x-amz-target", target)
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(LIST_SECRETS_BODY)
+        .send()
+        .await
+        .unwrap()
+}
+
+/// Start a test server accepting the test credentials
+async fn test_presigned_server() -> (SocketAddr, Credentials, TestServer) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    (server_address, credentials, TestServer { abort_handle, db })
+}
+
+/// Tests that a request presigned including the payload will succeed
+#[tokio::test]
+async fn test_presigned_success() {
+    let (server_address, credentials, _server) = test_presigned_server().await;
+
+    let response = presigned_list_secrets(
+        server_address,
+        &credentials,
+        SystemTime::now(),
+        Duration::from_secs(60),
+        SignableBody::Bytes(LIST_SECRETS_BODY),
+    )
+    .await;
+
+    assert_eq!(response.status(), 200);
+}
+
+/// Tests that a request presigned with an unsigned payload will succeed
+#[tokio::test]
+async fn test_presigned_unsigned_payload_success() {
+    let (server_address, credentials, _server) = test_presigned_server().await;
+
+    let response = presigned_list_secrets(
+        server_address,
+        &credentials,
+        SystemTime::now(),
+        Duration::from_secs(60),
+        SignableBody::UnsignedPayload,
+    )
+    .await;
+
+    assert_eq!(response.status(), 200);
+}
+
+/// Tests that a presigned request signed outside the usual clock skew window is
+/// accepted while it hasn't expired
+#[tokio::test]
+async fn test_presigned_long_expiry_success() {
+    let (server_address, credentials, _server) = test_presigned_server().await;
+
+    let response = presigned_list_secrets(
+        server_address,
+        &credentials,
+        SystemTime::now() - Duration::from_secs(60 * 30),
+        Duration::from_secs(60 * 60),
+        SignableBody::Bytes(LIST_SECRETS_BODY),
+    )
+    .await;
+
+    assert_eq!(response.status(), 200);
+}
+
+/// Tests that an expired presigned request will fail
+#[tokio::test]
+async fn test_presigned_expired_failure() {
+    let (server_address, credentials, _server) = test_presigned_server().await;
+
+    let response = presigned_list_secrets(
+        server_address,
+        &credentials,
+        SystemTime::now() - Duration::from_secs(120),
+        Duration::from_secs(60),
+        SignableBody::Bytes(LIST_SECRETS_BODY),
+    )
+    .await;
+
+    assert_eq!(error_type(&response), Some("InvalidSignatureException"));
+}
+
+/// Tests that a request presigned using the wrong secret will fail
+#[tokio::test]
+async fn test_presigned_invalid_secret_failure() {
+    let (server_address, credentials, _server) = test_presigned_server().await;
+
+    let credentials = Credentials::new(
+        credentials.access_key_id(),
+        "test_not_matching",
+        None,
+        None,
+        "test",
+    );
+
+    let response = presigned_list_secrets(
+        server_address,
+        &credentials,
+        SystemTime::now(),
+        Duration::from_secs(60),
+        SignableBody::Bytes(LIST_SECRETS_BODY),
+    )
+    .await;
+
+    assert_eq!(error_type(&response), Some("InvalidSignatureException"));
+}
@@ -0,0 +1,230 @@
+use aws_sdk_secretsmanager::{
+    error::SdkError,
+    operation::put_resource_policy::PutResourcePolicyError,
+    types::error::{
+        MalformedPolicyDocumentException, PublicPolicyException, ResourceNotFoundException,
+    },
+};
+
+use crate::common::test_server;
+
+mod common;
+
+const TEST_POLICY: &str = r#"{
+    "Version": "2012-10-17",
+    "Statement": [
+        {
+            "Effect": "Allow",
+            "Principal": { "AWS": "arn:aws:iam::1:role/test" },
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*"
+        }
+    ]
+}"#;
+
+const PUBLIC_POLICY: &str = r#"{
+    "Version": "2012-10-17",
+    "Statement": {
+        "Effect": "Allow",
+        "Principal": "*",
+        "Action": "secretsmanager:GetSecretValue",
+        "Resource": "*"
+    }
+}"#;
+
+/// Tests that a resource policy can be attached to a secret
+#[tokio::test]
+async fn test_put_resource_policy_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let put_response = client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(put_response.arn(), create_response.arn());
+    assert_eq!(put_response.name(), create_response.name());
+
+    let get_response = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.resource_policy(), Some(TEST_POLICY));
+}
+
+/// Tests that putting a resource policy replaces the existing policy
+#[tokio::test]
+async fn test_put_resource_policy_replace_success() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(PUBLIC_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.resource_policy(), Some(TEST_POLICY));
+}
+
+/// Tests that a public policy is allowed when public policies aren't blocked
+#[tokio::test]
+async fn test_put_resource_policy_public_success() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(PUBLIC_POLICY)
+        .block_public_policy(false)
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that a public policy is rejected when public policies are blocked
+#[tokio::test]
+async fn test_put_resource_policy_public_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let put_err = client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(PUBLIC_POLICY)
+        .block_public_policy(true)
+        .send()
+        .await
+        .unwrap_err();
+
+    let put_err = match put_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: PublicPolicyException = match put_err.into_err() {
+        PutResourcePolicyError::PublicPolicyException(error) => error,
+        error => panic!("expected PutResourcePolicyError::PublicPolicyException got {error:?}"),
+    };
+
+    // Policy should not have been stored
+    let get_response = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.resource_policy(), None);
+}
+
+/// Tests that a malformed policy is rejected
+#[tokio::test]
+async fn test_put_resource_policy_malformed_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let put_err = client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(r#"{"Version": "2012-10-17", "Statement": [{"Effect": "Allow"}]}"#)
+        .send()
+        .await
+        .unwrap_err();
+
+    let put_err = match put_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: MalformedPolicyDocumentException = match put_err.into_err() {
+        PutResourcePolicyError::MalformedPolicyDocumentException(error) => error,
+        error => panic!(
+            "expected PutResourcePolicyError::MalformedPolicyDocumentException got {error:?}"
+        ),
+    };
+}
+
+/// Tests that putting a policy on an unknown secret will fail
+#[tokio::test]
+async fn test_put_resource_policy_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let put_err = client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap_err();
+
+    let put_err = match put_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match put_err.into_err() {
+        PutResourcePolicyError::ResourceNotFoundException(error) => error,
+        error => panic!("expected PutResourcePolicyError::ResourceNotFoundException got {error:?}"),
+    };
+}
@@ -0,0 +1,166 @@
+use crate::common::{TestServer, start_test_server, test_memory_database, test_sdk_config};
+use aws_config::Region;
+use aws_credential_types::Credentials;
+
+mod common;
+
+/// Create a test server along with clients for the us-east-1 and eu-west-1 regions
+async fn test_region_clients() -> (
+    aws_sdk_secretsmanager::Client,
+    aws_sdk_secretsmanager::Client,
+    TestServer,
+) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let us_client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+    let eu_client = aws_sdk_secretsmanager::Client::from_conf(
+        aws_sdk_secretsmanager::config::Builder::from(&sdk_config)
+            .region(Region::from_static("eu-west-1"))
+            .build(),
+    );
+
+    (us_client, eu_client, TestServer { abort_handle, db })
+}
+
+/// Tests that a secret created in one region cannot be found from another region
+/// by either its name or ARN
+#[tokio::test]
+async fn test_secret_not_visible_from_other_region() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    let create_response = eu_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let err = us_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ResourceNotFoundException"))
+    );
+
+    let err = us_client
+        .describe_secret()
+        .secret_id(create_response.arn().unwrap())
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ResourceNotFoundException"))
+    );
+}
+
+/// Tests that the same secret name can be used in multiple regions
+#[tokio::test]
+async fn test_same_name_in_multiple_regions() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    let us_create_response = us_client
+        .create_secret()
+        .name("test")
+        .secret_string("us-value")
+        .send()
+        .await
+        .unwrap();
+
+    let eu_create_response = eu_client
+        .create_secret()
+        .name("test")
+        .secret_string("eu-value")
+        .send()
+        .await
+        .unwrap();
+
+    assert_ne!(us_create_response.arn(), eu_create_response.arn());
+
+    let us_get_response = us_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(us_get_response.arn(), us_create_response.arn());
+    assert_eq!(us_get_response.secret_string(), Some("us-value"));
+
+    let eu_get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(eu_get_response.arn(), eu_create_response.arn());
+    assert_eq!(eu_get_response.secret_string(), Some("eu-value"));
+
+    // Deleting in one region should not affect the other
+    us_client
+        .delete_secret()
+        .secret_id("test")
+        .force_delete_without_recovery(true)
+        .send()
+        .await
+        .unwrap();
+
+    let eu_get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(eu_get_response.secret_string(), Some("eu-value"));
+}
+
+/// Tests that listing secrets only includes secrets from the region of the request
+#[tokio::test]
+async fn test_list_secrets_region_scoped() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test-us")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    eu_client
+        .create_secret()
+        .name("test-eu")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let us_secrets = us_client.list_secrets().send().await.unwrap();
+    let us_names: Vec<&str> = us_secrets
+        .secret_list()
+        .iter()
+        .filter_map(|secret| secret.name())
+        .collect();
+    assert_eq!(us_names, vec!["test-us"]);
+
+    let eu_secrets = eu_client.list_secrets().send().await.unwrap();
+    let eu_names: Vec<&str> = eu_secrets
+        .secret_list()
+        .iter()
+        .filter_map(|secret| secret.name())
+        .collect();
+    assert_eq!(eu_names, vec!["test-eu"]);
+}
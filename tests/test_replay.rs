@@ -0,0 +1,243 @@
+use crate::common::{
+    TestServer, error_type, signed_admin_request, start_test_server_with_layer,
+    test_memory_database,
+};
+use aws_credential_types::Credentials;
+use aws_sigv4::{
+    http_request::{SignableBody, SignableRequest, SigningSettings, sign},
+    sign::{v4, v4a},
+};
+use loker::{arn::ArnConfig, middleware::aws_sig_v4::AwsSigV4AuthLayer, rotation::RotationRunner};
+use reqwest::Method;
+use std::{net::SocketAddr, time::SystemTime};
+
+mod common;
+
+/// Create a request to the `target` with the `body` signed using the `credentials`
+fn signed_request(
+    client: &reqwest::Client,
+    server_address: SocketAddr,
+    credentials: &Credentials,
+    target: &str,
+    body: &[u8],
+) -> reqwest::Request {
+    let url = format!("http://{server_address}/");
+
+    let identity = credentials.clone().into();
+    let signing_params = v4::SigningParams::builder()
+        .identity(&identity)
+        .region("us-east-1")
+        .name("secretsmanager")
+        .time(SystemTime::now())
+        .settings(SigningSettings::default())
+        .build()
+        .unwrap()
+        .into();
+
+    let signable_request = SignableRequest::new(
+        "POST",
+        &url,
+        [("x-amz-target", target)].into_iter(),
+        SignableBody::Bytes(body),
+    )
+    .unwrap();
+
+    let (signing_instructions, _signature) = sign(signable_request, &signing_params)
+        .unwrap()
+        .into_parts();
+
+    let mut request = client
+        .post(&url)
+        .header("x-amz-target", target)
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(body.to_vec());
+
+    for (name, value) in signing_instructions.headers() {
+        request = request.header(name, value);
+    }
+
+    request.build().unwrap()
+}
+
+/// Create a request to the `target` with the `body` signed using SigV4A for
+/// the `region_set` using the `credentials`
+fn sigv4a_signed_request(
+    client: &reqwest::Client,
+    server_address: SocketAddr,
+    credentials: &Credentials,
+    region_set: &str,
+    target: &str,
+    body: &[u8],
+) -> reqwest::Request {
+    let url = format!("http://{server_address}/");
+
+    let identity = credentials.clone().into();
+    let signing_params = v4a::SigningParams::builder()
+        .identity(&identity)
+        .region_set(region_set)
+        .name("secretsmanager")
+        .time(SystemTime::now())
+        .settings(SigningSettings::default())
+        .build()
+        .unwrap()
+        .into();
+
+    let signable_request = SignableRequest::new(
+        "POST",
+        &url,
+        [("x-amz-target", target)].into_iter(),
+        SignableBody::Bytes(body),
+    )
+    .unwrap();
+
+    let (signing_instructions, _signature) = sign(signable_request, &signing_params)
+        .unwrap()
+        .into_parts();
+
+    let mut request = client
+        .post(&url)
+        .header("x-amz-target", target)
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(body.to_vec());
+
+    for (name, value) in signing_instructions.headers() {
+        request = request.header(name, value);
+    }
+
+    request.build().unwrap()
+}
+
+/// Start a test server with replay protection accepting the test credentials
+async fn test_replay_server() -> (SocketAddr, Credentials, TestServer) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let auth_layer = AwsSigV4AuthLayer::new(credentials.clone(), &ArnConfig::default())
+        .with_replay_protection(Some(100));
+    let (server_address, abort_handle) =
+        start_test_server_with_layer(db.clone(), auth_layer, RotationRunner::default()).await;
+
+    (server_address, credentials, TestServer { abort_handle, db })
+}
+
+/// Tests that sending the exact same request to a target that changes state is rejected
+#[tokio::test]
+async fn test_replayed_mutating_request_failure() {
+    let (server_address, credentials, _server) = test_replay_server().await;
+    let client = reqwest::Client::new();
+
+    let request = signed_request(
+        &client,
+        server_address,
+        &credentials,
+        "secretsmanager.CreateSecret",
+        br#"{"Name":"test","SecretString":"test"}"#,
+    );
+    let replayed = request.try_clone().unwrap();
+
+    let response = client.execute(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = client.execute(replayed).await.unwrap();
+    assert_eq!(error_type(&response), Some("InvalidSignatureException"));
+}
+
+/// Tests that sending the exact same request to a read only target is allowed
+#[tokio::test]
+async fn test_replayed_read_only_request_success() {
+    let (server_address, credentials, _server) = test_replay_server().await;
+    let client = reqwest::Client::new();
+
+    let request = signed_request(
+        &client,
+        server_address,
+        &credentials,
+        "secretsmanager.ListSecrets",
+        b"{}",
+    );
+    let replayed = request.try_clone().unwrap();
+
+    let response = client.execute(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = client.execute(replayed).await.unwrap();
+    assert_eq!(response.status(), 200);
+}
+
+/// Tests that replaying a request with the signature re-encoded in upper-case hex,
+/// which still verifies, is rejected
+#[tokio::test]
+async fn test_replayed_uppercase_signature_failure() {
+    let (server_address, credentials, _server) = test_replay_server().await;
+    let client = reqwest::Client::new();
+
+    let request = sigv4a_signed_request(
+        &client,
+        server_address,
+        &credentials,
+        "us-east-1",
+        "secretsmanager.CreateSecret",
+        br#"{"Name":"test","SecretString":"test"}"#,
+    );
+    let mut replayed = request.try_clone().unwrap();
+
+    let response = client.execute(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    // Upper-case the hex encoded signature
+    let authorization = replayed.headers()["authorization"].to_str().unwrap();
+    let (credential, signature) = authorization.split_once("Signature=").unwrap();
+    let authorization = format!("{credential}Signature={}", signature.to_uppercase());
+    assert_ne!(authorization, replayed.headers()["authorization"]);
+    replayed
+        .headers_mut()
+        .insert("authorization", authorization.parse().unwrap());
+
+    let response = client.execute(replayed).await.unwrap();
+    assert_eq!(error_type(&response), Some("InvalidSignatureException"));
+}
+
+/// Tests that sending the exact same request to an admin route that changes state is rejected
+#[tokio::test]
+async fn test_replayed_admin_request_failure() {
+    let (server_address, credentials, _server) = test_replay_server().await;
+    let client = reqwest::Client::new();
+
+    let request = signed_admin_request(
+        &client,
+        server_address,
+        &credentials,
+        Method::POST,
+        "/_loker/reset",
+        &[],
+    );
+    let replayed = request.try_clone().unwrap();
+
+    let response = client.execute(request).await.unwrap();
+    assert_eq!(response.status(), 204);
+
+    let response = client.execute(replayed).await.unwrap();
+    assert_eq!(error_type(&response), Some("SignatureDoesNotMatch"));
+}
+
+/// Tests that sending the exact same request to a read only admin route is allowed
+#[tokio::test]
+async fn test_replayed_read_only_admin_request_success() {
+    let (server_address, credentials, _server) = test_replay_server().await;
+    let client = reqwest::Client::new();
+
+    let request = signed_admin_request(
+        &client,
+        server_address,
+        &credentials,
+        Method::GET,
+        "/_loker/faults",
+        &[],
+    );
+    let replayed = request.try_clone().unwrap();
+
+    let response = client.execute(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = client.execute(replayed).await.unwrap();
+    assert_eq!(response.status(), 200);
+}
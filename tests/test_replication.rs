@@ -0,0 +1,800 @@
+use crate::common::{TestServer, start_test_server, test_memory_database, test_sdk_config};
+use aws_config::Region;
+use aws_credential_types::Credentials;
+use aws_sdk_secretsmanager::types::{Filter, FilterNameStringType, ReplicaRegionType, StatusType};
+
+mod common;
+
+/// Create a test server along with clients for the us-east-1 and eu-west-1 regions
+async fn test_region_clients() -> (
+    aws_sdk_secretsmanager::Client,
+    aws_sdk_secretsmanager::Client,
+    TestServer,
+) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let us_client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+    let eu_client = aws_sdk_secretsmanager::Client::from_conf(
+        aws_sdk_secretsmanager::config::Builder::from(&sdk_config)
+            .region(Region::from_static("eu-west-1"))
+            .build(),
+    );
+
+    (us_client, eu_client, TestServer { abort_handle, db })
+}
+
+fn eu_replica() -> ReplicaRegionType {
+    ReplicaRegionType::builder().region("eu-west-1").build()
+}
+
+/// Tests that a replicated secret can be read from the replica region
+#[tokio::test]
+async fn test_replicate_secret() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    let create_response = us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let replicate_response = us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(replicate_response.arn(), create_response.arn());
+
+    let status = &replicate_response.replication_status()[0];
+    assert_eq!(status.region(), Some("eu-west-1"));
+    assert_eq!(status.status(), Some(&StatusType::InSync));
+
+    let replica_arn = create_response
+        .arn()
+        .unwrap()
+        .replace(":us-east-1:", ":eu-west-1:");
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.arn(), Some(replica_arn.as_str()));
+    assert_eq!(get_response.secret_string(), Some("test"));
+    assert_eq!(get_response.version_id(), create_response.version_id());
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id(&replica_arn)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+}
+
+/// Tests that replicating a secret to its own region is rejected
+#[tokio::test]
+async fn test_replicate_secret_same_region() {
+    let (us_client, _eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let err = us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(ReplicaRegionType::builder().region("us-east-1").build())
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("InvalidParameterException"))
+    );
+}
+
+/// Tests that changes to the primary secret are propagated to its replicas
+#[tokio::test]
+async fn test_replica_receives_primary_changes() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    let put_response = us_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-2")
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .update_secret()
+        .secret_id("test")
+        .description("updated")
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .tag_resource()
+        .secret_id("test")
+        .tags(
+            aws_sdk_secretsmanager::types::Tag::builder()
+                .key("key")
+                .value("value")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test-2"));
+    assert_eq!(get_response.version_id(), put_response.version_id());
+
+    let describe_response = eu_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.description(), Some("updated"));
+    assert_eq!(describe_response.primary_region(), Some("us-east-1"));
+
+    let tag = &describe_response.tags()[0];
+    assert_eq!(tag.key(), Some("key"));
+    assert_eq!(tag.value(), Some("value"));
+}
+
+/// Tests that a replica cannot be modified directly
+#[tokio::test]
+async fn test_replica_read_only() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    let err = eu_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-2")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("InvalidRequestException"))
+    );
+
+    let err = eu_client
+        .delete_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("InvalidRequestException"))
+    );
+}
+
+/// Tests that a primary secret cannot be deleted while it still has replicas
+#[tokio::test]
+async fn test_delete_primary_with_replicas() {
+    let (us_client, _eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    let err = us_client
+        .delete_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("InvalidRequestException"))
+    );
+}
+
+/// Tests that the primary secret describes the status of its replicas
+#[tokio::test]
+async fn test_describe_primary_replication_status() {
+    let (us_client, _eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = us_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(describe_response.primary_region().is_none());
+    assert!(describe_response.replication_status().is_empty());
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = us_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.primary_region(), Some("us-east-1"));
+
+    let status = &describe_response.replication_status()[0];
+    assert_eq!(status.region(), Some("eu-west-1"));
+    assert_eq!(status.status(), Some(&StatusType::InSync));
+}
+
+/// Tests that replication fails when the name is already taken in the replica region
+/// unless overwriting is requested
+#[tokio::test]
+async fn test_replicate_secret_name_conflict() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("us-value")
+        .send()
+        .await
+        .unwrap();
+
+    eu_client
+        .create_secret()
+        .name("test")
+        .secret_string("eu-value")
+        .send()
+        .await
+        .unwrap();
+
+    let replicate_response = us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    let status = &replicate_response.replication_status()[0];
+    assert_eq!(status.status(), Some(&StatusType::Failed));
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("eu-value"));
+
+    let replicate_response = us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .force_overwrite_replica_secret(true)
+        .send()
+        .await
+        .unwrap();
+
+    let status = &replicate_response.replication_status()[0];
+    assert_eq!(status.status(), Some(&StatusType::InSync));
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("us-value"));
+}
+
+/// Tests that removing a replica region deletes the replica secret
+#[tokio::test]
+async fn test_remove_regions_from_replication() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    let remove_response = us_client
+        .remove_regions_from_replication()
+        .secret_id("test")
+        .remove_replica_regions("eu-west-1")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(remove_response.replication_status().is_empty());
+
+    let err = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ResourceNotFoundException"))
+    );
+
+    // Primary can be deleted once it has no replicas
+    us_client
+        .delete_secret()
+        .secret_id("test")
+        .force_delete_without_recovery(true)
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that stopping replication promotes the replica to a standalone secret
+#[tokio::test]
+async fn test_stop_replication_to_replica() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    // Only replicas can be promoted
+    let err = us_client
+        .stop_replication_to_replica()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("InvalidRequestException"))
+    );
+
+    eu_client
+        .stop_replication_to_replica()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Promoted secret is now writable
+    eu_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("eu-value")
+        .send()
+        .await
+        .unwrap();
+
+    // Primary changes are no longer replicated
+    us_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("us-value")
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("eu-value"));
+
+    let describe_response = us_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(describe_response.replication_status().is_empty());
+}
+
+/// Tests filtering secrets by their primary region
+#[tokio::test]
+async fn test_list_secrets_primary_region_filter() {
+    let (us_client, eu_client, _server) = test_region_clients().await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    eu_client
+        .create_secret()
+        .name("test-2")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    let list_response = eu_client
+        .list_secrets()
+        .filters(
+            Filter::builder()
+                .key(FilterNameStringType::PrimaryRegion)
+                .values("us-east-1")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let secrets = list_response.secret_list();
+    assert_eq!(secrets.len(), 1);
+    assert_eq!(secrets[0].name(), Some("test"));
+    assert_eq!(secrets[0].primary_region(), Some("us-east-1"));
+}
+
+/// Create a KMS key in the region of the `kms_client` returning its ARN
+async fn create_key(kms_client: &aws_sdk_kms::Client) -> String {
+    kms_client
+        .create_key()
+        .send()
+        .await
+        .unwrap()
+        .key_metadata()
+        .unwrap()
+        .arn()
+        .unwrap()
+        .to_string()
+}
+
+/// Create a test server along with Secrets Manager and KMS clients for the us-east-1
+/// and eu-west-1 regions
+async fn test_region_kms_clients() -> (
+    aws_sdk_secretsmanager::Client,
+    aws_sdk_secretsmanager::Client,
+    aws_sdk_kms::Client,
+    aws_sdk_kms::Client,
+    TestServer,
+) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let eu_sdk_config = sdk_config
+        .to_builder()
+        .region(Region::from_static("eu-west-1"))
+        .build();
+
+    (
+        aws_sdk_secretsmanager::Client::new(&sdk_config),
+        aws_sdk_secretsmanager::Client::new(&eu_sdk_config),
+        aws_sdk_kms::Client::new(&sdk_config),
+        aws_sdk_kms::Client::new(&eu_sdk_config),
+        TestServer { abort_handle, db },
+    )
+}
+
+/// Tests that the versions of a replica are encrypted using the KMS key of the replica
+/// rather than the key of the primary secret
+#[tokio::test]
+async fn test_replica_kms_key() {
+    let (us_client, eu_client, us_kms_client, eu_kms_client, _server) =
+        test_region_kms_clients().await;
+
+    let us_key_arn = create_key(&us_kms_client).await;
+    let eu_key_arn = create_key(&eu_kms_client).await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .kms_key_id(&us_key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(
+            ReplicaRegionType::builder()
+                .region("eu-west-1")
+                .kms_key_id(&eu_key_arn)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = eu_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.kms_key_id(), Some(eu_key_arn.as_str()));
+
+    // Replica is readable without the key of the primary secret
+    us_kms_client
+        .disable_key()
+        .key_id(&us_key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+
+    us_kms_client
+        .enable_key()
+        .key_id(&us_key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    // New versions are encrypted with the key of the replica
+    us_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-2")
+        .send()
+        .await
+        .unwrap();
+
+    eu_kms_client
+        .disable_key()
+        .key_id(&eu_key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    let err = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("KMSInvalidStateException"))
+    );
+
+    eu_kms_client
+        .enable_key()
+        .key_id(&eu_key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test-2"));
+}
+
+/// Tests that a replica without a KMS key uses the default key of its region
+#[tokio::test]
+async fn test_replica_default_kms_key() {
+    let (us_client, eu_client, us_kms_client, _eu_kms_client, _server) =
+        test_region_kms_clients().await;
+
+    let us_key_arn = create_key(&us_kms_client).await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .kms_key_id(&us_key_arn)
+        .send()
+        .await
+        .unwrap();
+
+    us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(eu_replica())
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = eu_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.kms_key_id(), None);
+
+    let get_response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("test"));
+}
+
+/// Tests that replication fails when the KMS key of the replica doesn't exist in the
+/// replica region
+#[tokio::test]
+async fn test_replica_unknown_kms_key() {
+    let (us_client, eu_client, us_kms_client, _eu_kms_client, _server) =
+        test_region_kms_clients().await;
+
+    // Key only exists in the region of the primary secret
+    let us_key_arn = create_key(&us_kms_client).await;
+
+    us_client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let replicate_response = us_client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(
+            ReplicaRegionType::builder()
+                .region("eu-west-1")
+                .kms_key_id(&us_key_arn)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let status = &replicate_response.replication_status()[0];
+    assert_eq!(status.region(), Some("eu-west-1"));
+    assert_eq!(status.status(), Some(&StatusType::Failed));
+
+    let err = eu_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ResourceNotFoundException"))
+    );
+}
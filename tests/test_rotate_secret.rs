@@ -0,0 +1,1098 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use aws_sdk_secretsmanager::{
+    config::Credentials,
+    error::SdkError,
+    operation::rotate_secret::RotateSecretError,
+    types::{
+        RotationRulesType,
+        error::{InvalidParameterException, InvalidRequestException, ResourceNotFoundException},
+    },
+};
+use axum::{Extension, Json, Router, http::StatusCode, routing::post};
+use chrono::{Days, Utc};
+use loker::{
+    database::{
+        DbHandle,
+        secrets::{get_secret_rotation_steps, set_secret_next_rotation},
+    },
+    rotation::RotationRunner,
+};
+use serde::Deserialize;
+use tokio::{net::TcpListener, sync::Notify, task::AbortHandle};
+
+use crate::common::{
+    TestServer, start_test_server_with_rotation, test_memory_database, test_sdk_config, test_server,
+};
+
+mod common;
+
+const TEST_LAMBDA_ARN: &str = "arn:aws:lambda:us-east-1:1:function:test";
+
+#[derive(Deserialize)]
+struct RotationEvent {
+    #[serde(rename = "Step")]
+    step: String,
+    #[serde(rename = "SecretId")]
+    secret_id: String,
+    #[serde(rename = "ClientRequestToken")]
+    client_request_token: String,
+}
+
+/// State for the stand-in rotation webhook
+#[derive(Clone)]
+struct TestWebhook {
+    client: aws_sdk_secretsmanager::Client,
+    /// Steps received by the webhook
+    steps: Arc<Mutex<Vec<String>>>,
+    /// Step the webhook should fail on
+    fail_step: Option<&'static str>,
+    /// Step the webhook should wait on until released
+    hold_step: Option<&'static str>,
+    /// Releases the webhook waiting on the `hold_step`
+    release: Arc<Notify>,
+}
+
+/// Stand-in rotation function that performs the rotation against the server
+async fn handle_rotation_event(
+    Extension(webhook): Extension<TestWebhook>,
+    Json(event): Json<RotationEvent>,
+) -> StatusCode {
+    webhook.steps.lock().unwrap().push(event.step.clone());
+
+    if webhook.hold_step == Some(event.step.as_str()) {
+        webhook.release.notified().await;
+    }
+
+    if webhook.fail_step == Some(event.step.as_str()) {
+        return StatusCode::INTERNAL_SERVER_ERROR;
+    }
+
+    match event.step.as_str() {
+        "createSecret" => {
+            webhook
+                .client
+                .put_secret_value()
+                .secret_id(&event.secret_id)
+                .client_request_token(&event.client_request_token)
+                .secret_string("test-rotated")
+                .version_stages("AWSPENDING")
+                .send()
+                .await
+                .unwrap();
+        }
+        "finishSecret" => {
+            let describe_response = webhook
+                .client
+                .describe_secret()
+                .secret_id(&event.secret_id)
+                .send()
+                .await
+                .unwrap();
+
+            let current_version_id = describe_response
+                .version_ids_to_stages()
+                .unwrap()
+                .iter()
+                .find(|(_, stages)| stages.contains(&"AWSCURRENT".to_string()))
+                .map(|(version_id, _)| version_id.clone())
+                .unwrap();
+
+            webhook
+                .client
+                .update_secret_version_stage()
+                .secret_id(&event.secret_id)
+                .version_stage("AWSCURRENT")
+                .move_to_version_id(&event.client_request_token)
+                .remove_from_version_id(current_version_id)
+                .send()
+                .await
+                .unwrap();
+        }
+        _ => {}
+    }
+
+    StatusCode::OK
+}
+
+/// Start a test server with [TEST_LAMBDA_ARN] mapped to a stand-in webhook
+async fn test_server_with_webhook(
+    fail_step: Option<&'static str>,
+) -> (
+    aws_sdk_secretsmanager::Client,
+    TestServer,
+    Arc<Mutex<Vec<String>>>,
+    AbortHandle,
+) {
+    let (client, server, steps, _release, webhook_abort_handle) =
+        test_server_with_held_webhook(fail_step, None).await;
+
+    (client, server, steps, webhook_abort_handle)
+}
+
+/// Start a test server with [TEST_LAMBDA_ARN] mapped to a stand-in webhook that
+/// waits on the `hold_step` until released through the provided [Notify]
+async fn test_server_with_held_webhook(
+    fail_step: Option<&'static str>,
+    hold_step: Option<&'static str>,
+) -> (
+    aws_sdk_secretsmanager::Client,
+    TestServer,
+    Arc<Mutex<Vec<String>>>,
+    Arc<Notify>,
+    AbortHandle,
+) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let webhook_address = listener.local_addr().unwrap();
+
+    let rotation = RotationRunner::new(HashMap::from([(
+        TEST_LAMBDA_ARN.to_string(),
+        format!("http://{webhook_address}/"),
+    )]));
+
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) =
+        start_test_server_with_rotation(db.clone(), credentials.clone(), rotation).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    let steps = Arc::new(Mutex::new(Vec::new()));
+    let release = Arc::new(Notify::new());
+    let webhook = TestWebhook {
+        client: client.clone(),
+        steps: steps.clone(),
+        fail_step,
+        hold_step,
+        release: release.clone(),
+    };
+
+    let webhook_abort_handle = tokio::spawn(async move {
+        let app = Router::new()
+            .route("/", post(handle_rotation_event))
+            .layer(Extension(webhook));
+        axum::serve(listener, app).await.unwrap();
+    })
+    .abort_handle();
+
+    (
+        client,
+        TestServer { abort_handle, db },
+        steps,
+        release,
+        webhook_abort_handle,
+    )
+}
+
+/// Wait for the rotation of the secret `secret_id` to complete, rotations are
+/// performed after the RotateSecret request has been responded to
+async fn wait_for_rotation(client: &aws_sdk_secretsmanager::Client, secret_id: &str) {
+    for _ in 0..100 {
+        let describe_response = client
+            .describe_secret()
+            .secret_id(secret_id)
+            .send()
+            .await
+            .unwrap();
+
+        if describe_response.last_rotated_date().is_some() {
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    panic!("rotation of {secret_id} did not complete");
+}
+
+/// Wait for `count` rotation steps to be recorded for the secret with the `arn`
+async fn wait_for_rotation_steps(db: &DbHandle, arn: &str, count: usize) {
+    for _ in 0..100 {
+        let arn = arn.to_string();
+        let recorded = db
+            .call_unwrap(move |db| get_secret_rotation_steps(db, &arn))
+            .await
+            .unwrap();
+
+        if recorded.len() >= count {
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    panic!("rotation of {arn} did not record {count} steps");
+}
+
+/// Tests that rotating a secret creates a new current version
+#[tokio::test]
+async fn test_rotate_secret_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(rotate_response.arn(), create_response.arn());
+    assert_eq!(rotate_response.name(), create_response.name());
+
+    let rotated_version_id = rotate_response.version_id().unwrap();
+    assert_ne!(Some(rotated_version_id), create_response.version_id());
+
+    wait_for_rotation(&client, "test").await;
+
+    // Rotated version should now be the current version
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), Some(rotated_version_id));
+    assert_eq!(get_response.version_stages(), &["AWSCURRENT".to_string()]);
+    assert_eq!(get_response.secret_string(), Some("test"));
+
+    // Original version should now be the previous version
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_stage("AWSPREVIOUS")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), create_response.version_id());
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(true));
+    assert_eq!(
+        describe_response
+            .rotation_rules()
+            .and_then(|rules| rules.automatically_after_days()),
+        Some(30)
+    );
+    assert!(describe_response.last_rotated_date().unwrap().secs() > 0);
+
+    // Next rotation should be scheduled 30 days after the last rotation
+    let last_rotated_date = describe_response.last_rotated_date().unwrap().secs();
+    let next_rotation_date = describe_response.next_rotation_date().unwrap().secs();
+    assert_eq!(next_rotation_date - last_rotated_date, 30 * 24 * 60 * 60);
+
+    // No version should be left pending
+    let stages = describe_response.version_ids_to_stages().unwrap();
+    assert!(
+        stages
+            .values()
+            .all(|stages| !stages.contains(&"AWSPENDING".to_string()))
+    );
+}
+
+/// Tests that the rotation lambda ARN is stored on the secret
+#[tokio::test]
+async fn test_rotate_secret_lambda_arn_success() {
+    let (client, _server, _steps, _webhook) = test_server_with_webhook(None).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_LAMBDA_ARN)
+        .send()
+        .await
+        .unwrap();
+
+    wait_for_rotation(&client, "test").await;
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        describe_response.rotation_lambda_arn(),
+        Some(TEST_LAMBDA_ARN)
+    );
+}
+
+/// Tests that a rotation lambda ARN without a configured webhook is rejected
+#[tokio::test]
+async fn test_rotate_secret_unknown_lambda_arn_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn("arn:aws:lambda:us-east-1:1:function:unknown")
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidParameterException = match rotate_err.into_err() {
+        RotateSecretError::InvalidParameterException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidParameterException got {error:?}"),
+    };
+
+    // Rotation should not have been enabled
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(false));
+    assert_eq!(describe_response.rotation_lambda_arn(), None);
+}
+
+/// Tests that rotation can be enabled without rotating immediately
+#[tokio::test]
+async fn test_rotate_secret_not_immediately_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotate_immediately(false)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(7)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    // No rotation should have happened
+    assert_eq!(rotate_response.version_id(), None);
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), create_response.version_id());
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(true));
+    assert_eq!(describe_response.last_rotated_date(), None);
+    assert!(describe_response.next_rotation_date().unwrap().secs() > 0);
+}
+
+/// Tests that rotating a secret using the version ID of an incomplete rotation
+/// will reattempt the pending version
+#[tokio::test]
+async fn test_rotate_secret_pending_version_success() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let pending_response = client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-pending")
+        .version_stages("AWSPENDING")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .client_request_token(pending_response.version_id().unwrap())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(rotate_response.version_id(), pending_response.version_id());
+
+    wait_for_rotation(&client, "test").await;
+
+    // Pending version should have been promoted
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), pending_response.version_id());
+    assert_eq!(get_response.secret_string(), Some("test-pending"));
+}
+
+/// Tests that a different rotation cannot be started while another is pending
+#[tokio::test]
+async fn test_rotate_secret_pending_version_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-pending")
+        .version_stages("AWSPENDING")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .client_request_token("EXAMPLE1-90ab-cdef-fedc-ba987SECRET1")
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidRequestException = match rotate_err.into_err() {
+        RotateSecretError::InvalidRequestException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidRequestException got {error:?}"),
+    };
+}
+
+/// Tests that a new rotation cannot use the version ID of an existing version
+#[tokio::test]
+async fn test_rotate_secret_existing_version_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .client_request_token("EXAMPLE1-90ab-cdef-fedc-ba987SECRET1")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-2")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .client_request_token("EXAMPLE1-90ab-cdef-fedc-ba987SECRET1")
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidRequestException = match rotate_err.into_err() {
+        RotateSecretError::InvalidRequestException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidRequestException got {error:?}"),
+    };
+
+    // The original version must not have been promoted back to AWSCURRENT
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.secret_string(), Some("test-2"));
+}
+
+/// Tests that a secret scheduled for deletion cannot be rotated
+#[tokio::test]
+async fn test_rotate_secret_scheduled_deletion_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .delete_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidRequestException = match rotate_err.into_err() {
+        RotateSecretError::InvalidRequestException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidRequestException got {error:?}"),
+    };
+}
+
+/// Tests that trying to rotate an unknown secret will fail
+#[tokio::test]
+async fn test_rotate_secret_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match rotate_err.into_err() {
+        RotateSecretError::ResourceNotFoundException(error) => error,
+        error => panic!("expected RotateSecretError::ResourceNotFoundException got {error:?}"),
+    };
+}
+
+/// Tests that a rotation lambda mapped to a webhook has each rotation
+/// step sent to the webhook
+#[tokio::test]
+async fn test_rotate_secret_webhook_success() {
+    let (client, server, steps, _webhook) = test_server_with_webhook(None).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_LAMBDA_ARN)
+        .send()
+        .await
+        .unwrap();
+
+    wait_for_rotation(&client, "test").await;
+
+    assert_eq!(
+        steps.lock().unwrap().as_slice(),
+        &["createSecret", "setSecret", "testSecret", "finishSecret"]
+    );
+
+    // Webhook created version should now be the current version
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), rotate_response.version_id());
+    assert_eq!(get_response.secret_string(), Some("test-rotated"));
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(describe_response.last_rotated_date().is_some());
+
+    // All the steps should be recorded as successful
+    let arn = create_response.arn().unwrap().to_string();
+    let recorded = server
+        .db
+        .call_unwrap(move |db| get_secret_rotation_steps(db, &arn))
+        .await
+        .unwrap();
+
+    assert_eq!(recorded.len(), 4);
+    assert!(recorded.iter().all(|step| step.success));
+}
+
+/// Tests that RotateSecret responds before the rotation steps complete and that
+/// another rotation cannot be started while one is in progress
+#[tokio::test]
+async fn test_rotate_secret_in_progress_error() {
+    let (client, _server, steps, release, _webhook) =
+        test_server_with_held_webhook(None, Some("setSecret")).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Webhook is held on the setSecret step so the rotation can't have completed
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_LAMBDA_ARN)
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidRequestException = match rotate_err.into_err() {
+        RotateSecretError::InvalidRequestException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidRequestException got {error:?}"),
+    };
+
+    release.notify_one();
+    wait_for_rotation(&client, "test").await;
+
+    assert_eq!(
+        steps.lock().unwrap().as_slice(),
+        &["createSecret", "setSecret", "testSecret", "finishSecret"]
+    );
+}
+
+/// Tests that a failing webhook step stops the rotation and leaves
+/// the AWSPENDING version in place
+#[tokio::test]
+async fn test_rotate_secret_webhook_failure() {
+    let (client, server, steps, _webhook) = test_server_with_webhook(Some("testSecret")).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_LAMBDA_ARN)
+        .send()
+        .await
+        .unwrap();
+
+    let arn = create_response.arn().unwrap().to_string();
+    wait_for_rotation_steps(&server.db, &arn, 3).await;
+
+    // Rotation should stop at the failed step
+    assert_eq!(
+        steps.lock().unwrap().as_slice(),
+        &["createSecret", "setSecret", "testSecret"]
+    );
+
+    // Original version should still be current
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), create_response.version_id());
+
+    // Pending version should be left in place
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_stage("AWSPENDING")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), rotate_response.version_id());
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.last_rotated_date(), None);
+
+    // Failed step should be recorded
+    let recorded = server
+        .db
+        .call_unwrap(move |db| get_secret_rotation_steps(db, &arn))
+        .await
+        .unwrap();
+
+    let outcomes: Vec<(&str, bool)> = recorded
+        .iter()
+        .map(|step| (step.step.as_str(), step.success))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("createSecret", true),
+            ("setSecret", true),
+            ("testSecret", false)
+        ]
+    );
+    assert!(recorded[2].message.is_some());
+}
+
+/// Tests that a schedule expression is used to compute the next rotation date
+#[tokio::test]
+async fn test_rotate_secret_schedule_expression_success() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let before = Utc::now().timestamp();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotate_immediately(false)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .schedule_expression("rate(10 days)")
+                .duration("3h")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let after = Utc::now().timestamp();
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotation_rules = describe_response.rotation_rules().unwrap();
+    assert_eq!(rotation_rules.schedule_expression(), Some("rate(10 days)"));
+    assert_eq!(rotation_rules.duration(), Some("3h"));
+
+    // Next rotation should be scheduled 10 days from now
+    let next_rotation_date = describe_response.next_rotation_date().unwrap().secs();
+    let ten_days = 10 * 24 * 60 * 60;
+    assert!(next_rotation_date >= before + ten_days);
+    assert!(next_rotation_date <= after + ten_days);
+}
+
+/// Tests that an invalid schedule expression is rejected
+#[tokio::test]
+async fn test_rotate_secret_schedule_expression_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_rules(
+            RotationRulesType::builder()
+                .schedule_expression("rate(10 weeks)")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidParameterException = match rotate_err.into_err() {
+        RotateSecretError::InvalidParameterException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidParameterException got {error:?}"),
+    };
+}
+
+/// Tests that secrets which have reached their next rotation date are rotated
+#[tokio::test]
+async fn test_rotate_due_secrets_success() {
+    let (client, server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotate_immediately(false)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(1)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let rotation = RotationRunner::default();
+
+    // Secret isn't due yet so shouldn't be rotated
+    rotation.rotate_due_secrets(&server.db).await.unwrap();
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.version_id(), create_response.version_id());
+
+    // Move the next rotation into the past
+    let arn = create_response.arn().unwrap().to_string();
+    let next_rotation_at = Utc::now().checked_sub_days(Days::new(1));
+    server
+        .db
+        .call_unwrap(move |db| set_secret_next_rotation(db, &arn, next_rotation_at))
+        .await
+        .unwrap();
+
+    rotation.rotate_due_secrets(&server.db).await.unwrap();
+    wait_for_rotation(&client, "test").await;
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_ne!(get_response.version_id(), create_response.version_id());
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Next rotation should be scheduled a day after the rotation
+    let last_rotated_date = describe_response.last_rotated_date().unwrap().secs();
+    let next_rotation_date = describe_response.next_rotation_date().unwrap().secs();
+    assert_eq!(next_rotation_date - last_rotated_date, 24 * 60 * 60);
+}
+
+/// Tests that a rotation webhook that doesn't respond doesn't hold up the
+/// scheduled rotation of other secrets
+#[tokio::test]
+async fn test_rotate_due_secrets_unresponsive_webhook() {
+    // Webhook that accepts connections but never responds
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let webhook_address = listener.local_addr().unwrap();
+    let webhook_abort_handle = tokio::spawn(async move {
+        let mut connections = Vec::new();
+        loop {
+            let (connection, _) = listener.accept().await.unwrap();
+            connections.push(connection);
+        }
+    })
+    .abort_handle();
+
+    let rotation = RotationRunner::new(HashMap::from([(
+        TEST_LAMBDA_ARN.to_string(),
+        format!("http://{webhook_address}/"),
+    )]));
+
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) =
+        start_test_server_with_rotation(db.clone(), credentials.clone(), rotation.clone()).await;
+    let server = TestServer { abort_handle, db };
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    let mut arns = Vec::new();
+    for (name, rotation_lambda_arn) in [("test-webhook", Some(TEST_LAMBDA_ARN)), ("test", None)] {
+        let create_response = client
+            .create_secret()
+            .name(name)
+            .secret_string("test")
+            .send()
+            .await
+            .unwrap();
+
+        client
+            .rotate_secret()
+            .secret_id(name)
+            .set_rotation_lambda_arn(rotation_lambda_arn.map(str::to_string))
+            .rotate_immediately(false)
+            .rotation_rules(
+                RotationRulesType::builder()
+                    .automatically_after_days(1)
+                    .build(),
+            )
+            .send()
+            .await
+            .unwrap();
+
+        arns.push(create_response.arn().unwrap().to_string());
+    }
+
+    // Move the next rotations into the past
+    for arn in arns {
+        let next_rotation_at = Utc::now().checked_sub_days(Days::new(1));
+        server
+            .db
+            .call_unwrap(move |db| set_secret_next_rotation(db, &arn, next_rotation_at))
+            .await
+            .unwrap();
+    }
+
+    // Rotations are started without waiting for the webhook
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        rotation.rotate_due_secrets(&server.db),
+    )
+    .await
+    .expect("scheduled rotation waited on the webhook")
+    .unwrap();
+
+    // Secret rotated locally isn't held up by the webhook
+    wait_for_rotation(&client, "test").await;
+
+    // Secret waiting on the webhook is still being rotated
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test-webhook")
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidRequestException = match rotate_err.into_err() {
+        RotateSecretError::InvalidRequestException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidRequestException got {error:?}"),
+    };
+
+    webhook_abort_handle.abort();
+}
@@ -0,0 +1,327 @@
+use crate::common::{TestServer, start_test_server, test_memory_database, test_sdk_config};
+use aws_credential_types::Credentials;
+use loker::{
+    arn::ArnConfig,
+    database::{DbHandle, transaction},
+    dump::ImportSummary,
+    seed::{SeedError, SeedFile, SeedMode, apply_seed, load_seed_file},
+};
+use uuid::Uuid;
+
+mod common;
+
+/// Create a test server using the `db` along with a client for the server
+async fn test_client(db: DbHandle) -> (aws_sdk_secretsmanager::Client, TestServer) {
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    (client, TestServer { abort_handle, db })
+}
+
+/// Parse a JSON seed file
+fn seed_file(value: &str) -> SeedFile {
+    serde_json::from_str(value).unwrap()
+}
+
+async fn seed(db: &DbHandle, mode: SeedMode, seed: SeedFile) -> Result<ImportSummary, SeedError> {
+    db.call(move |db| {
+        transaction(db, move |t| {
+            apply_seed(t, &ArnConfig::default(), mode, seed)
+        })
+    })
+    .await
+    .map_err(|error| match error {
+        tokio_rusqlite::Error::Error(error) => error,
+        error => panic!("unexpected error {error}"),
+    })
+}
+
+/// Tests that the secrets from a seed file are created along with their versions and tags
+#[tokio::test]
+async fn test_seed_secrets() {
+    let db = test_memory_database().await;
+    let (client, _server) = test_client(db.clone()).await;
+
+    let summary = seed(
+        &db,
+        SeedMode::CreateIfMissing,
+        seed_file(
+            r#"{
+                "secrets": [
+                    {
+                        "name": "test",
+                        "description": "test description",
+                        "secret_string": "current",
+                        "tags": { "team": "backend" },
+                        "versions": [
+                            {
+                                "version_id": "00000000-0000-0000-0000-000000000001",
+                                "secret_string": "previous",
+                                "version_stages": ["AWSPREVIOUS"]
+                            }
+                        ]
+                    },
+                    { "name": "test-eu", "region": "eu-west-1", "secret_binary": "dGVzdA==" }
+                ]
+            }"#,
+        ),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(summary.secrets_created, 2);
+    assert_eq!(summary.versions_created, 3);
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("current"));
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_stage("AWSPREVIOUS")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("previous"));
+    assert_eq!(
+        get_response.version_id(),
+        Some("00000000-0000-0000-0000-000000000001")
+    );
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.description(), Some("test description"));
+    assert_eq!(describe_response.tags().len(), 1);
+}
+
+/// Tests that existing secrets are left as is when only creating missing secrets
+#[tokio::test]
+async fn test_seed_create_if_missing() {
+    let db = test_memory_database().await;
+    let (client, _server) = test_client(db.clone()).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("existing")
+        .send()
+        .await
+        .unwrap();
+
+    let summary = seed(
+        &db,
+        SeedMode::CreateIfMissing,
+        seed_file(r#"{ "secrets": [{ "name": "test", "secret_string": "seeded" }] }"#),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(summary.secrets_created, 0);
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("existing"));
+}
+
+/// Tests that existing secrets are replaced when overwriting
+#[tokio::test]
+async fn test_seed_overwrite() {
+    let db = test_memory_database().await;
+    let (client, _server) = test_client(db.clone()).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("existing")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .create_secret()
+        .name("other")
+        .secret_string("other")
+        .send()
+        .await
+        .unwrap();
+
+    seed(
+        &db,
+        SeedMode::Overwrite,
+        seed_file(r#"{ "secrets": [{ "name": "test", "secret_string": "seeded" }] }"#),
+    )
+    .await
+    .unwrap();
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.secret_string(), Some("seeded"));
+
+    // Secrets not in the seed file are kept
+    client
+        .get_secret_value()
+        .secret_id("other")
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that all existing secrets are removed when resetting
+#[tokio::test]
+async fn test_seed_reset() {
+    let db = test_memory_database().await;
+    let (client, _server) = test_client(db.clone()).await;
+
+    client
+        .create_secret()
+        .name("other")
+        .secret_string("other")
+        .send()
+        .await
+        .unwrap();
+
+    seed(
+        &db,
+        SeedMode::Reset,
+        seed_file(r#"{ "secrets": [{ "name": "test", "secret_string": "seeded" }] }"#),
+    )
+    .await
+    .unwrap();
+
+    let err = client
+        .get_secret_value()
+        .secret_id("other")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ResourceNotFoundException"))
+    );
+
+    client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that a seed file is rejected when a secret has no current version
+/// and nothing is created
+#[tokio::test]
+async fn test_seed_missing_current_version() {
+    let db = test_memory_database().await;
+    let (client, _server) = test_client(db.clone()).await;
+
+    let err = seed(
+        &db,
+        SeedMode::CreateIfMissing,
+        seed_file(
+            r#"{
+                "secrets": [
+                    { "name": "valid", "secret_string": "valid" },
+                    {
+                        "name": "test",
+                        "versions": [{ "secret_string": "test", "version_stages": ["AWSPREVIOUS"] }]
+                    }
+                ]
+            }"#,
+        ),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(err, SeedError::MissingCurrentVersion(_)));
+
+    let err = client
+        .get_secret_value()
+        .secret_id("valid")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ResourceNotFoundException"))
+    );
+}
+
+/// Tests that TOML seed files can be loaded
+#[tokio::test]
+async fn test_load_toml_seed_file() {
+    let path = std::env::temp_dir().join(format!("loker-seed-{}.toml", Uuid::new_v4()));
+
+    std::fs::write(
+        &path,
+        r#"
+        [[secrets]]
+        name = "test"
+        secret_string = "test"
+        tags = { team = "backend" }
+        "#,
+    )
+    .unwrap();
+
+    let seed = load_seed_file(&path.to_string_lossy()).unwrap();
+    assert_eq!(seed.secrets.len(), 1);
+    assert_eq!(seed.secrets[0].tags.len(), 1);
+
+    _ = std::fs::remove_file(path);
+}
+
+/// Tests that YAML seed files can be loaded
+#[tokio::test]
+async fn test_load_yaml_seed_file() {
+    for extension in ["yaml", "yml"] {
+        let path = std::env::temp_dir().join(format!("loker-seed-{}.{extension}", Uuid::new_v4()));
+
+        std::fs::write(
+            &path,
+            r#"
+secrets:
+  - name: test
+    secret_string: test
+    tags:
+      team: backend
+    versions:
+      - secret_string: previous
+        version_stages: [AWSPREVIOUS]
+"#,
+        )
+        .unwrap();
+
+        let seed = load_seed_file(&path.to_string_lossy()).unwrap();
+        assert_eq!(seed.secrets.len(), 1);
+        assert_eq!(seed.secrets[0].tags.len(), 1);
+        assert_eq!(seed.secrets[0].versions.len(), 1);
+
+        _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::common::{TestServer, error_type, start_test_server, test_memory_database};
+use aws_credential_types::Credentials;
+use aws_sigv4::{
+    http_request::{SignableBody, SignableRequest, SignatureLocation, SigningSettings, sign},
+    sign::v4a::SigningParams,
+};
+use std::{
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+mod common;
+
+/// Target of the requests made by the tests
+const CREATE_SECRET_TARGET: &str = "secretsmanager.CreateSecret";
+
+/// Create a CreateSecret request for the secret `name` signed using SigV4A for
+/// the `region_set` with the signature provided in the `signature_location`
+fn create_secret_request(
+    server_address: SocketAddr,
+    credentials: &Credentials,
+    region_set: &str,
+    signature_location: SignatureLocation,
+    name: &str,
+) -> reqwest::RequestBuilder {
+    let url = format!("http://{server_address}/");
+    let body = serde_json::json!({ "Name": name, "SecretString": "test" }).to_string();
+
+    let mut signing_settings = SigningSettings::default();
+    signing_settings.signature_location = signature_location;
+    if signature_location == SignatureLocation::QueryParams {
+        signing_settings.expires_in = Some(Duration::from_secs(60));
+    }
+
+    let identity = credentials.clone().into();
+    let signing_params = SigningParams::builder()
+        .identity(&identity)
+        .region_set(region_set)
+        .name("secretsmanager")
+        .time(SystemTime::now())
+        .settings(signing_settings)
+        .build()
+        .unwrap()
+        .into();
+
+    let signable_request = SignableRequest::new(
+        "POST",
+        &url,
+        [("x-amz-target", CREATE_SECRET_TARGET)].into_iter(),
+        SignableBody::Bytes(body.as_bytes()),
+    )
+    .unwrap();
+
+    let (signing_instructions, _signature) = sign(signable_request, &signing_params)
+        .unwrap()
+        .into_parts();
+
+    let query = serde_urlencoded::to_string(signing_instructions.params()).unwrap();
+    let url = if query.is_empty() {
+        url
+    } else {
+        format!("{url}?{query}")
+    };
+
+    let mut request = reqwest::Client::new()
+        .post(url)
+        .header("x-amz-target", CREATE_SECRET_TARGET)
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(body);
+
+    for (name, value) in signing_instructions.headers() {
+        request = request.header(name, value);
+    }
+
+    request
+}
+
+/// Send the CreateSecret `request` expecting it to succeed, provides the ARN of
+/// the created secret
+async fn create_secret_arn(request: reqwest::RequestBuilder) -> String {
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["ARN"].as_str().unwrap().to_string()
+}
+
+/// Start a test server accepting the test credentials
+async fn test_sigv4a_server() -> (SocketAddr, Credentials, TestServer) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) = start_test_server(db.clone(), credentials.clone()).await;
+
+    (server_address, credentials, TestServer { abort_handle, db })
+}
+
+/// Tests that a request signed for a single region uses that region
+#[tokio::test]
+async fn test_sigv4a_single_region_success() {
+    let (server_address, credentials, _server) = test_sigv4a_server().await;
+
+    let arn = create_secret_arn(create_secret_request(
+        server_address,
+        &credentials,
+        "eu-west-2",
+        SignatureLocation::Headers,
+        "test",
+    ))
+    .await;
+
+    assert!(arn.starts_with("arn:aws:secretsmanager:eu-west-2:"));
+}
+
+/// Tests that requests signed for multiple or any regions use the default region
+#[tokio::test]
+async fn test_sigv4a_multiple_regions_success() {
+    let (server_address, credentials, _server) = test_sigv4a_server().await;
+
+    for (region_set, name) in [("eu-west-2,us-west-1", "test-1"), ("*", "test-2")] {
+        let arn = create_secret_arn(create_secret_request(
+            server_address,
+            &credentials,
+            region_set,
+            SignatureLocation::Headers,
+            name,
+        ))
+        .await;
+
+        assert!(arn.starts_with("arn:aws:secretsmanager:us-east-1:"));
+    }
+}
+
+/// Tests that a request without the region set it was signed for is rejected
+#[tokio::test]
+async fn test_sigv4a_missing_region_set_error() {
+    let (server_address, credentials, _server) = test_sigv4a_server().await;
+
+    let mut request = create_secret_request(
+        server_address,
+        &credentials,
+        "eu-west-2",
+        SignatureLocation::Headers,
+        "test",
+    )
+    .build()
+    .unwrap();
+
+    assert!(request.headers_mut().remove("x-amz-region-set").is_some());
+
+    let response = reqwest::Client::new().execute(request).await.unwrap();
+    assert_eq!(error_type(&response), Some("IncompleteSignature"));
+}
+
+/// Tests that a request presigned using SigV4A uses the region it was signed for
+#[tokio::test]
+async fn test_sigv4a_presigned_success() {
+    let (server_address, credentials, _server) = test_sigv4a_server().await;
+
+    let request = create_secret_request(
+        server_address,
+        &credentials,
+        "eu-west-2",
+        SignatureLocation::QueryParams,
+        "test",
+    );
+
+    let url = request.try_clone().unwrap().build().unwrap().url().clone();
+    assert!(url.query().is_some_and(|query| {
+        query.contains("X-Amz-Algorithm=AWS4-ECDSA-P256-SHA256")
+            && query.contains("X-Amz-Region-Set=eu-west-2")
+    }));
+
+    let arn = create_secret_arn(request).await;
+    assert!(arn.starts_with("arn:aws:secretsmanager:eu-west-2:"));
+}
+
+/// Tests that a request with the region set changed after signing is rejected
+#[tokio::test]
+async fn test_sigv4a_changed_region_set_error() {
+    let (server_address, credentials, _server) = test_sigv4a_server().await;
+
+    let mut request = create_secret_request(
+        server_address,
+        &credentials,
+        "eu-west-2",
+        SignatureLocation::Headers,
+        "test",
+    )
+    .build()
+    .unwrap();
+
+    request
+        .headers_mut()
+        .insert("x-amz-region-set", "us-west-1".parse().unwrap());
+
+    let response = reqwest::Client::new().execute(request).await.unwrap();
+    assert_eq!(error_type(&response), Some("InvalidSignatureException"));
+}
+
+/// Tests that a request that doesn't sign its region set is rejected
+#[tokio::test]
+async fn test_sigv4a_unsigned_region_set_error() {
+    let (server_address, credentials, _server) = test_sigv4a_server().await;
+
+    let mut request = create_secret_request(
+        server_address,
+        &credentials,
+        "eu-west-2",
+        SignatureLocation::Headers,
+        "test",
+    )
+    .build()
+    .unwrap();
+
+    let authorization = request.headers()["authorization"].to_str().unwrap();
+    assert!(authorization.contains("x-amz-region-set"));
+
+    let authorization = authorization
+        .replace(";x-amz-region-set", "")
+        .replace("x-amz-region-set;", "");
+    request
+        .headers_mut()
+        .insert("authorization", authorization.parse().unwrap());
+    request
+        .headers_mut()
+        .insert("x-amz-region-set", "us-west-1".parse().unwrap());
+
+    let response = reqwest::Client::new().execute(request).await.unwrap();
+    assert_eq!(error_type(&response), Some("IncompleteSignature"));
+}
+
+/// Tests that a presigned request with the region set changed after signing
+/// is rejected
+#[tokio::test]
+async fn test_sigv4a_presigned_changed_region_set_error() {
+    let (server_address, credentials, _server) = test_sigv4a_server().await;
+
+    let mut request = create_secret_request(
+        server_address,
+        &credentials,
+        "eu-west-2",
+        SignatureLocation::QueryParams,
+        "test",
+    )
+    .build()
+    .unwrap();
+
+    let url = request
+        .url()
+        .as_str()
+        .replace("X-Amz-Region-Set=eu-west-2", "X-Amz-Region-Set=us-west-1");
+    *request.url_mut() = url.parse().unwrap();
+
+    let response = reqwest::Client::new().execute(request).await.unwrap();
+    assert_eq!(error_type(&response), Some("InvalidSignatureException"));
+}
@@ -0,0 +1,303 @@
+use crate::common::{
+    TestServer, start_test_server_with_layer, test_memory_database, test_sdk_config,
+};
+use aws_credential_types::Credentials;
+use aws_sigv4::{
+    http_request::{SignableBody, SignableRequest, SigningSettings, sign},
+    sign::v4::SigningParams,
+};
+use loker::{
+    arn::ArnConfig,
+    middleware::aws_sig_v4::{AccessKey, AwsSigV4AuthLayer},
+    policy::{PolicyDocument, PolicyKind},
+    rotation::RotationRunner,
+};
+use std::{sync::Arc, time::SystemTime};
+
+mod common;
+
+/// Create a test server along with an STS client using the provided `access_key`
+async fn test_sts_server(access_key: AccessKey) -> (aws_sdk_sts::Client, String, TestServer) {
+    let db = test_memory_database().await;
+    let credentials = access_key.credentials.clone();
+    let auth_layer = AwsSigV4AuthLayer::from_access_keys([access_key]);
+
+    let (server_address, abort_handle) =
+        start_test_server_with_layer(db.clone(), auth_layer, RotationRunner::default()).await;
+
+    let endpoint_url = format!("http://{server_address}/");
+    let sdk_config = test_sdk_config(&endpoint_url, credentials);
+    let sts_client = aws_sdk_sts::Client::new(&sdk_config);
+
+    (sts_client, endpoint_url, TestServer { abort_handle, db })
+}
+
+/// Tests that GetCallerIdentity reports the principal of the access key
+#[tokio::test]
+async fn test_get_caller_identity_success() {
+    let (sts_client, _endpoint_url, _server) = test_sts_server(AccessKey {
+        principal_arn: "arn:aws:iam::123456789012:user/service-a".to_string(),
+        ..AccessKey::new(Credentials::for_tests(), &ArnConfig::default())
+    })
+    .await;
+
+    let response = sts_client.get_caller_identity().send().await.unwrap();
+
+    assert_eq!(
+        response.arn(),
+        Some("arn:aws:iam::123456789012:user/service-a")
+    );
+    assert_eq!(response.account(), Some("123456789012"));
+    assert_eq!(
+        response.user_id(),
+        Some(Credentials::for_tests().access_key_id())
+    );
+}
+
+/// Tests that credentials issued by AssumeRole can be used to make requests
+/// as the assumed role
+#[tokio::test]
+async fn test_assume_role_success() {
+    let (sts_client, endpoint_url, _server) = test_sts_server(AccessKey::new(
+        Credentials::for_tests(),
+        &ArnConfig::default(),
+    ))
+    .await;
+
+    let response = sts_client
+        .assume_role()
+        .role_arn("arn:aws:iam::1:role/service-role")
+        .role_session_name("test-session")
+        .send()
+        .await
+        .unwrap();
+
+    let assumed_role_user = response.assumed_role_user().unwrap();
+    assert_eq!(
+        assumed_role_user.arn(),
+        "arn:aws:sts::1:assumed-role/service-role/test-session"
+    );
+
+    let credentials = response.credentials().unwrap();
+    assert!(credentials.access_key_id().starts_with("ASIA"));
+
+    let assumed_credentials = Credentials::new(
+        credentials.access_key_id(),
+        credentials.secret_access_key(),
+        Some(credentials.session_token().to_string()),
+        None,
+        "test",
+    );
+
+    // Credentials should be accepted by the secrets manager
+    let sdk_config = test_sdk_config(&endpoint_url, assumed_credentials.clone());
+    let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Caller should now be the assumed role
+    let sts_client = aws_sdk_sts::Client::new(&sdk_config);
+    let response = sts_client.get_caller_identity().send().await.unwrap();
+
+    assert_eq!(
+        response.arn(),
+        Some("arn:aws:sts::1:assumed-role/service-role/test-session")
+    );
+}
+
+/// Tests that the credentials issued by AssumeRole expire after the requested duration
+#[tokio::test]
+async fn test_assume_role_duration() {
+    let (sts_client, _endpoint_url, _server) = test_sts_server(AccessKey::new(
+        Credentials::for_tests(),
+        &ArnConfig::default(),
+    ))
+    .await;
+
+    let response = sts_client
+        .assume_role()
+        .role_arn("arn:aws:iam::1:role/service-role")
+        .role_session_name("test-session")
+        .duration_seconds(900)
+        .send()
+        .await
+        .unwrap();
+
+    let expiration = response.credentials().unwrap().expiration().secs();
+    let now = chrono::Utc::now().timestamp();
+
+    assert!((expiration - now - 900).abs() <= 5);
+}
+
+/// Tests that credentials issued to temporary credentials don't outlive the
+/// credentials used to request them
+#[tokio::test]
+async fn test_assume_role_chained_duration() {
+    let (sts_client, endpoint_url, _server) = test_sts_server(AccessKey::new(
+        Credentials::for_tests(),
+        &ArnConfig::default(),
+    ))
+    .await;
+
+    let response = sts_client
+        .assume_role()
+        .role_arn("arn:aws:iam::1:role/service-role")
+        .role_session_name("test-session")
+        .duration_seconds(900)
+        .send()
+        .await
+        .unwrap();
+
+    let credentials = response.credentials().unwrap();
+    let caller_expiration = credentials.expiration().secs();
+
+    let assumed_credentials = Credentials::new(
+        credentials.access_key_id(),
+        credentials.secret_access_key(),
+        Some(credentials.session_token().to_string()),
+        None,
+        "test",
+    );
+
+    let sdk_config = test_sdk_config(&endpoint_url, assumed_credentials);
+    let sts_client = aws_sdk_sts::Client::new(&sdk_config);
+
+    // Requested duration is longer than the remaining lifetime of the caller
+    let response = sts_client
+        .assume_role()
+        .role_arn("arn:aws:iam::1:role/other-role")
+        .role_session_name("test-session")
+        .duration_seconds(3600)
+        .send()
+        .await
+        .unwrap();
+
+    let expiration = response.credentials().unwrap().expiration().secs();
+
+    assert_eq!(expiration, caller_expiration);
+}
+
+/// Tests that an invalid role ARN will fail
+#[tokio::test]
+async fn test_assume_role_invalid_role_arn_failure() {
+    let (sts_client, _endpoint_url, _server) = test_sts_server(AccessKey::new(
+        Credentials::for_tests(),
+        &ArnConfig::default(),
+    ))
+    .await;
+
+    let err = sts_client
+        .assume_role()
+        .role_arn("arn:aws:iam::1:user/not-a-role")
+        .role_session_name("test-session")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ValidationError"))
+    );
+}
+
+/// Tests that an identity without permission to assume the role will fail
+#[tokio::test]
+async fn test_assume_role_access_denied_failure() {
+    let policy = PolicyDocument::parse(
+        r#"{
+            "Version": "2012-10-17",
+            "Statement": [
+                {
+                    "Effect": "Allow",
+                    "Action": "sts:AssumeRole",
+                    "Resource": "arn:aws:iam::1:role/allowed-role"
+                }
+            ]
+        }"#,
+        PolicyKind::Identity,
+    )
+    .unwrap();
+
+    let (sts_client, _endpoint_url, _server) = test_sts_server(AccessKey {
+        policy: Some(Arc::new(policy)),
+        ..AccessKey::new(Credentials::for_tests(), &ArnConfig::default())
+    })
+    .await;
+
+    sts_client
+        .assume_role()
+        .role_arn("arn:aws:iam::1:role/allowed-role")
+        .role_session_name("test-session")
+        .send()
+        .await
+        .unwrap();
+
+    let err = sts_client
+        .assume_role()
+        .role_arn("arn:aws:iam::1:role/other-role")
+        .role_session_name("test-session")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("AccessDenied"))
+    );
+}
+
+/// Tests that form encoded requests to paths other than the root are not handled
+/// as STS requests
+#[tokio::test]
+async fn test_form_encoded_admin_request_not_sts() {
+    let (_sts_client, endpoint_url, _server) = test_sts_server(AccessKey::new(
+        Credentials::for_tests(),
+        &ArnConfig::default(),
+    ))
+    .await;
+
+    let url = format!("{endpoint_url}_loker/reset");
+    let content_type = "application/x-www-form-urlencoded";
+    let body = b"Action=GetCallerIdentity&Version=2011-06-15";
+
+    let identity = Credentials::for_tests().into();
+    let signing_params = SigningParams::builder()
+        .identity(&identity)
+        .region("us-east-1")
+        .name("secretsmanager")
+        .time(SystemTime::now())
+        .settings(SigningSettings::default())
+        .build()
+        .unwrap()
+        .into();
+
+    let signable_request = SignableRequest::new(
+        "POST",
+        &url,
+        [("content-type", content_type)].into_iter(),
+        SignableBody::Bytes(body),
+    )
+    .unwrap();
+
+    let (signing_instructions, _signature) = sign(signable_request, &signing_params)
+        .unwrap()
+        .into_parts();
+
+    let mut request = reqwest::Client::new()
+        .post(&url)
+        .header("content-type", content_type)
+        .body(body.to_vec());
+
+    for (name, value) in signing_instructions.headers() {
+        request = request.header(name, value);
+    }
+
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), 204);
+}
@@ -0,0 +1,68 @@
+use crate::common::{
+    TestServer, start_test_server_with_throttle, test_memory_database, test_sdk_config,
+};
+use aws_config::retry::RetryConfig;
+use aws_credential_types::Credentials;
+use std::collections::HashMap;
+
+mod common;
+
+/// Create a test server throttling requests using the `limits` with a client
+/// that doesn't retry throttled requests
+async fn test_throttle_server(
+    limits: HashMap<String, u32>,
+) -> (aws_sdk_secretsmanager::Client, TestServer) {
+    let db = test_memory_database().await;
+    let credentials = Credentials::for_tests();
+    let (server_address, abort_handle) =
+        start_test_server_with_throttle(db.clone(), credentials.clone(), limits).await;
+
+    let sdk_config = test_sdk_config(&format!("http://{server_address}/"), credentials);
+    let config = aws_sdk_secretsmanager::config::Builder::from(&sdk_config)
+        .retry_config(RetryConfig::disabled())
+        .build();
+    let client = aws_sdk_secretsmanager::Client::from_conf(config);
+
+    (client, TestServer { abort_handle, db })
+}
+
+/// Tests that requests over the limit for a target are throttled
+#[tokio::test]
+async fn test_throttle_over_limit() {
+    let (client, _server) = test_throttle_server(HashMap::from([(
+        "secretsmanager.CreateSecret".to_string(),
+        1,
+    )]))
+    .await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let err = client
+        .create_secret()
+        .name("other")
+        .secret_string("other")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.as_service_error()
+            .is_some_and(|value| value.meta().code() == Some("ThrottlingException"))
+    );
+
+    // Targets without a limit are not throttled
+    for _ in 0..5 {
+        client
+            .get_secret_value()
+            .secret_id("test")
+            .send()
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+use aws_sdk_secretsmanager::{
+    error::SdkError, operation::validate_resource_policy::ValidateResourcePolicyError,
+    types::error::ResourceNotFoundException,
+};
+
+use crate::common::test_server;
+
+mod common;
+
+const TEST_POLICY: &str = r#"{
+    "Version": "2012-10-17",
+    "Statement": [
+        {
+            "Sid": "AllowRead",
+            "Effect": "Allow",
+            "Principal": { "AWS": ["arn:aws:iam::1:role/test", "arn:aws:iam::1:role/other"] },
+            "Action": ["secretsmanager:GetSecretValue", "secretsmanager:DescribeSecret"],
+            "Resource": "*",
+            "Condition": { "StringEquals": { "secretsmanager:ResourceTag/team": "test" } }
+        }
+    ]
+}"#;
+
+/// Tests that a valid policy passes validation
+#[tokio::test]
+async fn test_validate_resource_policy_success() {
+    let (client, _server) = test_server().await;
+
+    let validate_response = client
+        .validate_resource_policy()
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    assert!(validate_response.policy_validation_passed());
+    assert!(validate_response.validation_errors().is_empty());
+}
+
+/// Tests that a valid policy passes validation against a secret
+#[tokio::test]
+async fn test_validate_resource_policy_secret_success() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let validate_response = client
+        .validate_resource_policy()
+        .secret_id("test")
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    assert!(validate_response.policy_validation_passed());
+}
+
+/// Tests that each structural problem with a policy is reported
+#[tokio::test]
+async fn test_validate_resource_policy_invalid() {
+    let (client, _server) = test_server().await;
+
+    let validate_response = client
+        .validate_resource_policy()
+        .resource_policy(
+            r#"{
+                "Version": "2020-01-01",
+                "Statement": [
+                    {
+                        "Effect": "Maybe",
+                        "Action": "GetSecretValue"
+                    }
+                ]
+            }"#,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert!(!validate_response.policy_validation_passed());
+
+    let check_names: Vec<&str> = validate_response
+        .validation_errors()
+        .iter()
+        .filter_map(|error| error.check_name())
+        .collect();
+
+    assert_eq!(
+        check_names,
+        ["VERSION", "EFFECT", "PRINCIPAL", "ACTION", "RESOURCE"]
+    );
+    assert!(
+        validate_response
+            .validation_errors()
+            .iter()
+            .all(|error| error.error_message().is_some())
+    );
+}
+
+/// Tests that conditions using operators that can't be evaluated fail validation
+#[tokio::test]
+async fn test_validate_resource_policy_unsupported_condition() {
+    let (client, _server) = test_server().await;
+
+    let validate_response = client
+        .validate_resource_policy()
+        .resource_policy(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [
+                    {
+                        "Effect": "Deny",
+                        "Principal": "*",
+                        "Action": "secretsmanager:GetSecretValue",
+                        "Resource": "*",
+                        "Condition": {
+                            "ForAnyValue:StringEquals": { "aws:PrincipalTag/team": "test" },
+                            "NumericLessThan": { "aws:MultiFactorAuthAge": "3600" }
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert!(!validate_response.policy_validation_passed());
+
+    let check_names: Vec<&str> = validate_response
+        .validation_errors()
+        .iter()
+        .filter_map(|error| error.check_name())
+        .collect();
+
+    assert_eq!(check_names, ["CONDITION", "CONDITION"]);
+}
+
+/// Tests that a policy which isn't valid JSON fails validation
+#[tokio::test]
+async fn test_validate_resource_policy_invalid_json() {
+    let (client, _server) = test_server().await;
+
+    let validate_response = client
+        .validate_resource_policy()
+        .resource_policy("{")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(!validate_response.policy_validation_passed());
+    assert_eq!(validate_response.validation_errors().len(), 1);
+}
+
+/// Tests that validating against an unknown secret will fail
+#[tokio::test]
+async fn test_validate_resource_policy_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let validate_err = client
+        .validate_resource_policy()
+        .secret_id("test")
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap_err();
+
+    let validate_err = match validate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match validate_err.into_err() {
+        ValidateResourcePolicyError::ResourceNotFoundException(error) => error,
+        error => {
+            panic!("expected ValidateResourcePolicyError::ResourceNotFoundException got {error:?}")
+        }
+    };
+}